@@ -0,0 +1,88 @@
+use std::{sync::Arc, time::Duration};
+
+use actix_web::{
+    web::{self, Data},
+    HttpMessage, HttpRequest, HttpResponse,
+};
+
+use crate::{
+    api::errors::JsonMessage,
+    services::{
+        api_keys::ApiKeyPrincipal,
+        audit::{AuditActor, AuditRecord},
+        auth::JwtAccessData,
+    },
+    state::AppState,
+};
+
+const AUDIT_ATTEMPTS: u64 = 3;
+
+/// Caller of an authenticated request, anonymous when it passed no auth
+/// middleware
+pub(super) fn request_actor(req: &HttpRequest) -> AuditActor {
+    let extensions = req.extensions();
+
+    if let Some(user) = extensions.get::<JwtAccessData>() {
+        return AuditActor::User(user.uid);
+    }
+
+    match extensions.get::<ApiKeyPrincipal>() {
+        Some(principal) => AuditActor::ServiceAccount(principal.service_account_uid),
+        None => AuditActor::Anonymous,
+    }
+}
+
+/// Stores the event of an action that took place. A write that keeps failing
+/// is logged and the response of the action goes out anyway: failing it
+/// would make clients retry an action that already happened.
+pub(super) async fn audit(state: &Data<AppState>, req: &HttpRequest, record: AuditRecord) {
+    if let Err(record) = store(state, req, record).await {
+        log::error!("Audit event was not stored: {:?}", record);
+    }
+}
+
+/// Stores the event before data it is about goes out, e.g. a passport read.
+/// A write that keeps failing fails the request with the returned response,
+/// so nothing is disclosed unrecorded.
+pub(super) async fn audit_disclosure(
+    state: &Data<AppState>,
+    req: &HttpRequest,
+    record: AuditRecord,
+) -> Result<(), HttpResponse> {
+    store(state, req, record).await.map_err(|record| {
+        log::error!("Audit event was not stored: {:?}", record);
+
+        HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        })
+    })
+}
+
+/// Gives the record back when every attempt failed
+async fn store(
+    state: &Data<AppState>,
+    req: &HttpRequest,
+    mut record: AuditRecord,
+) -> Result<(), Arc<AuditRecord>> {
+    record.ip = req
+        .connection_info()
+        .realip_remote_addr()
+        .map(|ip| ip.to_owned());
+
+    let record = Arc::new(record);
+
+    for attempt in 1..=AUDIT_ATTEMPTS {
+        let state = state.clone();
+        let record = record.clone();
+
+        if let Ok(Ok(())) = web::block(move || state.audit_service().record(&record)).await {
+            return Ok(());
+        }
+
+        if attempt < AUDIT_ATTEMPTS {
+            actix_web::rt::time::sleep(Duration::from_millis(50 * attempt)).await;
+        }
+    }
+
+    Err(record)
+}
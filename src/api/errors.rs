@@ -11,3 +11,15 @@ pub fn invalid_data() -> HttpResponse {
         message: "invalid_data",
     })
 }
+
+pub fn no_rights() -> HttpResponse {
+    HttpResponse::Forbidden().json(JsonMessage {
+        message: "no_rights",
+    })
+}
+
+pub fn account_blocked() -> HttpResponse {
+    HttpResponse::Forbidden().json(JsonMessage {
+        message: "account_blocked",
+    })
+}
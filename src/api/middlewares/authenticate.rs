@@ -1,5 +1,6 @@
 use std::{
     future::{ready, Ready},
+    rc::Rc,
     sync::Arc,
 };
 
@@ -7,18 +8,23 @@ use actix_web::{
     body::EitherBody,
     dev::{Service, ServiceRequest, ServiceResponse, Transform},
     http::header,
+    web::{self, Data},
     HttpMessage, HttpRequest,
 };
 
-use crate::services::auth::{AuthService, SecretsProvider};
+use crate::{
+    services::auth::{AuthService, SecretsProvider},
+    state::AppState,
+};
 use futures_util::future::LocalBoxFuture;
 
 pub struct JwtAuthService<S, T>
 where
     T: SecretsProvider,
 {
-    service: S,
+    service: Rc<S>,
     secrets_provider: Arc<T>,
+    api_keys: bool,
 }
 
 macro_rules! need_authorization {
@@ -37,13 +43,7 @@ macro_rules! need_authorization {
 }
 
 pub fn extract_auth_token(req: &HttpRequest) -> Option<&str> {
-    let auth_header = req.headers().get(header::AUTHORIZATION);
-
-    if auth_header.is_none() {
-        return None;
-    }
-
-    let auth_header = auth_header.unwrap();
+    let auth_header = req.headers().get(header::AUTHORIZATION)?;
 
     if auth_header.is_empty() {
         return None;
@@ -58,7 +58,7 @@ pub fn extract_auth_token(req: &HttpRequest) -> Option<&str> {
 
     let mut auth_value = auth_value.unwrap().split(' ');
     let auth_type = auth_value.next();
-    let token = auth_value.last();
+    let token = auth_value.next_back();
 
     if auth_type.is_none() || token.is_none() {
         return None;
@@ -74,9 +74,17 @@ pub fn extract_auth_token(req: &HttpRequest) -> Option<&str> {
     Some(token)
 }
 
+pub fn extract_api_key(req: &HttpRequest) -> Option<&str> {
+    req.headers()
+        .get("x-api-key")
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+}
+
 impl<S, B, T: SecretsProvider> Service<ServiceRequest> for JwtAuthService<S, T>
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>
+        + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -87,7 +95,26 @@ where
     actix_web::dev::forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        let token = extract_auth_token(&req.request());
+        if let Some(api_key) = extract_api_key(req.request()) {
+            if self.api_keys {
+                return self.call_with_api_key(api_key.to_owned(), req);
+            }
+
+            log::info!("{} {}: API keys are not accepted", req.method(), req.path());
+
+            let res = req.into_response(
+                actix_web::HttpResponse::Unauthorized()
+                    .json(crate::api::errors::JsonMessage {
+                        message: "api_key_not_allowed",
+                    })
+                    .map_into_boxed_body(),
+            );
+            return Box::pin(async move {
+                Ok(res.map_body(|_, body| actix_web::body::EitherBody::right(body)))
+            });
+        }
+
+        let token = extract_auth_token(req.request());
 
         if token.is_none() {
             need_authorization!(req);
@@ -111,6 +138,41 @@ where
         }
 
         let data = data.unwrap();
+        let denied = req
+            .app_data::<Data<AppState>>()
+            .map(|state| {
+                state
+                    .redis()
+                    .is_access_token_denied(&data.jti, &data.sid, &data.uid, data.issued_at_ms())
+            });
+
+        match denied {
+            Some(Ok(false)) => (),
+            Some(Ok(true)) => {
+                let res = req.into_response(
+                    actix_web::HttpResponse::Unauthorized()
+                        .json(crate::api::errors::JsonMessage {
+                            message: "token_revoked",
+                        })
+                        .map_into_boxed_body(),
+                );
+                return Box::pin(async move {
+                    Ok(res.map_body(|_, body| actix_web::body::EitherBody::right(body)))
+                });
+            }
+            _ => {
+                let res = req.into_response(
+                    actix_web::HttpResponse::InternalServerError()
+                        .json(crate::api::errors::JsonMessage {
+                            message: "internal_error",
+                        })
+                        .map_into_boxed_body(),
+                );
+                return Box::pin(async move {
+                    Ok(res.map_body(|_, body| actix_web::body::EitherBody::right(body)))
+                });
+            }
+        }
 
         req.extensions_mut().insert(data);
 
@@ -123,22 +185,95 @@ where
     }
 }
 
+impl<S, B, T: SecretsProvider> JwtAuthService<S, T>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>
+        + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    /// Integrations authenticate with an API key instead of a JWT, the key's
+    /// service account ends up in the request extensions. Only
+    /// `RequirePermission` grants it anything, handlers acting on the current
+    /// user find no user and refuse it.
+    fn call_with_api_key(
+        &self,
+        api_key: String,
+        req: ServiceRequest,
+    ) -> LocalBoxFuture<'static, Result<ServiceResponse<EitherBody<B>>, actix_web::Error>> {
+        let service = self.service.clone();
+
+        Box::pin(async move {
+            let principal = match req.app_data::<Data<AppState>>().cloned() {
+                Some(state) => web::block(move || state.api_key_service().authenticate(&api_key))
+                    .await
+                    .ok(),
+                None => None,
+            };
+
+            let response = match principal {
+                Some(Ok(Some(principal))) => {
+                    log::debug!(
+                        "API key {} of service account {}",
+                        principal.key_uid,
+                        principal.service_account_uid
+                    );
+                    req.extensions_mut().insert(principal);
+
+                    let res = service.call(req).await?;
+
+                    return Ok(res.map_body(|_, body| EitherBody::left(body)));
+                }
+                Some(Ok(None)) => actix_web::HttpResponse::Unauthorized().json(
+                    crate::api::errors::JsonMessage {
+                        message: "invalid_api_key",
+                    },
+                ),
+                _ => actix_web::HttpResponse::InternalServerError().json(
+                    crate::api::errors::JsonMessage {
+                        message: "internal_error",
+                    },
+                ),
+            };
+
+            Ok(req
+                .into_response(response.map_into_boxed_body())
+                .map_body(|_, body| EitherBody::right(body)))
+        })
+    }
+}
+
 pub struct JwtAuth<T>
 where
     T: SecretsProvider,
 {
     secrets_provider: Arc<T>,
+    api_keys: bool,
 }
 
 impl<T: SecretsProvider> JwtAuth<T> {
+    /// Users only, requests with an API key are refused
     pub fn new(secrets_provider: Arc<T>) -> Self {
-        Self { secrets_provider }
+        Self {
+            secrets_provider,
+            api_keys: false,
+        }
+    }
+
+    /// Also accepts API keys. Only for scopes where every route either names
+    /// its permission with `RequirePermission` or acts on the current user.
+    pub fn with_api_keys(self) -> Self {
+        Self {
+            api_keys: true,
+            ..self
+        }
     }
 }
 
 impl<S, B, T: SecretsProvider> Transform<S, ServiceRequest> for JwtAuth<T>
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>
+        + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -150,8 +285,9 @@ where
 
     fn new_transform(&self, service: S) -> Self::Future {
         ready(Ok(JwtAuthService {
-            service,
+            service: Rc::new(service),
             secrets_provider: self.secrets_provider.clone(),
+            api_keys: self.api_keys,
         }))
     }
 }
@@ -0,0 +1,140 @@
+use std::future::{ready, Ready};
+
+use actix_web::{
+    body::EitherBody,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    web::Data,
+    HttpMessage,
+};
+use futures_util::future::LocalBoxFuture;
+
+use crate::{
+    api::audit::{audit, request_actor},
+    services::{
+        api_keys::ApiKeyPrincipal,
+        audit::{AuditAction, AuditOutcome, AuditRecord},
+        auth::JwtAccessData,
+        permissions::Permission,
+    },
+    state::AppState,
+};
+
+pub struct RequirePermissionService<S> {
+    service: S,
+    permission: Permission,
+}
+
+impl<S, B> Service<ServiceRequest> for RequirePermissionService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+    type Response = ServiceResponse<EitherBody<B>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let granted = {
+            let extensions = req.extensions();
+
+            extensions
+                .get::<JwtAccessData>()
+                .map(|user| self.permission.is_granted_to(user.role))
+                .or_else(|| {
+                    extensions
+                        .get::<ApiKeyPrincipal>()
+                        .map(|principal| principal.scopes.contains(&self.permission))
+                })
+        };
+
+        let response = match granted {
+            Some(true) => None,
+            Some(false) => Some(crate::api::errors::no_rights()),
+            None => Some(actix_web::HttpResponse::Unauthorized().json(
+                crate::api::errors::JsonMessage {
+                    message: "need_authorization",
+                },
+            )),
+        };
+
+        if let Some(response) = response {
+            log::info!(
+                "{} {}: permission {} denied",
+                req.method(),
+                req.path(),
+                self.permission.as_str()
+            );
+
+            let denial = match (granted, req.app_data::<Data<AppState>>()) {
+                (Some(false), Some(state)) => Some((
+                    state.clone(),
+                    AuditRecord::new(
+                        request_actor(req.request()),
+                        AuditAction::PermissionDenied,
+                        AuditOutcome::Denied,
+                    )
+                    .details(format!(
+                        "{} on {} {}",
+                        self.permission.as_str(),
+                        req.method(),
+                        req.path()
+                    )),
+                )),
+                _ => None,
+            };
+
+            return Box::pin(async move {
+                if let Some((state, record)) = denial {
+                    audit(&state, req.request(), record).await;
+                }
+
+                let res = req.into_response(response.map_into_boxed_body());
+
+                Ok(res.map_body(|_, body| EitherBody::right(body)))
+            });
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let res = fut.await?;
+
+            Ok(res.map_body(|_, body| EitherBody::left(body)))
+        })
+    }
+}
+
+/// Lets the request through only if the role of the authenticated user, or
+/// the scopes of the API key, have the permission. Goes inside `JwtAuth`, on a scope or on a single handler:
+/// `#[delete("", wrap = "RequirePermission::new(Permission::LawsDelete)")]`.
+pub struct RequirePermission {
+    permission: Permission,
+}
+
+impl RequirePermission {
+    pub fn new(permission: Permission) -> Self {
+        Self { permission }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequirePermission
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+    type Transform = RequirePermissionService<S>;
+    type InitError = ();
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequirePermissionService {
+            service,
+            permission: self.permission,
+        }))
+    }
+}
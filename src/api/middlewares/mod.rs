@@ -1 +1,2 @@
 pub(super) mod authenticate;
+pub(super) mod authorize;
@@ -1,6 +1,8 @@
+mod audit;
 pub mod errors;
 mod middlewares;
 mod v1;
+mod well_known;
 
 use std::sync::Arc;
 
@@ -13,3 +15,7 @@ pub(super) fn configure(config: Arc<Config>) -> impl Fn(&mut web::ServiceConfig)
         cfg.service(web::scope("/v1").configure(v1::configure(config.clone())));
     }
 }
+
+pub(super) fn configure_well_known(cfg: &mut web::ServiceConfig) {
+    cfg.service(well_known::jwks);
+}
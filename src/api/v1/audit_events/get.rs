@@ -0,0 +1,28 @@
+use actix_web::{
+    get,
+    web::{self, Data, Query},
+    HttpResponse, Responder,
+};
+
+use crate::{
+    api::errors::{invalid_data, JsonMessage},
+    db::DbError,
+    services::{audit::AuditServiceError, dto::audit::AuditEventsQuery},
+    state::AppState,
+};
+
+#[get("")]
+pub(super) async fn get_audit_events(
+    query: Query<AuditEventsQuery>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let block_result = web::block(move || state.audit_service().get_events(&query)).await;
+
+    match block_result {
+        Ok(Ok(events)) => HttpResponse::Ok().json(events),
+        Ok(Err(DbError::Execution(AuditServiceError::InvalidPage))) => invalid_data(),
+        _ => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
@@ -0,0 +1,58 @@
+mod patch;
+
+use std::sync::Arc;
+
+use crate::{
+    api::errors::JsonMessage,
+    config::Config,
+    db::DbError,
+    services::auth::AuthServiceError,
+};
+
+use actix_web::{web, HttpResponse};
+
+pub(super) fn configure(_: Arc<Config>) -> impl Fn(&mut web::ServiceConfig) {
+    move |cfg| {
+        cfg.service(patch::change_password)
+            .service(patch::change_username)
+            .service(patch::change_email);
+    }
+}
+
+fn change_error_response(err: DbError<AuthServiceError<()>>) -> HttpResponse {
+    match err {
+        DbError::Execution(AuthServiceError::InvalidPassword) => {
+            HttpResponse::Forbidden().json(JsonMessage {
+                message: "invalid_password",
+            })
+        }
+        DbError::Execution(AuthServiceError::ExternalAccount) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "external_account",
+            })
+        }
+        DbError::Execution(AuthServiceError::AlreadyExists) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "already_exists",
+            })
+        }
+        DbError::Execution(AuthServiceError::AccountLocked) => {
+            HttpResponse::Locked().json(JsonMessage {
+                message: "account_locked",
+            })
+        }
+        DbError::Execution(AuthServiceError::TooManyAttempts) => {
+            HttpResponse::TooManyRequests().json(JsonMessage {
+                message: "too_many_attempts",
+            })
+        }
+        DbError::Execution(AuthServiceError::UserNotFound) => {
+            HttpResponse::NotFound().json(JsonMessage {
+                message: "user_not_found",
+            })
+        }
+        _ => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
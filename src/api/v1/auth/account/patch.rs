@@ -0,0 +1,227 @@
+use actix_web::{
+    patch,
+    web::{self, Data, Json},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use validator::Validate;
+
+use super::change_error_response;
+use crate::{
+    api::{
+        audit::audit,
+        errors::{invalid_data, JsonMessage},
+    },
+    services::{
+        audit::{AuditAction, AuditOutcome, AuditRecord},
+        auth::JwtAccessData,
+        dto::auth::{ChangeEmailDto, ChangePasswordDto, ChangeUsernameDto},
+    },
+    state::AppState,
+};
+
+#[patch("password")]
+pub(super) async fn change_password(
+    req: HttpRequest,
+    json: Json<ChangePasswordDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let uid = user.uid;
+    let clonned_state = state.clone();
+    let block_result = web::block(move || {
+        state.auth_service().change_password(
+            &user.uid,
+            &json.current_password,
+            &json.new_password,
+            state.config(),
+            state.login_throttle_service(),
+        )
+    })
+    .await;
+
+    match block_result {
+        Ok(Ok(_)) => (),
+        Ok(Err(err)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::own_account(uid, AuditAction::PasswordChange, AuditOutcome::Failure),
+            )
+            .await;
+
+            return change_error_response(err);
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError().json(JsonMessage {
+                message: "internal_error",
+            })
+        }
+    }
+
+    // Every other device has to log in with the new password
+    if clonned_state
+        .session_service()
+        .revoke_others(&user.uid, &user.sid)
+        .is_err()
+    {
+        return HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        });
+    }
+
+    audit(
+        &clonned_state,
+        &req,
+        AuditRecord::own_account(uid, AuditAction::PasswordChange, AuditOutcome::Success),
+    )
+    .await;
+
+    HttpResponse::Ok().json(JsonMessage { message: "ok" })
+}
+
+#[patch("username")]
+pub(super) async fn change_username(
+    req: HttpRequest,
+    json: Json<ChangeUsernameDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let uid = user.uid;
+    let clonned_state = state.clone();
+    let block_result = web::block(move || {
+        state.auth_service().change_username(
+            &user.uid,
+            &json.current_password,
+            &json.username,
+            state.login_throttle_service(),
+        )
+    })
+    .await;
+
+    match block_result {
+        Ok(Ok(_)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::own_account(uid, AuditAction::UsernameChange, AuditOutcome::Success),
+            )
+            .await;
+
+            HttpResponse::Ok().json(JsonMessage { message: "ok" })
+        }
+        Ok(Err(err)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::own_account(uid, AuditAction::UsernameChange, AuditOutcome::Failure),
+            )
+            .await;
+
+            change_error_response(err)
+        }
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[patch("email")]
+pub(super) async fn change_email(
+    req: HttpRequest,
+    json: Json<ChangeEmailDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let uid = user.uid;
+    let clonned_state = state.clone();
+    let block_result = web::block(move || {
+        state.auth_service().change_email(
+            &user.uid,
+            &json.current_password,
+            &json.email,
+            state.login_throttle_service(),
+        )
+    })
+    .await;
+
+    match block_result {
+        Ok(Ok(_)) => (),
+        Ok(Err(err)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::own_account(uid, AuditAction::EmailChange, AuditOutcome::Failure),
+            )
+            .await;
+
+            return change_error_response(err);
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError().json(JsonMessage {
+                message: "internal_error",
+            })
+        }
+    }
+
+    audit(
+        &clonned_state,
+        &req,
+        AuditRecord::own_account(uid, AuditAction::EmailChange, AuditOutcome::Success),
+    )
+    .await;
+
+    let mail_result = web::block(move || {
+        let verification_service = clonned_state.verification_service();
+
+        // Links sent to the previous address must not work for the new one
+        if verification_service.revoke_tokens(&uid).is_err() {
+            log::error!("Verification tokens of {} were not revoked", uid);
+        }
+
+        verification_service.send_email_verification(&uid, clonned_state.config())
+    })
+    .await;
+
+    // The address is changed already, the email can be sent again on request
+    if !matches!(mail_result, Ok(Ok(_))) {
+        log::error!("Verification email for {} was not sent", uid);
+    }
+
+    HttpResponse::Ok().json(JsonMessage { message: "ok" })
+}
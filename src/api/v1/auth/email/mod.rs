@@ -0,0 +1,18 @@
+mod post;
+
+use std::sync::Arc;
+
+use crate::{api::middlewares::authenticate::JwtAuth, config::Config};
+
+use actix_web::web;
+
+pub(super) fn configure(config: Arc<Config>) -> impl Fn(&mut web::ServiceConfig) {
+    move |cfg| {
+        cfg.service(
+            web::scope("/resend")
+                .wrap(JwtAuth::new(config.clone()))
+                .service(post::resend_verification),
+        )
+        .service(post::verify_email);
+    }
+}
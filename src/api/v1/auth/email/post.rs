@@ -0,0 +1,111 @@
+use actix_web::{
+    post,
+    web::{self, Data, Json},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use validator::Validate;
+
+use crate::{
+    api::{
+        audit::audit,
+        errors::{invalid_data, JsonMessage},
+    },
+    db::DbError,
+    services::{
+        audit::{AuditAction, AuditOutcome, AuditRecord},
+        auth::JwtAccessData,
+        dto::verification::VerifyEmailDto,
+        verification::VerificationServiceError,
+    },
+    state::AppState,
+};
+
+#[post("verify")]
+pub(super) async fn verify_email(
+    req: HttpRequest,
+    json: Json<VerifyEmailDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let clonned_state = state.clone();
+    let block_result =
+        web::block(move || state.verification_service().verify_email(&json.token)).await;
+
+    match block_result {
+        Ok(Ok(uid)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::own_account(uid, AuditAction::EmailVerify, AuditOutcome::Success),
+            )
+            .await;
+
+            HttpResponse::Ok().json(JsonMessage { message: "ok" })
+        }
+        Ok(Err(DbError::Execution(VerificationServiceError::InvalidToken))) => {
+            HttpResponse::BadRequest().json(JsonMessage {
+                message: "invalid_token",
+            })
+        }
+        _ => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[post("")]
+pub(super) async fn resend_verification(
+    req: HttpRequest,
+    state: Data<AppState>,
+) -> impl Responder {
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let uid = user.uid;
+    let clonned_state = state.clone();
+    let block_result = web::block(move || {
+        state
+            .verification_service()
+            .send_email_verification(&user.uid, state.config())
+    })
+    .await;
+
+    match block_result {
+        Ok(Ok(_)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::own_account(
+                    uid,
+                    AuditAction::EmailVerificationResend,
+                    AuditOutcome::Success,
+                ),
+            )
+            .await;
+
+            HttpResponse::Ok().json(JsonMessage { message: "ok" })
+        }
+        Ok(Err(DbError::Execution(VerificationServiceError::AlreadyVerified))) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "already_verified",
+            })
+        }
+        Ok(Err(DbError::Execution(VerificationServiceError::UserNotFound))) => {
+            HttpResponse::NotFound().json(JsonMessage {
+                message: "user_not_found",
+            })
+        }
+        _ => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
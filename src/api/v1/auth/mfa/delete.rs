@@ -0,0 +1,72 @@
+use actix_web::{
+    delete,
+    web::{self, Data, Json},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use validator::Validate;
+
+use super::mfa_error_response;
+use crate::{
+    api::{
+        audit::audit,
+        errors::{invalid_data, JsonMessage},
+    },
+    services::{
+        audit::{AuditAction, AuditOutcome, AuditRecord},
+        auth::JwtAccessData,
+        dto::mfa::MfaCodeDto,
+        mfa::MfaPolicyProvider,
+    },
+    state::AppState,
+};
+
+#[delete("")]
+pub(super) async fn disable_totp(
+    req: HttpRequest,
+    json: Json<MfaCodeDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+
+    if state.config().mfa_required_for(user.role) {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "mfa_required_for_role",
+        });
+    }
+
+    let uid = user.uid;
+    let clonned_state = state.clone();
+    let block_result =
+        web::block(move || state
+            .mfa_service()
+            .disable(&user.uid, &json.code, state.config())).await;
+
+    match block_result {
+        Ok(Ok(_)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::own_account(uid, AuditAction::MfaDisable, AuditOutcome::Success),
+            )
+            .await;
+
+            HttpResponse::Ok().json(JsonMessage { message: "ok" })
+        }
+        Ok(Err(err)) => mfa_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
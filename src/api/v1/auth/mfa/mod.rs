@@ -0,0 +1,62 @@
+mod delete;
+mod post;
+
+use std::sync::Arc;
+
+use crate::{
+    api::{errors::JsonMessage, middlewares::authenticate::JwtAuth},
+    config::Config,
+    db::DbError,
+    services::mfa::MfaServiceError,
+};
+
+use actix_web::{web, HttpResponse};
+
+pub(super) fn configure(config: Arc<Config>) -> impl Fn(&mut web::ServiceConfig) {
+    move |cfg| {
+        cfg.service(
+            web::scope("/totp")
+                .wrap(JwtAuth::new(config.clone()))
+                .service(post::setup_totp)
+                .service(post::confirm_totp)
+                .service(post::regenerate_recovery_codes)
+                .service(delete::disable_totp),
+        )
+        .service(post::verify)
+        .service(post::enroll)
+        .service(post::confirm_enrollment);
+    }
+}
+
+fn mfa_error_response(err: DbError<MfaServiceError>) -> HttpResponse {
+    match err {
+        DbError::Execution(MfaServiceError::InvalidCode) => {
+            HttpResponse::BadRequest().json(JsonMessage {
+                message: "invalid_code",
+            })
+        }
+        DbError::Execution(MfaServiceError::AlreadyEnabled) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "mfa_already_enabled",
+            })
+        }
+        DbError::Execution(MfaServiceError::NotEnabled) => {
+            HttpResponse::BadRequest().json(JsonMessage {
+                message: "mfa_not_enabled",
+            })
+        }
+        DbError::Execution(MfaServiceError::SetupNotStarted) => {
+            HttpResponse::BadRequest().json(JsonMessage {
+                message: "mfa_setup_not_started",
+            })
+        }
+        DbError::Execution(MfaServiceError::UserNotFound) => {
+            HttpResponse::NotFound().json(JsonMessage {
+                message: "user_not_found",
+            })
+        }
+        _ => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
@@ -0,0 +1,332 @@
+use actix_web::{
+    post,
+    web::{self, Data, Json},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use validator::Validate;
+
+use super::{
+    super::tokens::{authorized_response_with_recovery_codes, start_session, store_tokens},
+    mfa_error_response,
+};
+use crate::{
+    api::{
+        audit::audit,
+        errors::{account_blocked, invalid_data, JsonMessage},
+    },
+    db::DbError,
+    services::{
+        audit::{AuditAction, AuditOutcome, AuditRecord},
+        auth::{AuthServiceError, JwtAccessData, LoginAttemptsError, LoginAttemptsGuard},
+        dto::mfa::{
+            MfaCodeDto, MfaPendingCodeDto, MfaPendingData, MfaPendingDto,
+            RecoveryCodesResponse,
+        },
+        mfa::{MfaPolicyProvider, MfaServiceError},
+    },
+    state::AppState,
+};
+
+fn internal_error() -> HttpResponse {
+    HttpResponse::InternalServerError().json(JsonMessage {
+        message: "internal_error",
+    })
+}
+
+fn find_pending(state: &AppState, mfa_token: &str) -> Result<MfaPendingData, HttpResponse> {
+    match state.mfa_service().find_pending(mfa_token) {
+        Ok(Some(pending)) => Ok(pending),
+        Ok(None) => Err(HttpResponse::Unauthorized().json(JsonMessage {
+            message: "mfa_token_not_found",
+        })),
+        Err(_) => Err(internal_error()),
+    }
+}
+
+/// Finishes a login parked by the second factor
+async fn finish_login(
+    req: &HttpRequest,
+    state: Data<AppState>,
+    mfa_token: &str,
+    pending: MfaPendingData,
+    recovery_codes: Option<Vec<String>>,
+) -> HttpResponse {
+    let _ = state.mfa_service().remove_pending(mfa_token);
+    state.login_throttle_service().succeeded(&pending.uid);
+    audit(
+        &state,
+        req,
+        AuditRecord::own_account(pending.uid, AuditAction::LoginMfa, AuditOutcome::Success),
+    )
+    .await;
+
+    let clonned_state = state.clone();
+    let block_result =
+        web::block(move || state.auth_service().issue_tokens(&pending.uid, state.config())).await;
+
+    let tokens = match block_result {
+        Ok(Ok(tokens)) => tokens,
+        Ok(Err(DbError::Execution(AuthServiceError::AccountBlocked))) => {
+            return account_blocked()
+        }
+        _ => return internal_error(),
+    };
+
+    store_tokens(&clonned_state, &tokens);
+    start_session(&clonned_state, req, &tokens, pending.device);
+    let expires = tokens.exp;
+
+    authorized_response_with_recovery_codes(tokens, expires, recovery_codes)
+}
+
+#[post("verify")]
+pub(super) async fn verify(
+    req: HttpRequest,
+    json: Json<MfaPendingCodeDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let pending = match find_pending(&state, &json.mfa_token) {
+        Ok(pending) => pending,
+        Err(response) => return response,
+    };
+
+    if pending.enrollment {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "mfa_enrollment_required",
+        });
+    }
+
+    match state.login_throttle_service().check(&pending.uid) {
+        Ok(_) => (),
+        Err(LoginAttemptsError::Locked) => {
+            return HttpResponse::Locked().json(JsonMessage {
+                message: "account_locked",
+            })
+        }
+        Err(LoginAttemptsError::Backoff) => {
+            return HttpResponse::TooManyRequests().json(JsonMessage {
+                message: "too_many_attempts",
+            })
+        }
+        Err(LoginAttemptsError::Storage) => return internal_error(),
+    }
+
+    let uid = pending.uid;
+    let clonned_state = state.clone();
+    let code = json.code.clone();
+    let block_result = web::block(move || clonned_state.mfa_service().verify(
+            &uid,
+            &code,
+            clonned_state.config(),
+        )).await;
+
+    match block_result {
+        Ok(Ok(_)) => finish_login(&req, state, &json.mfa_token, pending, None).await,
+        Ok(Err(DbError::Execution(MfaServiceError::InvalidCode))) => {
+            state.login_throttle_service().failed(&uid);
+            audit(
+                &state,
+                &req,
+                AuditRecord::own_account(uid, AuditAction::LoginMfa, AuditOutcome::Failure),
+            )
+            .await;
+
+            HttpResponse::BadRequest().json(JsonMessage {
+                message: "invalid_code",
+            })
+        }
+        Ok(Err(err)) => mfa_error_response(err),
+        Err(_) => internal_error(),
+    }
+}
+
+#[post("enroll")]
+pub(super) async fn enroll(json: Json<MfaPendingDto>, state: Data<AppState>) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let pending = match find_pending(&state, &json.mfa_token) {
+        Ok(pending) => pending,
+        Err(response) => return response,
+    };
+
+    if !pending.enrollment {
+        return HttpResponse::Conflict().json(JsonMessage {
+            message: "mfa_already_enabled",
+        });
+    }
+
+    let block_result = web::block(move || {
+        state
+            .mfa_service()
+            .start_setup(&pending.uid, state.config().mfa_issuer(), state.config())
+    })
+    .await;
+
+    match block_result {
+        Ok(Ok(setup)) => HttpResponse::Ok().json(setup),
+        Ok(Err(err)) => mfa_error_response(err),
+        Err(_) => internal_error(),
+    }
+}
+
+#[post("enroll/confirm")]
+pub(super) async fn confirm_enrollment(
+    req: HttpRequest,
+    json: Json<MfaPendingCodeDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let pending = match find_pending(&state, &json.mfa_token) {
+        Ok(pending) => pending,
+        Err(response) => return response,
+    };
+
+    if !pending.enrollment {
+        return HttpResponse::Conflict().json(JsonMessage {
+            message: "mfa_already_enabled",
+        });
+    }
+
+    let uid = pending.uid;
+    let clonned_state = state.clone();
+    let code = json.code.clone();
+    let block_result =
+        web::block(move || clonned_state
+            .mfa_service()
+            .confirm_setup(&uid, &code, clonned_state.config())).await;
+
+    match block_result {
+        Ok(Ok(recovery_codes)) => {
+            audit(
+                &state,
+                &req,
+                AuditRecord::own_account(uid, AuditAction::MfaEnable, AuditOutcome::Success),
+            )
+            .await;
+
+            finish_login(&req, state, &json.mfa_token, pending, Some(recovery_codes)).await
+        }
+        Ok(Err(err)) => mfa_error_response(err),
+        Err(_) => internal_error(),
+    }
+}
+
+#[post("")]
+pub(super) async fn setup_totp(req: HttpRequest, state: Data<AppState>) -> impl Responder {
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let block_result = web::block(move || {
+        state
+            .mfa_service()
+            .start_setup(&user.uid, state.config().mfa_issuer(), state.config())
+    })
+    .await;
+
+    match block_result {
+        Ok(Ok(setup)) => HttpResponse::Ok().json(setup),
+        Ok(Err(err)) => mfa_error_response(err),
+        Err(_) => internal_error(),
+    }
+}
+
+#[post("confirm")]
+pub(super) async fn confirm_totp(
+    req: HttpRequest,
+    json: Json<MfaCodeDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let uid = user.uid;
+    let clonned_state = state.clone();
+    let block_result =
+        web::block(move || state
+            .mfa_service()
+            .confirm_setup(&user.uid, &json.code, state.config())).await;
+
+    match block_result {
+        Ok(Ok(recovery_codes)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::own_account(uid, AuditAction::MfaEnable, AuditOutcome::Success),
+            )
+            .await;
+
+            HttpResponse::Ok().json(RecoveryCodesResponse { recovery_codes })
+        }
+        Ok(Err(err)) => mfa_error_response(err),
+        Err(_) => internal_error(),
+    }
+}
+
+#[post("recovery-codes")]
+pub(super) async fn regenerate_recovery_codes(
+    req: HttpRequest,
+    json: Json<MfaCodeDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let uid = user.uid;
+    let clonned_state = state.clone();
+    let block_result = web::block(move || {
+        state
+            .mfa_service()
+            .regenerate_recovery_codes(&user.uid, &json.code, state.config())
+    })
+    .await;
+
+    match block_result {
+        Ok(Ok(recovery_codes)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::own_account(uid, AuditAction::MfaRecoveryCodes, AuditOutcome::Success),
+            )
+            .await;
+
+            HttpResponse::Ok().json(RecoveryCodesResponse { recovery_codes })
+        }
+        Ok(Err(err)) => mfa_error_response(err),
+        Err(_) => internal_error(),
+    }
+}
@@ -1,14 +1,35 @@
+mod account;
+mod email;
+mod mfa;
+mod oidc;
+mod password;
 mod post;
+mod sessions;
+mod tokens;
 
 use std::sync::Arc;
 
-use crate::config::Config;
+use crate::{api::middlewares::authenticate::JwtAuth, config::Config};
 
 use actix_web::web;
 
-pub(super) fn configure(_: Arc<Config>) -> impl Fn(&mut web::ServiceConfig) {
+pub(super) fn configure(config: Arc<Config>) -> impl Fn(&mut web::ServiceConfig) {
     move |cfg| {
         cfg
+            .service(
+                web::scope("/account")
+                    .wrap(JwtAuth::new(config.clone()))
+                    .configure(account::configure(config.clone())),
+            )
+            .service(web::scope("/email").configure(email::configure(config.clone())))
+            .service(web::scope("/password").configure(password::configure(config.clone())))
+            .service(web::scope("/oidc").configure(oidc::configure(config.clone())))
+            .service(web::scope("/mfa").configure(mfa::configure(config.clone())))
+            .service(
+                web::scope("/sessions")
+                    .wrap(JwtAuth::new(config.clone()))
+                    .configure(sessions::configure(config.clone())),
+            )
             .service(post::register)
             .service(post::authorize)
             .service(post::refresh_tokens)
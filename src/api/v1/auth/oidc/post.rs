@@ -0,0 +1,212 @@
+use actix_web::{
+    post,
+    web::{self, Data, Json},
+    HttpRequest, HttpResponse, Responder,
+};
+use validator::Validate;
+
+use super::super::tokens::{
+    authorized_response, mfa_required_response, start_session, store_tokens,
+};
+use crate::{
+    api::{
+        audit::audit,
+        errors::{account_blocked, invalid_data, JsonMessage},
+    },
+    db::DbError,
+    services::{
+        audit::{AuditAction, AuditActor, AuditOutcome, AuditRecord},
+        auth::{AuthServiceError, AuthorizationResult},
+        dto::oidc::{OidcAuthorizationResponse, OidcAuthorizeDto, OidcCallbackDto},
+        oidc::OidcServiceError,
+    },
+    state::AppState,
+};
+
+fn oidc_error_response(err: OidcServiceError) -> HttpResponse {
+    match err {
+        OidcServiceError::Disabled => HttpResponse::NotFound().json(JsonMessage {
+            message: "oidc_disabled",
+        }),
+        OidcServiceError::InvalidState => HttpResponse::BadRequest().json(JsonMessage {
+            message: "invalid_state",
+        }),
+        OidcServiceError::TokenExchange | OidcServiceError::InvalidIdToken => {
+            HttpResponse::Unauthorized().json(JsonMessage {
+                message: "oidc_login_failed",
+            })
+        }
+        OidcServiceError::MissingEmail => HttpResponse::BadRequest().json(JsonMessage {
+            message: "email_not_provided",
+        }),
+        OidcServiceError::Discovery => HttpResponse::BadGateway().json(JsonMessage {
+            message: "identity_provider_unavailable",
+        }),
+        OidcServiceError::Cache => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[post("authorize")]
+pub(super) async fn authorize(
+    json: Json<OidcAuthorizeDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let block_result = web::block(move || {
+        state
+            .oidc_service()
+            .authorization_url(json.into_inner().device)
+    })
+    .await;
+
+    match block_result {
+        Ok(Ok(authorization_url)) => {
+            HttpResponse::Ok().json(OidcAuthorizationResponse { authorization_url })
+        }
+        Ok(Err(err)) => oidc_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[post("callback")]
+pub(super) async fn callback(
+    req: HttpRequest,
+    json: Json<OidcCallbackDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let internal_error = HttpResponse::InternalServerError().json(JsonMessage {
+        message: "internal_error",
+    });
+    let clonned_state = state.clone();
+    let block_result =
+        web::block(move || state.oidc_service().exchange(&json.code, &json.state)).await;
+
+    if block_result.is_err() {
+        return internal_error;
+    }
+
+    let (identity, device) = match block_result.unwrap() {
+        Ok(result) => result,
+        Err(err) => {
+            if let OidcServiceError::InvalidState
+            | OidcServiceError::TokenExchange
+            | OidcServiceError::InvalidIdToken = err
+            {
+                audit(
+                    &clonned_state,
+                    &req,
+                    AuditRecord::new(
+                        AuditActor::Anonymous,
+                        AuditAction::LoginOidc,
+                        AuditOutcome::Failure,
+                    ),
+                )
+                .await;
+            }
+
+            return oidc_error_response(err);
+        }
+    };
+    let issuer = identity.issuer.clone();
+    let login_failed = |outcome, reason: &str| {
+        AuditRecord::new(AuditActor::Anonymous, AuditAction::LoginOidc, outcome)
+            .details(format!("{}: {}", reason, issuer))
+    };
+
+    let state = clonned_state.clone();
+    let block_result = web::block(move || {
+        state.auth_service().authorize_external_user(
+            &identity,
+            state.config(),
+            state.login_throttle_service(),
+        )
+    })
+    .await;
+
+    if block_result.is_err() {
+        return internal_error;
+    }
+
+    let tokens = match block_result.unwrap() {
+        Ok(AuthorizationResult::Authorized(tokens)) => tokens,
+        Ok(AuthorizationResult::MfaRequired { uid, enrollment }) => {
+            return mfa_required_response(&clonned_state, uid, device, enrollment)
+        }
+        Err(DbError::Execution(AuthServiceError::AlreadyExists)) => {
+            return HttpResponse::Conflict().json(JsonMessage {
+                message: "already_exists",
+            })
+        }
+        Err(DbError::Execution(AuthServiceError::ExternalLinkNotAllowed)) => {
+            audit(
+                &clonned_state,
+                &req,
+                login_failed(AuditOutcome::Denied, "external_link_not_allowed"),
+            )
+            .await;
+
+            return HttpResponse::Conflict().json(JsonMessage {
+                message: "external_link_not_allowed",
+            });
+        }
+        Err(DbError::Execution(AuthServiceError::AccountLocked)) => {
+            audit(
+                &clonned_state,
+                &req,
+                login_failed(AuditOutcome::Denied, "account_locked"),
+            )
+            .await;
+
+            return HttpResponse::Locked().json(JsonMessage {
+                message: "account_locked",
+            });
+        }
+        Err(DbError::Execution(AuthServiceError::TooManyAttempts)) => {
+            audit(
+                &clonned_state,
+                &req,
+                login_failed(AuditOutcome::Denied, "too_many_attempts"),
+            )
+            .await;
+
+            return HttpResponse::TooManyRequests().json(JsonMessage {
+                message: "too_many_attempts",
+            });
+        }
+        Err(DbError::Execution(AuthServiceError::AccountBlocked)) => {
+            audit(
+                &clonned_state,
+                &req,
+                login_failed(AuditOutcome::Denied, "account_blocked"),
+            )
+            .await;
+
+            return account_blocked();
+        }
+        Err(_) => return internal_error,
+    };
+
+    store_tokens(&clonned_state, &tokens);
+    start_session(&clonned_state, &req, &tokens, device);
+    audit(
+        &clonned_state,
+        &req,
+        AuditRecord::own_account(tokens.uid, AuditAction::LoginOidc, AuditOutcome::Success)
+            .details(issuer),
+    )
+    .await;
+    let expires = tokens.exp;
+
+    authorized_response(tokens, expires)
+}
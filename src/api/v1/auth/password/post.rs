@@ -0,0 +1,143 @@
+use actix_web::{
+    post,
+    web::{self, Data, Json},
+    HttpRequest, HttpResponse, Responder,
+};
+use validator::Validate;
+
+use crate::{
+    api::{
+        audit::audit,
+        errors::{invalid_data, JsonMessage},
+    },
+    db::DbError,
+    services::{
+        audit::{AuditAction, AuditActor, AuditOutcome, AuditRecord},
+        auth::AuthServiceError,
+        dto::verification::{ForgotPasswordDto, ResetPasswordDto},
+        verification::VerificationServiceError,
+    },
+    state::AppState,
+};
+
+#[post("forgot")]
+pub(super) async fn forgot_password(
+    req: HttpRequest,
+    json: Json<ForgotPasswordDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let clonned_state = state.clone();
+    let email = json.email.clone();
+    let block_result = web::block(move || {
+        state
+            .verification_service()
+            .send_password_reset(&json.email, state.config())
+    })
+    .await;
+
+    match block_result {
+        Ok(Ok(_)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::new(
+                    AuditActor::Anonymous,
+                    AuditAction::PasswordResetRequest,
+                    AuditOutcome::Success,
+                )
+                .details(email),
+            )
+            .await;
+
+            HttpResponse::Ok().json(JsonMessage { message: "ok" })
+        }
+        _ => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[post("reset")]
+pub(super) async fn reset_password(
+    req: HttpRequest,
+    json: Json<ResetPasswordDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let internal_error = HttpResponse::InternalServerError().json(JsonMessage {
+        message: "internal_error",
+    });
+    let clonned_state = state.clone();
+    let token = json.token.clone();
+    let block_result = web::block(move || {
+        clonned_state
+            .verification_service()
+            .consume_password_reset(&token)
+    })
+    .await;
+
+    if block_result.is_err() {
+        return internal_error;
+    }
+
+    let uid = match block_result.unwrap() {
+        Ok(uid) => uid,
+        Err(DbError::Execution(VerificationServiceError::InvalidToken)) => {
+            audit(
+                &state,
+                &req,
+                AuditRecord::new(
+                    AuditActor::Anonymous,
+                    AuditAction::PasswordReset,
+                    AuditOutcome::Failure,
+                ),
+            )
+            .await;
+
+            return HttpResponse::BadRequest().json(JsonMessage {
+                message: "invalid_token",
+            });
+        }
+        Err(_) => return internal_error,
+    };
+
+    let clonned_state = state.clone();
+    let block_result = web::block(move || {
+        state
+            .auth_service()
+            .set_password(&uid, &json.password, state.config())
+    })
+    .await;
+
+    match block_result {
+        Ok(Ok(_)) => (),
+        Ok(Err(DbError::Execution(AuthServiceError::ExternalAccount))) => {
+            return HttpResponse::Conflict().json(JsonMessage {
+                message: "external_account",
+            })
+        }
+        _ => return internal_error,
+    }
+
+    // Whoever knew the old password must not stay logged in
+    if clonned_state.session_service().revoke_all(&uid).is_err() {
+        return internal_error;
+    }
+
+    let _ = clonned_state.login_throttle_service().unlock_account(&uid);
+    audit(
+        &clonned_state,
+        &req,
+        AuditRecord::own_account(uid, AuditAction::PasswordReset, AuditOutcome::Success),
+    )
+    .await;
+
+    HttpResponse::Ok().json(JsonMessage { message: "ok" })
+}
@@ -7,25 +7,27 @@ use actix_web::{
     web::{self, Data, Json},
     HttpResponse, Responder, HttpRequest,
 };
-use serde::Serialize;
+use uuid::Uuid;
 use validator::Validate;
 
+use super::tokens::{
+    authorized_response, mfa_required_response, request_ip, start_session, store_tokens,
+};
 use crate::{
-    api::errors::{invalid_data, JsonMessage},
+    api::{
+        audit::audit,
+        errors::{account_blocked, invalid_data, JsonMessage},
+        middlewares::authenticate::extract_auth_token,
+    },
     db::DbError,
     services::{
-        auth::{AuthServiceError, AuthService},
+        audit::{AuditAction, AuditActor, AuditOutcome, AuditRecord, AuditTarget},
+        auth::{AuthService, AuthServiceError, AuthorizationResult, LoginAttemptsError},
         dto::auth::{AuthorizationDto, RegistrationDto},
     },
     state::AppState,
 };
 
-#[derive(Serialize)]
-struct AuthDataResult {
-    access_token: String,
-    expires: usize,
-}
-
 #[post("logout")]
 pub(super) async fn logout(req: HttpRequest, state: Data<AppState>) -> impl Responder {
     let refresh_token = req.cookie("refresh_token");
@@ -37,7 +39,28 @@ pub(super) async fn logout(req: HttpRequest, state: Data<AppState>) -> impl Resp
             });
     }
 
-    let refresh_token = refresh_token.unwrap().to_string();
+    let refresh_token = refresh_token.unwrap().value().to_string();
+
+    let mut actor = AuditActor::Anonymous;
+
+    if let Some(access_token) = extract_auth_token(&req) {
+        if let Ok(user_data) = AuthService::validate_token(access_token, state.config()) {
+            let _ = state.redis().deny_access_token(&user_data.jti, user_data.exp);
+            actor = AuditActor::User(user_data.uid);
+        }
+    }
+
+    if let Ok(refresh_data) = AuthService::decrypt_refresh_token(&refresh_token, state.config()) {
+        let _ = state.session_service().end(&refresh_data.family);
+        audit(
+            &state,
+            &req,
+            AuditRecord::new(actor, AuditAction::Logout, AuditOutcome::Success)
+                .target(AuditTarget::Session(refresh_data.family)),
+        )
+        .await;
+    }
+
     let _ = state.redis().remove(&refresh_token);
     let expires_time = OffsetDateTime::from_unix_timestamp(0);
     HttpResponse::Ok()
@@ -71,24 +94,57 @@ pub(super) async fn refresh_tokens(req: HttpRequest, state: Data<AppState>) -> i
 
     let refresh_token = refresh_token.unwrap();
     let refresh_token = refresh_token.value();
-    let access_token = clonned_state.redis().get_pair(&refresh_token);
 
-    if access_token.is_err() {
+    if refresh_token.is_empty() {
+        return refresh_token_not_found;
+    }
+
+    let refresh_data = AuthService::decrypt_refresh_token(refresh_token, state.config());
+
+    if let Err(err) = refresh_data {
+        match err {
+            AuthServiceError::TokenExpired => return refresh_token_not_found,
+            AuthServiceError::InvalidToken => return HttpResponse::BadRequest().json(JsonMessage {
+                message: "invalid_token"
+            }),
+            _ => return internal_error,
+        }
+    }
+
+    let family = refresh_data.unwrap().family;
+    let current_refresh_token = clonned_state.redis().get_refresh_family(&family);
+
+    if current_refresh_token.is_err() {
         return internal_error;
     }
 
-    let access_token = access_token.unwrap();
+    let current_refresh_token = current_refresh_token.unwrap();
 
-    if access_token.is_none() {
+    if current_refresh_token.is_none() {
         return refresh_token_not_found;
     }
 
+    if current_refresh_token.unwrap() != refresh_token {
+        if clonned_state.redis().revoke_refresh_family(&family).is_err() {
+            return internal_error;
+        }
+
+        return refresh_token_reused(&clonned_state, &req, family).await;
+    }
+
+    let access_token = clonned_state.redis().get_pair(refresh_token);
+
+    if access_token.is_err() {
+        return internal_error;
+    }
+
     let access_token = access_token.unwrap();
 
-    if refresh_token.is_empty() {
+    if access_token.is_none() {
         return refresh_token_not_found;
     }
 
+    let access_token = access_token.unwrap();
     let user_data = AuthService::decrypt_token(&access_token, state.config());
 
     if let Err(err) = user_data {
@@ -102,8 +158,12 @@ pub(super) async fn refresh_tokens(req: HttpRequest, state: Data<AppState>) -> i
 
     let user_data = user_data.unwrap();
 
-    let block_result =
-        web::block(move || state.auth_service().refresh_tokens(&user_data, state.config())).await;
+    let block_result = web::block(move || {
+        state
+            .auth_service()
+            .refresh_tokens(&user_data, family, state.config())
+    })
+    .await;
 
     if block_result.is_err() {
         return internal_error;
@@ -117,37 +177,77 @@ pub(super) async fn refresh_tokens(req: HttpRequest, state: Data<AppState>) -> i
                 return HttpResponse::NotFound().json(JsonMessage {
                     message: "user_not_found"
                 }),
+            DbError::Execution(AuthServiceError::AccountBlocked) => return account_blocked(),
             _ => return internal_error,
         }
     }
 
     let tokens = service_result.unwrap();
 
-    let _ = clonned_state.redis().remove(&refresh_token);
-    let _ = clonned_state.redis().add_pair(&tokens.1, &access_token, tokens.3);
+    // Another request may have rotated the same token since the check above
+    match clonned_state.redis().rotate_refresh_family(
+        &family,
+        refresh_token,
+        &tokens.refresh_token,
+        &tokens.access_token,
+        tokens.refresh_exp,
+    ) {
+        Ok(true) => (),
+        Ok(false) => return refresh_token_reused(&clonned_state, &req, family).await,
+        Err(_) => return internal_error,
+    }
 
-    let expires_time = OffsetDateTime::from_unix_timestamp(tokens.3 as i64 * 1000);
+    let _ = clonned_state.session_service().touch(
+        &tokens.uid,
+        &tokens.family,
+        request_ip(&req),
+        tokens.refresh_exp,
+    );
 
-    HttpResponse::Ok()
-        .cookie(
-            Cookie::build("refresh_token", tokens.1)
-                .secure(true)
-                .http_only(true)
-                .path("/api/v1/auth")
-                .expires(expires_time.unwrap_or(OffsetDateTime::now_utc() + 30.days() * 1000))
-                .finish(),
+    let expires = tokens.exp * 1000;
+
+    authorized_response(tokens, expires)
+}
+
+/// The family is revoked by then, every token of it is refused from now on
+async fn refresh_token_reused(
+    state: &Data<AppState>,
+    req: &HttpRequest,
+    family: Uuid,
+) -> HttpResponse {
+    log::warn!(
+        "Security event: rotated refresh token reused, revoking token family {}",
+        family
+    );
+
+    audit(
+        state,
+        req,
+        AuditRecord::new(
+            AuditActor::Anonymous,
+            AuditAction::RefreshTokenReuse,
+            AuditOutcome::Denied,
         )
-        .json(AuthDataResult {
-            access_token: tokens.0,
-            expires: tokens.2 * 1000
-        })
+        .target(AuditTarget::Session(family)),
+    )
+    .await;
+
+    HttpResponse::Unauthorized().json(JsonMessage {
+        message: "refresh_token_reused",
+    })
 }
 
 #[post("register")]
-pub(super) async fn register(json: Json<RegistrationDto>, state: Data<AppState>) -> impl Responder {
+pub(super) async fn register(
+    req: HttpRequest,
+    json: Json<RegistrationDto>,
+    state: Data<AppState>,
+) -> impl Responder {
     if json.validate().is_err() {
         return invalid_data();
     }
+
+    let device = json.device.clone();
     let internal_error = HttpResponse::InternalServerError().json(JsonMessage {
         message: "internal_error",
     });
@@ -175,26 +275,37 @@ pub(super) async fn register(json: Json<RegistrationDto>, state: Data<AppState>)
 
     let tokens = service_result.unwrap();
 
-    let _ = clonned_state.redis().add_pair(&tokens.1, &tokens.0, tokens.3);
-    let expires_time = OffsetDateTime::from_unix_timestamp(tokens.3 as i64 * 1000);
+    store_tokens(&clonned_state, &tokens);
+    start_session(&clonned_state, &req, &tokens, device);
+    audit(
+        &clonned_state,
+        &req,
+        AuditRecord::own_account(tokens.uid, AuditAction::Register, AuditOutcome::Success),
+    )
+    .await;
+
+    let uid = tokens.uid;
+    let mail_state = clonned_state.clone();
+    let mail_result = web::block(move || {
+        mail_state
+            .verification_service()
+            .send_email_verification(&uid, mail_state.config())
+    })
+    .await;
+
+    // The account is usable anyway, the email can be sent again on request
+    if !matches!(mail_result, Ok(Ok(_))) {
+        log::error!("Verification email for {} was not sent", uid);
+    }
 
-    HttpResponse::Ok()
-        .cookie(
-            Cookie::build("refresh_token", tokens.1)
-                .secure(true)
-                .http_only(true)
-                .path("/api/v1/auth")
-                .expires(expires_time.unwrap_or(OffsetDateTime::now_utc() + 30.days() * 1000))
-                .finish(),
-        )
-        .json(AuthDataResult {
-            access_token: tokens.0,
-            expires: tokens.2 * 1000
-        })
+    let expires = tokens.exp * 1000;
+
+    authorized_response(tokens, expires)
 }
 
 #[post("")]
 pub(super) async fn authorize(
+    req: HttpRequest,
     json: Json<AuthorizationDto>,
     state: Data<AppState>,
 ) -> impl Responder {
@@ -202,13 +313,47 @@ pub(super) async fn authorize(
         return invalid_data();
     }
 
+    let device = json.device.clone();
+    let login = json.email_or_username.clone();
+    let ip = request_ip(&req);
+    let login_failed = |outcome, reason: &str| {
+        AuditRecord::new(AuditActor::Anonymous, AuditAction::Login, outcome)
+            .details(format!("{}: {}", reason, login))
+    };
+
     let internal_error = HttpResponse::InternalServerError().json(JsonMessage {
         message: "internal_error",
     });
+    let too_many_attempts = HttpResponse::TooManyRequests().json(JsonMessage {
+        message: "too_many_attempts",
+    });
+
+    if let Some(ip) = &ip {
+        match state.login_throttle_service().check_ip(ip) {
+            Ok(_) => (),
+            Err(LoginAttemptsError::Storage) => return internal_error,
+            Err(_) => {
+                audit(
+                    &state,
+                    &req,
+                    login_failed(AuditOutcome::Denied, "too_many_attempts"),
+                )
+                .await;
+
+                return too_many_attempts;
+            }
+        }
+    }
 
     let clonned_state = state.clone();
-    let block_result =
-        web::block(move || state.auth_service().authorize_user(json.0, state.config())).await;
+    let block_result = web::block(move || {
+        state.auth_service().authorize_user(
+            json.0,
+            state.config(),
+            state.login_throttle_service(),
+        )
+    })
+    .await;
 
     if block_result.is_err() {
         return internal_error;
@@ -219,29 +364,79 @@ pub(super) async fn authorize(
     if let Err(db_err) = db_result {
         match db_err {
             DbError::Execution(service_result) => match service_result {
-                AuthServiceError::UserNotFound => return invalid_data(),
-                AuthServiceError::InvalidPassword => return invalid_data(),
+                AuthServiceError::UserNotFound | AuthServiceError::InvalidPassword => {
+                    if let Some(ip) = &ip {
+                        clonned_state.login_throttle_service().ip_failed(ip);
+                    }
+
+                    audit(
+                        &clonned_state,
+                        &req,
+                        login_failed(AuditOutcome::Failure, "invalid_credentials"),
+                    )
+                    .await;
+
+                    return invalid_data();
+                }
+                AuthServiceError::AccountLocked => {
+                    audit(
+                        &clonned_state,
+                        &req,
+                        login_failed(AuditOutcome::Denied, "account_locked"),
+                    )
+                    .await;
+
+                    return HttpResponse::Locked().json(JsonMessage {
+                        message: "account_locked",
+                    });
+                }
+                AuthServiceError::TooManyAttempts => {
+                    audit(
+                        &clonned_state,
+                        &req,
+                        login_failed(AuditOutcome::Denied, "too_many_attempts"),
+                    )
+                    .await;
+
+                    return too_many_attempts;
+                }
+                AuthServiceError::ExternalAccount => {
+                    return HttpResponse::Conflict().json(JsonMessage {
+                        message: "external_account",
+                    })
+                }
+                AuthServiceError::AccountBlocked => {
+                    audit(
+                        &clonned_state,
+                        &req,
+                        login_failed(AuditOutcome::Denied, "account_blocked"),
+                    )
+                    .await;
+
+                    return account_blocked();
+                }
                 _ => return internal_error,
             },
             _ => return internal_error,
         }
     }
 
-    let tokens = db_result.unwrap();
-    let _ = clonned_state.redis().add_pair(&tokens.1, &tokens.0, tokens.3);
-    let expires_time = OffsetDateTime::from_unix_timestamp(tokens.3 as i64 * 1000);
-
-    HttpResponse::Ok()
-        .cookie(
-            Cookie::build("refresh_token", tokens.1)
-                .secure(true)
-                .http_only(true)
-                .path("/api/v1/auth")
-                .expires(expires_time.unwrap_or(OffsetDateTime::now_utc() + 30.days() * 1000))
-                .finish(),
-        )
-        .json(AuthDataResult {
-            access_token: tokens.0,
-            expires: tokens.2
-        })
+    let tokens = match db_result.unwrap() {
+        AuthorizationResult::Authorized(tokens) => tokens,
+        AuthorizationResult::MfaRequired { uid, enrollment } => {
+            return mfa_required_response(&clonned_state, uid, device, enrollment)
+        }
+    };
+
+    store_tokens(&clonned_state, &tokens);
+    start_session(&clonned_state, &req, &tokens, device);
+    audit(
+        &clonned_state,
+        &req,
+        AuditRecord::own_account(tokens.uid, AuditAction::Login, AuditOutcome::Success),
+    )
+    .await;
+    let expires = tokens.exp;
+
+    authorized_response(tokens, expires)
 }
@@ -0,0 +1,103 @@
+use actix_web::{
+    delete,
+    web::{self, Data},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{
+    api::{audit::audit, errors::JsonMessage},
+    services::{
+        audit::{AuditAction, AuditActor, AuditOutcome, AuditRecord, AuditTarget},
+        auth::JwtAccessData,
+        session::SessionServiceError,
+    },
+    state::AppState,
+};
+
+#[derive(Serialize)]
+struct RevokedSessions {
+    uids: Vec<Uuid>,
+}
+
+#[delete("/{uid}")]
+pub(super) async fn revoke_session(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let session_uid = path.into_inner();
+
+    match state.session_service().revoke(&user.uid, &session_uid) {
+        Ok(_) => {
+            audit(
+                &state,
+                &req,
+                AuditRecord::new(
+                    AuditActor::User(user.uid),
+                    AuditAction::SessionRevoke,
+                    AuditOutcome::Success,
+                )
+                .target(AuditTarget::Session(session_uid)),
+            )
+            .await;
+
+            HttpResponse::Ok().json(RevokedSessions {
+                uids: vec![session_uid],
+            })
+        }
+        Err(SessionServiceError::NotFound) => HttpResponse::NotFound().json(JsonMessage {
+            message: "session_not_found",
+        }),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[delete("")]
+pub(super) async fn revoke_other_sessions(
+    req: HttpRequest,
+    state: Data<AppState>,
+) -> impl Responder {
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+
+    match state.session_service().revoke_others(&user.uid, &user.sid) {
+        Ok(uids) => {
+            audit(
+                &state,
+                &req,
+                AuditRecord::own_account(
+                    user.uid,
+                    AuditAction::OtherSessionsRevoke,
+                    AuditOutcome::Success,
+                )
+                .details(format!("{} sessions", uids.len())),
+            )
+            .await;
+
+            HttpResponse::Ok().json(RevokedSessions { uids })
+        }
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
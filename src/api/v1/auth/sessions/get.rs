@@ -0,0 +1,46 @@
+use actix_web::{get, web::Data, HttpMessage, HttpRequest, HttpResponse, Responder};
+use serde::Serialize;
+
+use crate::{
+    api::errors::JsonMessage,
+    services::{auth::JwtAccessData, dto::session::SessionData},
+    state::AppState,
+};
+
+#[derive(Serialize)]
+struct SessionInfo {
+    #[serde(flatten)]
+    session: SessionData,
+    current: bool,
+}
+
+#[get("")]
+pub(super) async fn get_sessions(req: HttpRequest, state: Data<AppState>) -> impl Responder {
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let sessions = state.session_service().list(&user.uid);
+
+    if sessions.is_err() {
+        return HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        });
+    }
+
+    let sessions = sessions
+        .unwrap()
+        .into_iter()
+        .map(|session| SessionInfo {
+            current: session.uid == user.sid,
+            session,
+        })
+        .collect::<Vec<SessionInfo>>();
+
+    HttpResponse::Ok().json(sessions)
+}
@@ -0,0 +1,16 @@
+mod delete;
+mod get;
+
+use std::sync::Arc;
+
+use crate::config::Config;
+
+use actix_web::web;
+
+pub(super) fn configure(_: Arc<Config>) -> impl Fn(&mut web::ServiceConfig) {
+    move |cfg| {
+        cfg.service(get::get_sessions)
+            .service(delete::revoke_session)
+            .service(delete::revoke_other_sessions);
+    }
+}
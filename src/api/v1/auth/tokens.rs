@@ -0,0 +1,126 @@
+use actix_web::{
+    cookie::{
+        time::{ext::NumericalDuration, OffsetDateTime},
+        Cookie,
+    },
+    http::header,
+    HttpRequest, HttpResponse,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{
+    api::errors::JsonMessage,
+    services::{
+        auth::TokensData,
+        dto::{mfa::MfaPendingData, session::SessionData},
+    },
+    state::AppState,
+};
+
+#[derive(Serialize)]
+struct AuthDataResult {
+    access_token: String,
+    expires: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    recovery_codes: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+struct MfaRequiredResult {
+    message: &'static str,
+    mfa_token: String,
+    enrollment_required: bool,
+}
+
+pub(super) fn store_tokens(state: &AppState, tokens: &TokensData) {
+    let _ = state
+        .redis()
+        .add_pair(&tokens.refresh_token, &tokens.access_token, tokens.refresh_exp);
+    let _ = state
+        .redis()
+        .set_refresh_family(&tokens.family, &tokens.refresh_token, tokens.refresh_exp);
+}
+
+pub(super) fn request_ip(req: &HttpRequest) -> Option<String> {
+    req.connection_info().realip_remote_addr().map(|ip| ip.to_owned())
+}
+
+pub(super) fn start_session(
+    state: &AppState,
+    req: &HttpRequest,
+    tokens: &TokensData,
+    device: Option<String>,
+) {
+    let now = chrono::Utc::now().naive_utc();
+    let user_agent = req
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_owned());
+    let session = SessionData {
+        uid: tokens.family,
+        device: device.or_else(|| user_agent.clone()),
+        ip: request_ip(req),
+        user_agent,
+        created_at: now,
+        last_used_at: now,
+    };
+
+    let _ = state
+        .session_service()
+        .create(&tokens.uid, &session, tokens.refresh_exp);
+}
+
+pub(super) fn authorized_response(tokens: TokensData, expires: usize) -> HttpResponse {
+    authorized_response_with_recovery_codes(tokens, expires, None)
+}
+
+pub(super) fn authorized_response_with_recovery_codes(
+    tokens: TokensData,
+    expires: usize,
+    recovery_codes: Option<Vec<String>>,
+) -> HttpResponse {
+    let expires_time = OffsetDateTime::from_unix_timestamp(tokens.refresh_exp as i64 * 1000);
+
+    HttpResponse::Ok()
+        .cookie(
+            Cookie::build("refresh_token", tokens.refresh_token)
+                .secure(true)
+                .http_only(true)
+                .path("/api/v1/auth")
+                .expires(expires_time.unwrap_or(OffsetDateTime::now_utc() + 30.days() * 1000))
+                .finish(),
+        )
+        .json(AuthDataResult {
+            access_token: tokens.access_token,
+            expires,
+            recovery_codes,
+        })
+}
+
+/// Parks a login that still needs the second factor. The login itself is
+/// recorded once the second factor is passed.
+pub(super) fn mfa_required_response(
+    state: &AppState,
+    uid: Uuid,
+    device: Option<String>,
+    enrollment: bool,
+) -> HttpResponse {
+    let mfa_token = state.mfa_service().create_pending(&MfaPendingData {
+        uid,
+        device,
+        enrollment,
+    });
+
+    match mfa_token {
+        Ok(mfa_token) => HttpResponse::Ok().json(MfaRequiredResult {
+            message: "mfa_required",
+            mfa_token,
+            enrollment_required: enrollment,
+        }),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
@@ -0,0 +1,221 @@
+use actix_web::{
+    get,
+    http::header,
+    web::{self, Data, Query},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use uuid::Uuid;
+
+use super::billing_error_response;
+use crate::{
+    api::{
+        audit::{audit_disclosure, request_actor},
+        errors::JsonMessage,
+        middlewares::authorize::RequirePermission,
+    },
+    services::{
+        audit::{AuditAction, AuditOutcome, AuditRecord},
+        auth::JwtAccessData,
+        dto::billing::{
+            BalancesQuery, ExportFormat, InvoiceExportRow, InvoicesExportQuery, InvoicesQuery,
+        },
+        permissions::Permission,
+    },
+    state::AppState,
+};
+
+const CSV_HEADER: &str = "number,uid,transaction_uid,client_uid,law_uid,status,currency,\
+subtotal,discount,tax,total,paid,outstanding,created_at,voided_at";
+const CSV_DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[get("", wrap = "RequirePermission::new(Permission::InvoicesManage)")]
+pub(super) async fn get_invoices(
+    query: Query<InvoicesQuery>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let block_result = web::block(move || state.billing_service().get_invoices(&query)).await;
+
+    match block_result {
+        Ok(Ok(invoices)) => HttpResponse::Ok().json(invoices),
+        Ok(Err(err)) => billing_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[get("/me")]
+pub(super) async fn get_own_invoices(
+    req: HttpRequest,
+    query: Query<InvoicesQuery>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let block_result = web::block(move || state.billing_service().get_own(&user.uid, &query)).await;
+
+    match block_result {
+        Ok(Ok(invoices)) => HttpResponse::Ok().json(invoices),
+        Ok(Err(err)) => billing_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[get("/me/balance")]
+pub(super) async fn get_own_balance(req: HttpRequest, state: Data<AppState>) -> impl Responder {
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let block_result = web::block(move || state.billing_service().get_own_balance(&user.uid)).await;
+
+    match block_result {
+        Ok(Ok(balances)) => HttpResponse::Ok().json(balances),
+        Ok(Err(err)) => billing_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[get(
+    "/balances",
+    wrap = "RequirePermission::new(Permission::InvoicesManage)"
+)]
+pub(super) async fn get_balances(
+    query: Query<BalancesQuery>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let block_result = web::block(move || state.billing_service().get_balances(&query)).await;
+
+    match block_result {
+        Ok(Ok(balances)) => HttpResponse::Ok().json(balances),
+        Ok(Err(err)) => billing_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[get("/export", wrap = "RequirePermission::new(Permission::InvoicesManage)")]
+pub(super) async fn export(
+    req: HttpRequest,
+    query: Query<InvoicesExportQuery>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let format = query.format.unwrap_or_default();
+    let clonned_state = state.clone();
+    let block_result = web::block(move || state.billing_service().export(&query)).await;
+
+    let rows = match block_result {
+        Ok(Ok(rows)) => rows,
+        Ok(Err(err)) => return billing_error_response(err),
+        Err(_) => {
+            return HttpResponse::InternalServerError().json(JsonMessage {
+                message: "internal_error",
+            })
+        }
+    };
+
+    if let Err(response) = audit_disclosure(
+        &clonned_state,
+        &req,
+        AuditRecord::new(
+            request_actor(&req),
+            AuditAction::InvoiceExport,
+            AuditOutcome::Success,
+        )
+        .details(format!("rows: {}", rows.len())),
+    )
+    .await
+    {
+        return response;
+    }
+
+    match format {
+        ExportFormat::Json => HttpResponse::Ok().json(rows),
+        ExportFormat::Csv => HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header((
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"invoices.csv\"",
+            ))
+            .body(export_csv(&rows)),
+    }
+}
+
+#[get("/{uid}")]
+pub(super) async fn get_invoice(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let uid = path.into_inner();
+    let manage = Permission::InvoicesManage.is_granted_to(user.role);
+    let block_result =
+        web::block(move || state.billing_service().get(&user.uid, &uid, manage)).await;
+
+    match block_result {
+        Ok(Ok(invoice)) => HttpResponse::Ok().json(invoice),
+        Ok(Err(err)) => billing_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+/// Every column is a number, uid, code or date, so nothing needs quoting
+fn export_csv(rows: &[InvoiceExportRow]) -> String {
+    let optional = |uid: Option<Uuid>| uid.map(|uid| uid.to_string()).unwrap_or_default();
+    let mut csv = String::from(CSV_HEADER);
+
+    csv.push('\n');
+
+    for row in rows {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+            row.number,
+            row.uid,
+            row.transaction_uid,
+            optional(row.client_uid),
+            optional(row.law_uid),
+            <&str>::from(row.status),
+            row.currency,
+            row.subtotal,
+            row.discount,
+            row.tax,
+            row.total,
+            row.paid,
+            row.outstanding,
+            row.created_at.format(CSV_DATETIME_FORMAT),
+            row.voided_at
+                .map(|voided_at| voided_at.format(CSV_DATETIME_FORMAT).to_string())
+                .unwrap_or_default(),
+        ));
+    }
+
+    csv
+}
@@ -0,0 +1,114 @@
+mod get;
+mod post;
+
+use std::sync::Arc;
+
+use crate::{
+    api::errors::{invalid_data, no_rights, JsonMessage},
+    config::Config,
+    db::DbError,
+    services::billing::BillingServiceError,
+};
+
+use actix_web::{web, HttpResponse};
+
+pub(super) fn configure(_: Arc<Config>) -> impl Fn(&mut web::ServiceConfig) {
+    move |cfg| {
+        cfg.service(post::create)
+            .service(get::get_invoices)
+            .service(get::get_own_invoices)
+            .service(get::get_own_balance)
+            .service(get::get_balances)
+            .service(get::export)
+            .service(get::get_invoice)
+            .service(post::record_payment)
+            .service(post::pay)
+            .service(post::void);
+    }
+}
+
+fn billing_error_response(err: DbError<BillingServiceError>) -> HttpResponse {
+    match err {
+        DbError::Execution(BillingServiceError::NotFound) => {
+            HttpResponse::NotFound().json(JsonMessage {
+                message: "invoice_not_found",
+            })
+        }
+        DbError::Execution(BillingServiceError::UserNotFound) => {
+            HttpResponse::NotFound().json(JsonMessage {
+                message: "user_not_found",
+            })
+        }
+        DbError::Execution(BillingServiceError::NotAllowed) => no_rights(),
+        DbError::Execution(BillingServiceError::TransactionNotFound) => HttpResponse::NotFound()
+            .json(JsonMessage {
+                message: "law_transaction_not_found",
+            }),
+        DbError::Execution(BillingServiceError::NotBillable) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "law_transaction_not_billable",
+            })
+        }
+        DbError::Execution(BillingServiceError::AlreadyInvoiced) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "already_invoiced",
+            })
+        }
+        DbError::Execution(BillingServiceError::ServiceNotFound) => {
+            HttpResponse::NotFound().json(JsonMessage {
+                message: "service_not_found",
+            })
+        }
+        DbError::Execution(BillingServiceError::CurrencyMismatch) => HttpResponse::BadRequest()
+            .json(JsonMessage {
+                message: "currency_mismatch",
+            }),
+        DbError::Execution(BillingServiceError::InvalidDiscount) => HttpResponse::BadRequest()
+            .json(JsonMessage {
+                message: "invalid_discount",
+            }),
+        DbError::Execution(BillingServiceError::InvalidAmount) => {
+            HttpResponse::BadRequest().json(JsonMessage {
+                message: "invalid_amount",
+            })
+        }
+        DbError::Execution(BillingServiceError::AmountTooLarge) => {
+            HttpResponse::BadRequest().json(JsonMessage {
+                message: "amount_too_large",
+            })
+        }
+        DbError::Execution(BillingServiceError::Overpayment) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "overpayment",
+            })
+        }
+        DbError::Execution(BillingServiceError::Settled) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "invoice_paid",
+            })
+        }
+        DbError::Execution(BillingServiceError::Void) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "invoice_void",
+            })
+        }
+        DbError::Execution(BillingServiceError::HasPayments) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "invoice_has_payments",
+            })
+        }
+        DbError::Execution(BillingServiceError::PaymentPending) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "payment_pending",
+            })
+        }
+        DbError::Execution(BillingServiceError::PaymentDeclined) => HttpResponse::PaymentRequired()
+            .json(JsonMessage {
+                message: "payment_declined",
+            }),
+        DbError::Execution(BillingServiceError::InvalidPage) => invalid_data(),
+        _ => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
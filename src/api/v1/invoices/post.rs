@@ -0,0 +1,210 @@
+use actix_web::{
+    post,
+    web::{self, Data, Json},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::billing_error_response;
+use crate::{
+    api::{
+        audit::{audit, request_actor},
+        errors::{invalid_data, JsonMessage},
+        middlewares::authorize::RequirePermission,
+    },
+    services::{
+        audit::{AuditAction, AuditActor, AuditOutcome, AuditRecord, AuditTarget},
+        auth::JwtAccessData,
+        dto::billing::{CreateInvoiceDto, PayInvoiceDto, RecordPaymentDto},
+        permissions::Permission,
+    },
+    state::AppState,
+};
+
+#[post("", wrap = "RequirePermission::new(Permission::InvoicesManage)")]
+pub(super) async fn create(
+    req: HttpRequest,
+    json: Json<CreateInvoiceDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let uid = user.unwrap().uid;
+    let clonned_state = state.clone();
+    let block_result = web::block(move || state.billing_service().create(&uid, &json)).await;
+
+    match block_result {
+        Ok(Ok(invoice)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::new(
+                    AuditActor::User(uid),
+                    AuditAction::InvoiceCreate,
+                    AuditOutcome::Success,
+                )
+                .target(AuditTarget::Invoice(invoice.invoice.uid))
+                .details(format!(
+                    "total: {} {}",
+                    invoice.invoice.total, invoice.invoice.currency
+                )),
+            )
+            .await;
+
+            HttpResponse::Created().json(invoice)
+        }
+        Ok(Err(err)) => billing_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[post(
+    "/{uid}/payments",
+    wrap = "RequirePermission::new(Permission::InvoicesManage)"
+)]
+pub(super) async fn record_payment(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    json: Json<RecordPaymentDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let uid = user.unwrap().uid;
+    let invoice_uid = path.into_inner();
+    let amount = json.amount;
+    let clonned_state = state.clone();
+    let block_result = web::block(move || {
+        state
+            .billing_service()
+            .record_payment(&uid, &invoice_uid, &json)
+    })
+    .await;
+
+    match block_result {
+        Ok(Ok(invoice)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::new(
+                    AuditActor::User(uid),
+                    AuditAction::PaymentRecord,
+                    AuditOutcome::Success,
+                )
+                .target(AuditTarget::Invoice(invoice.invoice.uid))
+                .details(format!("amount: {} {}", amount, invoice.invoice.currency)),
+            )
+            .await;
+
+            HttpResponse::Ok().json(invoice)
+        }
+        Ok(Err(err)) => billing_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[post("/{uid}/pay")]
+pub(super) async fn pay(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    json: Json<PayInvoiceDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let uid = user.unwrap().uid;
+    let invoice_uid = path.into_inner();
+    let clonned_state = state.clone();
+    let block_result =
+        web::block(move || state.billing_service().pay(&uid, &invoice_uid, &json)).await;
+
+    match block_result {
+        Ok(Ok((invoice, amount))) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::new(
+                    AuditActor::User(uid),
+                    AuditAction::PaymentCharge,
+                    AuditOutcome::Success,
+                )
+                .target(AuditTarget::Invoice(invoice.invoice.uid))
+                .details(format!("amount: {} {}", amount, invoice.invoice.currency)),
+            )
+            .await;
+
+            HttpResponse::Ok().json(invoice)
+        }
+        Ok(Err(err)) => billing_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[post(
+    "/{uid}/void",
+    wrap = "RequirePermission::new(Permission::InvoicesManage)"
+)]
+pub(super) async fn void(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let invoice_uid = path.into_inner();
+    let clonned_state = state.clone();
+    let block_result = web::block(move || state.billing_service().void(&invoice_uid)).await;
+
+    match block_result {
+        Ok(Ok(invoice)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::new(
+                    request_actor(&req),
+                    AuditAction::InvoiceVoid,
+                    AuditOutcome::Success,
+                )
+                .target(AuditTarget::Invoice(invoice.uid)),
+            )
+            .await;
+
+            HttpResponse::Ok().json(invoice)
+        }
+        Ok(Err(err)) => billing_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
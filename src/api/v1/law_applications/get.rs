@@ -0,0 +1,56 @@
+use actix_web::{
+    get,
+    web::{self, Data, Query},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+
+use super::law_application_error_response;
+use crate::{
+    api::{errors::JsonMessage, middlewares::authorize::RequirePermission},
+    services::{
+        auth::JwtAccessData, dto::law_application::LawApplicationsQuery, permissions::Permission,
+    },
+    state::AppState,
+};
+
+#[get("/me")]
+pub(super) async fn get_own_applications(
+    req: HttpRequest,
+    state: Data<AppState>,
+) -> impl Responder {
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let block_result = web::block(move || state.law_application_service().get_own(&user.uid)).await;
+
+    match block_result {
+        Ok(Ok(applications)) => HttpResponse::Ok().json(applications),
+        Ok(Err(err)) => law_application_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[get("", wrap = "RequirePermission::new(Permission::LawApplicationsReview)")]
+pub(super) async fn get_applications(
+    query: Query<LawApplicationsQuery>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let block_result =
+        web::block(move || state.law_application_service().get_applications(&query)).await;
+
+    match block_result {
+        Ok(Ok(applications)) => HttpResponse::Ok().json(applications),
+        Ok(Err(err)) => law_application_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
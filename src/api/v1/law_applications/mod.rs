@@ -0,0 +1,62 @@
+mod get;
+mod post;
+
+use std::sync::Arc;
+
+use crate::{
+    api::errors::{invalid_data, no_rights, JsonMessage},
+    config::Config,
+    db::DbError,
+    services::law_applications::LawApplicationServiceError,
+};
+
+use actix_web::{web, HttpResponse};
+
+pub(super) fn configure(_: Arc<Config>) -> impl Fn(&mut web::ServiceConfig) {
+    move |cfg| {
+        cfg.service(post::apply)
+            .service(get::get_own_applications)
+            .service(get::get_applications)
+            .service(post::approve)
+            .service(post::reject);
+    }
+}
+
+fn law_application_error_response(err: DbError<LawApplicationServiceError>) -> HttpResponse {
+    match err {
+        DbError::Execution(LawApplicationServiceError::NotFound) => {
+            HttpResponse::NotFound().json(JsonMessage {
+                message: "application_not_found",
+            })
+        }
+        DbError::Execution(LawApplicationServiceError::AlreadyLaw) => HttpResponse::Conflict()
+            .json(JsonMessage {
+                message: "already_law",
+            }),
+        DbError::Execution(LawApplicationServiceError::NotClient) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "not_client",
+            })
+        }
+        DbError::Execution(LawApplicationServiceError::ItnTaken) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "itn_taken",
+            })
+        }
+        DbError::Execution(LawApplicationServiceError::Pending) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "application_pending",
+            })
+        }
+        DbError::Execution(LawApplicationServiceError::Reviewed) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "application_reviewed",
+            })
+        }
+        DbError::Execution(LawApplicationServiceError::SelfReview) => no_rights(),
+        DbError::Execution(LawApplicationServiceError::InvalidPage) => invalid_data(),
+        _ => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
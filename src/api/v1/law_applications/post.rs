@@ -0,0 +1,192 @@
+use actix_web::{
+    post,
+    web::{self, Data, Json},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{super::users::deny_access_tokens, law_application_error_response};
+use crate::{
+    api::{
+        audit::audit,
+        errors::{invalid_data, JsonMessage},
+        middlewares::authorize::RequirePermission,
+    },
+    services::{
+        audit::{AuditAction, AuditActor, AuditOutcome, AuditRecord, AuditTarget},
+        auth::JwtAccessData,
+        dto::law_application::{LawApplicationDto, ReviewLawApplicationDto},
+        permissions::Permission,
+    },
+    state::AppState,
+};
+
+#[post("")]
+pub(super) async fn apply(
+    req: HttpRequest,
+    json: Json<LawApplicationDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let uid = user.unwrap().uid;
+    let clonned_state = state.clone();
+    let block_result = web::block(move || state.law_application_service().apply(&uid, &json)).await;
+
+    match block_result {
+        Ok(Ok(application)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::new(
+                    AuditActor::User(uid),
+                    AuditAction::LawApplicationCreate,
+                    AuditOutcome::Success,
+                )
+                .target(AuditTarget::LawApplication(application.uid)),
+            )
+            .await;
+
+            HttpResponse::Created().json(application)
+        }
+        Ok(Err(err)) => law_application_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[post(
+    "/{uid}/approve",
+    wrap = "RequirePermission::new(Permission::LawApplicationsReview)"
+)]
+pub(super) async fn approve(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    json: Json<ReviewLawApplicationDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let reviewer_uid = user.unwrap().uid;
+    let application_uid = path.into_inner();
+    let clonned_state = state.clone();
+    let block_result = web::block(move || {
+        state.law_application_service().approve(
+            &reviewer_uid,
+            &application_uid,
+            json.reason.as_deref(),
+        )
+    })
+    .await;
+
+    let (application, applicant_uid) = match block_result {
+        Ok(Ok(result)) => result,
+        Ok(Err(err)) => return law_application_error_response(err),
+        Err(_) => {
+            return HttpResponse::InternalServerError().json(JsonMessage {
+                message: "internal_error",
+            })
+        }
+    };
+
+    // The law role is picked up on the next refresh
+    if let Some(applicant_uid) = applicant_uid {
+        if deny_access_tokens(&clonned_state, &applicant_uid).is_err() {
+            log::error!("Access tokens of {} were not denied", applicant_uid);
+        }
+    }
+
+    audit(
+        &clonned_state,
+        &req,
+        AuditRecord::new(
+            AuditActor::User(reviewer_uid),
+            AuditAction::LawApplicationApprove,
+            AuditOutcome::Success,
+        )
+        .target(AuditTarget::LawApplication(application.uid)),
+    )
+    .await;
+
+    HttpResponse::Ok().json(application)
+}
+
+#[post(
+    "/{uid}/reject",
+    wrap = "RequirePermission::new(Permission::LawApplicationsReview)"
+)]
+pub(super) async fn reject(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    json: Json<ReviewLawApplicationDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    // The applicant has to know what to fix
+    if json.validate().is_err() || json.reason.is_none() {
+        return invalid_data();
+    }
+
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let reviewer_uid = user.unwrap().uid;
+    let application_uid = path.into_inner();
+    let clonned_state = state.clone();
+    let block_result = web::block(move || {
+        state.law_application_service().reject(
+            &reviewer_uid,
+            &application_uid,
+            json.reason.as_deref().unwrap_or_default(),
+        )
+    })
+    .await;
+
+    match block_result {
+        Ok(Ok(application)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::new(
+                    AuditActor::User(reviewer_uid),
+                    AuditAction::LawApplicationReject,
+                    AuditOutcome::Success,
+                )
+                .target(AuditTarget::LawApplication(application.uid)),
+            )
+            .await;
+
+            HttpResponse::Ok().json(application)
+        }
+        Ok(Err(err)) => law_application_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
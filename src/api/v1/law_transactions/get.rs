@@ -0,0 +1,67 @@
+use actix_web::{
+    get,
+    web::{self, Data, Query},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use uuid::Uuid;
+
+use super::transaction_error_response;
+use crate::{
+    api::errors::JsonMessage,
+    services::{auth::JwtAccessData, dto::law_transaction::LawTransactionsQuery},
+    state::AppState,
+};
+
+#[get("")]
+pub(super) async fn get_own_transactions(
+    req: HttpRequest,
+    query: Query<LawTransactionsQuery>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let block_result =
+        web::block(move || state.transaction_service().get_own(&user.uid, &query)).await;
+
+    match block_result {
+        Ok(Ok(transactions)) => HttpResponse::Ok().json(transactions),
+        Ok(Err(err)) => transaction_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[get("/{uid}")]
+pub(super) async fn get_transaction(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let uid = path.into_inner();
+    let block_result = web::block(move || state.transaction_service().get(&user.uid, &uid)).await;
+
+    match block_result {
+        Ok(Ok(transaction)) => HttpResponse::Ok().json(transaction),
+        Ok(Err(err)) => transaction_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
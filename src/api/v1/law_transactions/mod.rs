@@ -0,0 +1,70 @@
+mod get;
+mod post;
+
+use std::sync::Arc;
+
+use crate::{
+    api::errors::{invalid_data, no_rights, JsonMessage},
+    config::Config,
+    db::DbError,
+    services::transactions::TransactionServiceError,
+};
+
+use actix_web::{web, HttpResponse};
+
+pub(super) fn configure(_: Arc<Config>) -> impl Fn(&mut web::ServiceConfig) {
+    move |cfg| {
+        cfg.service(post::create)
+            .service(get::get_own_transactions)
+            .service(get::get_transaction)
+            .service(post::accept)
+            .service(post::decline)
+            .service(post::complete)
+            .service(post::cancel);
+    }
+}
+
+fn transaction_error_response(err: DbError<TransactionServiceError>) -> HttpResponse {
+    match err {
+        DbError::Execution(TransactionServiceError::NotFound) => {
+            HttpResponse::NotFound().json(JsonMessage {
+                message: "law_transaction_not_found",
+            })
+        }
+        DbError::Execution(TransactionServiceError::UserNotFound) => {
+            HttpResponse::NotFound().json(JsonMessage {
+                message: "user_not_found",
+            })
+        }
+        DbError::Execution(TransactionServiceError::ServiceNotFound) => HttpResponse::NotFound()
+            .json(JsonMessage {
+                message: "service_not_found",
+            }),
+        DbError::Execution(TransactionServiceError::CourtCaseNotFound) => HttpResponse::NotFound()
+            .json(JsonMessage {
+                message: "court_case_not_found",
+            }),
+        DbError::Execution(TransactionServiceError::SelfEngagement) => HttpResponse::Conflict()
+            .json(JsonMessage {
+                message: "self_engagement",
+            }),
+        DbError::Execution(TransactionServiceError::EmailNotVerified) => HttpResponse::Forbidden()
+            .json(JsonMessage {
+                message: "email_not_verified",
+            }),
+        DbError::Execution(TransactionServiceError::NotAllowed) => no_rights(),
+        DbError::Execution(TransactionServiceError::IllegalTransition) => HttpResponse::Conflict()
+            .json(JsonMessage {
+                message: "illegal_transition",
+            }),
+        DbError::Execution(TransactionServiceError::Invoiced) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "open_invoice",
+            })
+        }
+        DbError::Execution(TransactionServiceError::InvalidPage) => invalid_data(),
+        _ => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
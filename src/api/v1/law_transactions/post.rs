@@ -0,0 +1,189 @@
+use actix_web::{
+    post,
+    web::{self, Data, Json},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::transaction_error_response;
+use crate::{
+    api::{
+        audit::audit,
+        errors::{invalid_data, JsonMessage},
+    },
+    db::models::custom_types::law_transaction_statuses::LawTransactionsStatues,
+    services::{
+        audit::{AuditAction, AuditActor, AuditOutcome, AuditRecord, AuditTarget},
+        auth::JwtAccessData,
+        dto::law_transaction::{CreateLawTransactionDto, LawTransactionReasonDto},
+    },
+    state::AppState,
+};
+
+#[post("")]
+pub(super) async fn create(
+    req: HttpRequest,
+    json: Json<CreateLawTransactionDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let uid = user.unwrap().uid;
+    let clonned_state = state.clone();
+    let block_result = web::block(move || state.transaction_service().create(&uid, &json)).await;
+
+    match block_result {
+        Ok(Ok(transaction)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::new(
+                    AuditActor::User(uid),
+                    AuditAction::LawTransactionCreate,
+                    AuditOutcome::Success,
+                )
+                .target(AuditTarget::LawTransaction(transaction.uid)),
+            )
+            .await;
+
+            HttpResponse::Created().json(transaction)
+        }
+        Ok(Err(err)) => transaction_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[post("/{uid}/accept")]
+pub(super) async fn accept(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    state: Data<AppState>,
+) -> impl Responder {
+    transition(
+        req,
+        state,
+        path.into_inner(),
+        LawTransactionsStatues::Processing,
+        None,
+        AuditAction::LawTransactionAccept,
+    )
+    .await
+}
+
+#[post("/{uid}/decline")]
+pub(super) async fn decline(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    json: Json<LawTransactionReasonDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    transition(
+        req,
+        state,
+        path.into_inner(),
+        LawTransactionsStatues::Declined,
+        json.into_inner().reason,
+        AuditAction::LawTransactionDecline,
+    )
+    .await
+}
+
+#[post("/{uid}/complete")]
+pub(super) async fn complete(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    state: Data<AppState>,
+) -> impl Responder {
+    transition(
+        req,
+        state,
+        path.into_inner(),
+        LawTransactionsStatues::Completed,
+        None,
+        AuditAction::LawTransactionComplete,
+    )
+    .await
+}
+
+#[post("/{uid}/cancel")]
+pub(super) async fn cancel(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    json: Json<LawTransactionReasonDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    transition(
+        req,
+        state,
+        path.into_inner(),
+        LawTransactionsStatues::Cancelled,
+        json.into_inner().reason,
+        AuditAction::LawTransactionCancel,
+    )
+    .await
+}
+
+async fn transition(
+    req: HttpRequest,
+    state: Data<AppState>,
+    transaction_uid: Uuid,
+    next: LawTransactionsStatues,
+    reason: Option<String>,
+    action: AuditAction,
+) -> HttpResponse {
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let uid = user.unwrap().uid;
+    let clonned_state = state.clone();
+    let block_result = web::block(move || {
+        state
+            .transaction_service()
+            .transition(&uid, &transaction_uid, next, reason.as_deref())
+    })
+    .await;
+
+    match block_result {
+        Ok(Ok(transaction)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::new(AuditActor::User(uid), action, AuditOutcome::Success)
+                    .target(AuditTarget::LawTransaction(transaction.uid)),
+            )
+            .await;
+
+            HttpResponse::Ok().json(transaction)
+        }
+        Ok(Err(err)) => transaction_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
@@ -1,26 +1,27 @@
-use actix_web::{delete, HttpRequest, web::{Data, Json, self}, Responder, HttpMessage, HttpResponse};
-
-use crate::{services::{auth::JwtAccessData, dto::user::DeleteLawsRequestResponse}, api::errors::JsonMessage, state::AppState};
-
-#[delete("")]
-async fn delete(req: HttpRequest, json: Json<DeleteLawsRequestResponse>, state: Data<AppState>) -> impl Responder {
-    let user = req.extensions().get::<JwtAccessData>().cloned();
-
-    if user.is_none() {
-        return HttpResponse::Forbidden().json(JsonMessage {
-            message: "token_not_found"
-        })
-    }
-
-    let user = user.unwrap();
-
-    if user.role != "admin" {
-        return HttpResponse::Forbidden().json(JsonMessage {
-            message: "no_rights"
-        });
-    }
-
+use actix_web::{delete, web::{Data, Json, self}, Responder, HttpResponse, HttpRequest};
+
+use crate::{
+    services::{
+        audit::{AuditAction, AuditOutcome, AuditRecord, AuditTarget},
+        dto::user::DeleteLawsRequestResponse,
+        permissions::Permission,
+    },
+    api::{
+        audit::{audit, request_actor},
+        errors::JsonMessage,
+        middlewares::authorize::RequirePermission,
+    },
+    state::AppState,
+};
+
+#[delete("", wrap = "RequirePermission::new(Permission::LawsDelete)")]
+async fn delete(
+    req: HttpRequest,
+    json: Json<DeleteLawsRequestResponse>,
+    state: Data<AppState>,
+) -> impl Responder {
     let uids = json.uids.clone();
+    let clonned_state = state.clone();
     let result = web::block(move || state.user_service().delete_laws(&json.uids)).await;
 
     if result.is_err() {
@@ -35,6 +36,16 @@ async fn delete(req: HttpRequest, json: Json<DeleteLawsRequestResponse>, state:
         });
     }
 
+    for uid in &uids {
+        audit(
+            &clonned_state,
+            &req,
+            AuditRecord::new(request_actor(&req), AuditAction::LawsDelete, AuditOutcome::Success)
+                .target(AuditTarget::Law(*uid)),
+        )
+        .await;
+    }
+
     return HttpResponse::Ok().json(DeleteLawsRequestResponse {
         uids
     });
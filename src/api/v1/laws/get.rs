@@ -1,51 +1,70 @@
-use actix_web::{get, HttpResponse, Responder, web::{Data, self}};
-use serde::Deserialize;
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpResponse, Responder,
+};
+use uuid::Uuid;
+use validator::Validate;
 
-use crate::{state::AppState, db::DbError, api::errors::JsonMessage, services::user::UserServiceError};
-
-#[derive(Deserialize)]
-pub struct LawPage {
-    pub page: Option<u64>,
-}
+use super::super::services::catalog_error_response;
+use crate::{
+    api::errors::{invalid_data, JsonMessage},
+    db::DbError,
+    services::{dto::user::LawsQuery, user::UserServiceError},
+    state::AppState,
+};
 
 #[get("")]
-pub(super) async fn get_laws(query: web::Query<LawPage>, state: Data<AppState>) -> impl Responder {
-    let mut page = 1_u64;
-
-    if query.page.is_some() {
-        
-        page = query.page.unwrap();
+pub(super) async fn get_laws(
+    query: web::Query<LawsQuery>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if query.validate().is_err() {
+        return invalid_data();
     }
 
-    let internal_error = HttpResponse::InternalServerError().json(
-        JsonMessage { message: "internal_error" }
-    );
-    let unreachable_error = HttpResponse::InternalServerError().json(
-        JsonMessage { message: "unreachable_code" }
-    );
-    let result = web::block(move ||
-        state.user_service().get_laws(page)
-    )
-        .await;
-
-    if result.is_err() {
-        return internal_error;
+    let result = web::block(move || state.user_service().get_laws(&query)).await;
+
+    match result {
+        Ok(Ok(laws)) => HttpResponse::Ok().json(laws),
+        Ok(Err(DbError::Execution(UserServiceError::InvalidPage))) => invalid_data(),
+        _ => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
     }
+}
 
-    let unwrapped_result = result.unwrap();
-
-    if let Err(e) = unwrapped_result {
-        return match e {
-            DbError::Execution(e) => match e {
-                UserServiceError::GetLaws => internal_error,
-                _ => unreachable_error,
-            },
-            DbError::Connection => internal_error,
-            _ => HttpResponse::InternalServerError().json(
-                JsonMessage { message: "unreachable_code" }
-            ),
+#[get("/{uid}")]
+pub(super) async fn get_law(path: web::Path<Uuid>, state: Data<AppState>) -> impl Responder {
+    let uid = path.into_inner();
+    let result = web::block(move || state.user_service().get_law(&uid)).await;
+
+    match result {
+        Ok(Ok(law)) => HttpResponse::Ok().json(law),
+        Ok(Err(DbError::Execution(UserServiceError::NotFound))) => {
+            HttpResponse::NotFound().json(JsonMessage {
+                message: "law_not_found",
+            })
         }
+        _ => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
     }
+}
 
-    return HttpResponse::Ok().json(unwrapped_result.unwrap());
+#[get("/{uid}/services")]
+pub(super) async fn get_law_services(
+    path: web::Path<Uuid>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let uid = path.into_inner();
+    let result = web::block(move || state.catalog_service().get_law_services(&uid)).await;
+
+    match result {
+        Ok(Ok(services)) => HttpResponse::Ok().json(services),
+        Ok(Err(err)) => catalog_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
 }
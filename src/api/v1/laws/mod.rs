@@ -10,6 +10,8 @@ use actix_web::web;
 pub(super) fn configure(_: Arc<Config>) -> impl Fn(&mut web::ServiceConfig) {
     move |cfg| {
         cfg.service(get::get_laws)
+            .service(get::get_law)
+            .service(get::get_law_services)
             .service(delete::delete);
     }
 }
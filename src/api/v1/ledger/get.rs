@@ -0,0 +1,50 @@
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpResponse, Responder,
+};
+use uuid::Uuid;
+
+use crate::{api::errors::JsonMessage, state::AppState};
+
+#[get("/verify")]
+pub(super) async fn verify_ledger(state: Data<AppState>) -> impl Responder {
+    let block_result = web::block(move || state.ledger_service().verify(state.config())).await;
+
+    match block_result {
+        Ok(Ok(verification)) => {
+            if let Some(broken) = &verification.first_broken {
+                log::warn!(
+                    "Security event: court case ledger broken at {} ({})",
+                    broken.seq,
+                    broken.reason
+                );
+            }
+
+            HttpResponse::Ok().json(verification)
+        }
+        _ => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[get("/court-cases/{uid}")]
+pub(super) async fn get_case_history(
+    path: web::Path<Uuid>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let court_case_uid = path.into_inner();
+    let block_result =
+        web::block(move || state.ledger_service().get_case_history(&court_case_uid)).await;
+
+    match block_result {
+        Ok(Ok(entries)) if entries.is_empty() => HttpResponse::NotFound().json(JsonMessage {
+            message: "court_case_not_found",
+        }),
+        Ok(Ok(entries)) => HttpResponse::Ok().json(entries),
+        _ => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
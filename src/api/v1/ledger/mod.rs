@@ -0,0 +1,14 @@
+mod get;
+
+use std::sync::Arc;
+
+use crate::config::Config;
+
+use actix_web::web;
+
+pub(super) fn configure(_: Arc<Config>) -> impl Fn(&mut web::ServiceConfig) {
+    move |cfg| {
+        cfg.service(get::verify_ledger)
+            .service(get::get_case_history);
+    }
+}
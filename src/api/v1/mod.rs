@@ -1,19 +1,77 @@
+mod audit_events;
 mod auth;
+mod invoices;
+mod law_applications;
+mod law_transactions;
 mod laws;
+mod ledger;
+mod passports;
+mod service_accounts;
+mod services;
+mod users;
 
 use crate::config::Config;
 use actix_web::web;
 use std::sync::Arc;
 
-use super::middlewares::authenticate::JwtAuth;
+use super::middlewares::{authenticate::JwtAuth, authorize::RequirePermission};
+use crate::services::permissions::Permission;
 
 pub(super) fn configure(config: Arc<Config>) -> impl Fn(&mut web::ServiceConfig) {
     move |cfg| {
         cfg.service(
+            web::scope("/audit-events")
+                .wrap(RequirePermission::new(Permission::AuditRead))
+                .wrap(JwtAuth::new(config.clone()).with_api_keys())
+                .configure(audit_events::configure(config.clone())),
+        )
+        .service(
+            web::scope("/invoices")
+                .wrap(JwtAuth::new(config.clone()).with_api_keys())
+                .configure(invoices::configure(config.clone())),
+        )
+        .service(
+            web::scope("/law-applications")
+                .wrap(JwtAuth::new(config.clone()).with_api_keys())
+                .configure(law_applications::configure(config.clone())),
+        )
+        .service(
+            web::scope("/law-transactions")
+                .wrap(JwtAuth::new(config.clone()))
+                .configure(law_transactions::configure(config.clone())),
+        )
+        .service(
             web::scope("/laws")
                 .wrap(JwtAuth::new(config.clone()))
                 .configure(laws::configure(config.clone())),
         )
+        .service(
+            web::scope("/ledger")
+                .wrap(RequirePermission::new(Permission::LedgerRead))
+                .wrap(JwtAuth::new(config.clone()).with_api_keys())
+                .configure(ledger::configure(config.clone())),
+        )
+        .service(
+            web::scope("/passports")
+                .wrap(JwtAuth::new(config.clone()).with_api_keys())
+                .configure(passports::configure(config.clone())),
+        )
+        .service(
+            web::scope("/service-accounts")
+                .wrap(RequirePermission::new(Permission::ApiKeysManage))
+                .wrap(JwtAuth::new(config.clone()).with_api_keys())
+                .configure(service_accounts::configure(config.clone())),
+        )
+        .service(
+            web::scope("/services")
+                .wrap(JwtAuth::new(config.clone()).with_api_keys())
+                .configure(services::configure(config.clone())),
+        )
+        .service(
+            web::scope("/users")
+                .wrap(JwtAuth::new(config.clone()).with_api_keys())
+                .configure(users::configure(config.clone())),
+        )
         .service(web::scope("/auth").configure(auth::configure(config.clone())));
     }
 }
@@ -0,0 +1,161 @@
+use actix_web::{
+    get,
+    web::{self, Data, Query},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::passport_error_response;
+use crate::{
+    api::{
+        audit::{audit_disclosure, request_actor},
+        errors::{invalid_data, JsonMessage},
+        middlewares::authorize::RequirePermission,
+    },
+    services::{
+        audit::{AuditAction, AuditOutcome, AuditRecord, AuditTarget},
+        auth::JwtAccessData,
+        permissions::Permission,
+    },
+    state::AppState,
+};
+
+#[derive(Deserialize, Validate)]
+pub(super) struct PassportNumberQuery {
+    #[validate(length(min = 1, max = 32))]
+    number: String,
+}
+
+#[derive(Serialize)]
+struct ProfilesResponse {
+    profile_uids: Vec<Uuid>,
+}
+
+#[get("/me")]
+pub(super) async fn get_own_passport(req: HttpRequest, state: Data<AppState>) -> impl Responder {
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let block_result =
+        web::block(move || state.passport_service().get_own(&user.uid, state.config())).await;
+
+    match block_result {
+        Ok(Ok(passport)) => HttpResponse::Ok().json(passport),
+        Ok(Err(err)) => passport_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[get("/search", wrap = "RequirePermission::new(Permission::PassportsRead)")]
+pub(super) async fn find_by_number(
+    req: HttpRequest,
+    query: Query<PassportNumberQuery>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if query.validate().is_err() {
+        return invalid_data();
+    }
+
+    let clonned_state = state.clone();
+    let block_result = web::block(move || {
+        state
+            .passport_service()
+            .find_profiles_by_number(&query.number, state.config())
+    })
+    .await;
+
+    match block_result {
+        Ok(Ok(profile_uids)) => {
+            if let Err(response) = audit_disclosure(
+                &clonned_state,
+                &req,
+                AuditRecord::new(
+                    request_actor(&req),
+                    AuditAction::PassportSearch,
+                    AuditOutcome::Success,
+                )
+                .details(format!("{} found", profile_uids.len())),
+            )
+            .await
+            {
+                return response;
+            }
+
+            HttpResponse::Ok().json(ProfilesResponse { profile_uids })
+        }
+        _ => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[get("/{uid}", wrap = "RequirePermission::new(Permission::PassportsRead)")]
+pub(super) async fn get_passport(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let profile_uid = path.into_inner();
+    let clonned_state = state.clone();
+    let block_result = web::block(move || {
+        state
+            .passport_service()
+            .get_by_profile(&profile_uid, state.config())
+    })
+    .await;
+
+    match block_result {
+        Ok(Ok(passport)) => {
+            if let Err(response) = audit_disclosure(
+                &clonned_state,
+                &req,
+                AuditRecord::new(
+                    request_actor(&req),
+                    AuditAction::PassportView,
+                    AuditOutcome::Success,
+                )
+                .target(AuditTarget::Passport(passport.uid)),
+            )
+            .await
+            {
+                return response;
+            }
+
+            HttpResponse::Ok().json(passport)
+        }
+        Ok(Err(err)) => passport_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[get(
+    "/{uid}/history",
+    wrap = "RequirePermission::new(Permission::PassportsRead)"
+)]
+pub(super) async fn get_passport_history(
+    path: web::Path<Uuid>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let profile_uid = path.into_inner();
+    let block_result = web::block(move || state.passport_service().get_history(&profile_uid)).await;
+
+    match block_result {
+        Ok(Ok(history)) => HttpResponse::Ok().json(history),
+        Ok(Err(err)) => passport_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
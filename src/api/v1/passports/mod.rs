@@ -0,0 +1,49 @@
+mod get;
+mod patch;
+mod post;
+
+use std::sync::Arc;
+
+use crate::{
+    api::errors::{no_rights, JsonMessage},
+    config::Config,
+    db::DbError,
+    services::passports::PassportServiceError,
+};
+
+use actix_web::{web, HttpResponse};
+
+pub(super) fn configure(_: Arc<Config>) -> impl Fn(&mut web::ServiceConfig) {
+    move |cfg| {
+        cfg.service(get::get_own_passport)
+            .service(patch::update_own_passport)
+            .service(get::find_by_number)
+            .service(get::get_passport)
+            .service(get::get_passport_history)
+            .service(post::verify_passport);
+    }
+}
+
+fn passport_error_response(err: DbError<PassportServiceError>) -> HttpResponse {
+    match err {
+        DbError::Execution(PassportServiceError::NotFound) => {
+            HttpResponse::NotFound().json(JsonMessage {
+                message: "passport_not_found",
+            })
+        }
+        DbError::Execution(PassportServiceError::AlreadyVerified) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "passport_already_verified",
+            })
+        }
+        DbError::Execution(PassportServiceError::Incomplete) => {
+            HttpResponse::BadRequest().json(JsonMessage {
+                message: "passport_incomplete",
+            })
+        }
+        DbError::Execution(PassportServiceError::SelfVerification) => no_rights(),
+        _ => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
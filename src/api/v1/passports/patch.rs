@@ -0,0 +1,71 @@
+use actix_web::{
+    patch,
+    web::{self, Data, Json},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use validator::Validate;
+
+use super::passport_error_response;
+use crate::{
+    api::{
+        audit::audit,
+        errors::{invalid_data, JsonMessage},
+    },
+    services::{
+        audit::{AuditAction, AuditActor, AuditOutcome, AuditRecord, AuditTarget},
+        auth::JwtAccessData,
+        dto::passport::UpdatePassportDto,
+    },
+    state::AppState,
+};
+
+#[patch("/me")]
+pub(super) async fn update_own_passport(
+    req: HttpRequest,
+    json: Json<UpdatePassportDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let uid = user.uid;
+    let clonned_state = state.clone();
+    let block_result = web::block(move || {
+        state
+            .passport_service()
+            .update_own(&user.uid, &json, state.config())
+    })
+    .await;
+
+    match block_result {
+        Ok(Ok(passport)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::new(
+                    AuditActor::User(uid),
+                    AuditAction::PassportUpdate,
+                    AuditOutcome::Success,
+                )
+                .target(AuditTarget::Passport(passport.uid)),
+            )
+            .await;
+
+            HttpResponse::Ok().json(passport)
+        }
+        Ok(Err(err)) => passport_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
@@ -0,0 +1,68 @@
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use uuid::Uuid;
+
+use super::passport_error_response;
+use crate::{
+    api::{audit::audit, errors::JsonMessage, middlewares::authorize::RequirePermission},
+    services::{
+        audit::{AuditAction, AuditActor, AuditOutcome, AuditRecord, AuditTarget},
+        auth::JwtAccessData,
+        permissions::Permission,
+    },
+    state::AppState,
+};
+
+#[post(
+    "/{uid}/verify",
+    wrap = "RequirePermission::new(Permission::PassportsVerify)"
+)]
+pub(super) async fn verify_passport(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let uid = user.uid;
+    let profile_uid = path.into_inner();
+    let clonned_state = state.clone();
+    let block_result = web::block(move || {
+        state
+            .passport_service()
+            .verify(&profile_uid, &user.uid, state.config())
+    })
+    .await;
+
+    match block_result {
+        Ok(Ok(passport)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::new(
+                    AuditActor::User(uid),
+                    AuditAction::PassportVerify,
+                    AuditOutcome::Success,
+                )
+                .target(AuditTarget::Passport(passport.uid)),
+            )
+            .await;
+
+            HttpResponse::Ok().json(passport)
+        }
+        Ok(Err(err)) => passport_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
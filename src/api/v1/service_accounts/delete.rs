@@ -0,0 +1,54 @@
+use actix_web::{
+    delete,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Responder,
+};
+use uuid::Uuid;
+
+use super::api_key_error_response;
+use crate::{
+    api::{
+        audit::{audit, request_actor},
+        errors::JsonMessage,
+    },
+    services::audit::{AuditAction, AuditOutcome, AuditRecord, AuditTarget},
+    state::AppState,
+};
+
+#[delete("/{uid}/keys/{key_uid}")]
+pub(super) async fn revoke_key(
+    req: HttpRequest,
+    path: web::Path<(Uuid, Uuid)>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let (service_account_uid, key_uid) = path.into_inner();
+    let clonned_state = state.clone();
+    let block_result = web::block(move || {
+        state
+            .api_key_service()
+            .revoke_key(&service_account_uid, &key_uid)
+    })
+    .await;
+
+    match block_result {
+        Ok(Ok(_)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::new(
+                    request_actor(&req),
+                    AuditAction::ApiKeyRevoke,
+                    AuditOutcome::Success,
+                )
+                .target(AuditTarget::ApiKey(key_uid)),
+            )
+            .await;
+
+            HttpResponse::Ok().json(JsonMessage { message: "ok" })
+        }
+        Ok(Err(err)) => api_key_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
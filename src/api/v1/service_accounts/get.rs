@@ -0,0 +1,21 @@
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpResponse, Responder,
+};
+
+use super::api_key_error_response;
+use crate::{api::errors::JsonMessage, state::AppState};
+
+#[get("")]
+pub(super) async fn get_service_accounts(state: Data<AppState>) -> impl Responder {
+    let block_result = web::block(move || state.api_key_service().get_service_accounts()).await;
+
+    match block_result {
+        Ok(Ok(accounts)) => HttpResponse::Ok().json(accounts),
+        Ok(Err(err)) => api_key_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
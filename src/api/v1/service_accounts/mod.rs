@@ -0,0 +1,44 @@
+mod delete;
+mod get;
+mod post;
+
+use std::sync::Arc;
+
+use crate::{
+    api::errors::JsonMessage, config::Config, db::DbError,
+    services::api_keys::ApiKeyServiceError,
+};
+
+use actix_web::{web, HttpResponse};
+
+pub(super) fn configure(_: Arc<Config>) -> impl Fn(&mut web::ServiceConfig) {
+    move |cfg| {
+        cfg.service(get::get_service_accounts)
+            .service(post::create_service_account)
+            .service(post::issue_key)
+            .service(delete::revoke_key);
+    }
+}
+
+fn api_key_error_response(err: DbError<ApiKeyServiceError>) -> HttpResponse {
+    match err {
+        DbError::Execution(ApiKeyServiceError::AlreadyExists) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "already_exists",
+            })
+        }
+        DbError::Execution(ApiKeyServiceError::NotFound) => {
+            HttpResponse::NotFound().json(JsonMessage {
+                message: "not_found",
+            })
+        }
+        DbError::Execution(ApiKeyServiceError::InvalidScope) => {
+            HttpResponse::BadRequest().json(JsonMessage {
+                message: "invalid_scope",
+            })
+        }
+        _ => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
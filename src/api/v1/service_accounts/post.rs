@@ -0,0 +1,116 @@
+use actix_web::{
+    post,
+    web::{self, Data, Json},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::api_key_error_response;
+use crate::{
+    api::{
+        audit::{audit, request_actor},
+        errors::{invalid_data, JsonMessage},
+    },
+    services::{
+        audit::{AuditAction, AuditOutcome, AuditRecord, AuditTarget},
+        auth::JwtAccessData,
+        dto::api_keys::{IssueApiKeyDto, ServiceAccountDto},
+    },
+    state::AppState,
+};
+
+#[post("")]
+pub(super) async fn create_service_account(
+    req: HttpRequest,
+    json: Json<ServiceAccountDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let clonned_state = state.clone();
+    let block_result =
+        web::block(move || state.api_key_service().create_service_account(&json.name)).await;
+
+    match block_result {
+        Ok(Ok(account)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::new(
+                    request_actor(&req),
+                    AuditAction::ServiceAccountCreate,
+                    AuditOutcome::Success,
+                )
+                .target(AuditTarget::ServiceAccount(account.uid)),
+            )
+            .await;
+
+            HttpResponse::Created().json(account)
+        }
+        Ok(Err(err)) => api_key_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[post("/{uid}/keys")]
+pub(super) async fn issue_key(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    json: Json<IssueApiKeyDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    // Keys are issued by people, never by other keys
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let service_account_uid = path.into_inner();
+    let clonned_state = state.clone();
+    let block_result = web::block(move || {
+        state.api_key_service().issue_key(
+            &service_account_uid,
+            &json.name,
+            &json.scopes,
+            json.expires_at,
+            &user.uid,
+        )
+    })
+    .await;
+
+    match block_result {
+        Ok(Ok(issued)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::new(
+                    request_actor(&req),
+                    AuditAction::ApiKeyIssue,
+                    AuditOutcome::Success,
+                )
+                .target(AuditTarget::ApiKey(issued.api_key.uid))
+                .details(issued.api_key.scopes.join(" ")),
+            )
+            .await;
+
+            HttpResponse::Created().json(issued)
+        }
+        Ok(Err(err)) => api_key_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
@@ -0,0 +1,40 @@
+use actix_web::{
+    get,
+    web::{self, Data, Query},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+
+use super::catalog_error_response;
+use crate::{
+    api::{errors::JsonMessage, middlewares::authorize::RequirePermission},
+    services::{auth::JwtAccessData, dto::catalog::OwnServicesQuery, permissions::Permission},
+    state::AppState,
+};
+
+#[get("/me", wrap = "RequirePermission::new(Permission::ServicesManage)")]
+pub(super) async fn get_own_services(
+    req: HttpRequest,
+    query: Query<OwnServicesQuery>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let archived = query.archived.unwrap_or(false);
+    let block_result =
+        web::block(move || state.catalog_service().get_own(&user.uid, archived)).await;
+
+    match block_result {
+        Ok(Ok(services)) => HttpResponse::Ok().json(services),
+        Ok(Err(err)) => catalog_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
@@ -0,0 +1,47 @@
+mod get;
+mod patch;
+mod post;
+
+use std::sync::Arc;
+
+use crate::{
+    api::errors::{no_rights, JsonMessage},
+    config::Config,
+    db::DbError,
+    services::catalog::CatalogServiceError,
+};
+
+use actix_web::{web, HttpResponse};
+
+pub(super) fn configure(_: Arc<Config>) -> impl Fn(&mut web::ServiceConfig) {
+    move |cfg| {
+        cfg.service(get::get_own_services)
+            .service(post::create)
+            .service(patch::update)
+            .service(post::archive);
+    }
+}
+
+pub(super) fn catalog_error_response(err: DbError<CatalogServiceError>) -> HttpResponse {
+    match err {
+        DbError::Execution(CatalogServiceError::NotLaw) => no_rights(),
+        DbError::Execution(CatalogServiceError::LawNotFound) => {
+            HttpResponse::NotFound().json(JsonMessage {
+                message: "law_not_found",
+            })
+        }
+        DbError::Execution(CatalogServiceError::NotFound) => {
+            HttpResponse::NotFound().json(JsonMessage {
+                message: "service_not_found",
+            })
+        }
+        DbError::Execution(CatalogServiceError::Archived) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "service_archived",
+            })
+        }
+        _ => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
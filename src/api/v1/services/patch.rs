@@ -0,0 +1,71 @@
+use actix_web::{
+    patch,
+    web::{self, Data, Json},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::catalog_error_response;
+use crate::{
+    api::{
+        audit::audit,
+        errors::{invalid_data, JsonMessage},
+        middlewares::authorize::RequirePermission,
+    },
+    services::{
+        audit::{AuditAction, AuditActor, AuditOutcome, AuditRecord, AuditTarget},
+        auth::JwtAccessData,
+        dto::catalog::UpdateServiceDto,
+        permissions::Permission,
+    },
+    state::AppState,
+};
+
+#[patch("/{uid}", wrap = "RequirePermission::new(Permission::ServicesManage)")]
+pub(super) async fn update(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    json: Json<UpdateServiceDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let uid = user.unwrap().uid;
+    let service_uid = path.into_inner();
+    let clonned_state = state.clone();
+    let block_result =
+        web::block(move || state.catalog_service().update(&uid, &service_uid, &json)).await;
+
+    match block_result {
+        Ok(Ok(service)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::new(
+                    AuditActor::User(uid),
+                    AuditAction::ServiceUpdate,
+                    AuditOutcome::Success,
+                )
+                .target(AuditTarget::Service(service.uid)),
+            )
+            .await;
+
+            HttpResponse::Ok().json(service)
+        }
+        Ok(Err(err)) => catalog_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
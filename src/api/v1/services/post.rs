@@ -0,0 +1,114 @@
+use actix_web::{
+    post,
+    web::{self, Data, Json},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::catalog_error_response;
+use crate::{
+    api::{
+        audit::audit,
+        errors::{invalid_data, JsonMessage},
+        middlewares::authorize::RequirePermission,
+    },
+    services::{
+        audit::{AuditAction, AuditActor, AuditOutcome, AuditRecord, AuditTarget},
+        auth::JwtAccessData,
+        dto::catalog::CreateServiceDto,
+        permissions::Permission,
+    },
+    state::AppState,
+};
+
+#[post("", wrap = "RequirePermission::new(Permission::ServicesManage)")]
+pub(super) async fn create(
+    req: HttpRequest,
+    json: Json<CreateServiceDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let uid = user.unwrap().uid;
+    let clonned_state = state.clone();
+    let block_result = web::block(move || state.catalog_service().create(&uid, &json)).await;
+
+    match block_result {
+        Ok(Ok(service)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::new(
+                    AuditActor::User(uid),
+                    AuditAction::ServiceCreate,
+                    AuditOutcome::Success,
+                )
+                .target(AuditTarget::Service(service.uid)),
+            )
+            .await;
+
+            HttpResponse::Created().json(service)
+        }
+        Ok(Err(err)) => catalog_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[post(
+    "/{uid}/archive",
+    wrap = "RequirePermission::new(Permission::ServicesManage)"
+)]
+pub(super) async fn archive(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let uid = user.unwrap().uid;
+    let service_uid = path.into_inner();
+    let clonned_state = state.clone();
+    let block_result =
+        web::block(move || state.catalog_service().archive(&uid, &service_uid)).await;
+
+    match block_result {
+        Ok(Ok(service)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::new(
+                    AuditActor::User(uid),
+                    AuditAction::ServiceArchive,
+                    AuditOutcome::Success,
+                )
+                .target(AuditTarget::Service(service.uid)),
+            )
+            .await;
+
+            HttpResponse::Ok().json(service)
+        }
+        Ok(Err(err)) => catalog_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
@@ -0,0 +1,67 @@
+use actix_web::{
+    delete,
+    web::{self, Data},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use uuid::Uuid;
+
+use super::{end_sessions, user_error_response};
+use crate::{
+    api::{audit::audit, errors::JsonMessage, middlewares::authorize::RequirePermission},
+    services::{
+        audit::{AuditAction, AuditActor, AuditOutcome, AuditRecord, AuditTarget},
+        auth::JwtAccessData,
+        permissions::Permission,
+    },
+    state::AppState,
+};
+
+#[delete("/{uid}", wrap = "RequirePermission::new(Permission::UsersManage)")]
+pub(super) async fn delete_user(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let internal_error = HttpResponse::InternalServerError().json(JsonMessage {
+        message: "internal_error",
+    });
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let admin_uid = user.unwrap().uid;
+    let profile_uid = path.into_inner();
+    let clonned_state = state.clone();
+    let block_result =
+        web::block(move || state.user_service().soft_delete(&admin_uid, &profile_uid)).await;
+
+    let auth_uid = match block_result {
+        Ok(Ok(auth_uid)) => auth_uid,
+        Ok(Err(err)) => return user_error_response(err),
+        Err(_) => return internal_error,
+    };
+
+    if let Some(auth_uid) = auth_uid {
+        if end_sessions(&clonned_state, &auth_uid).is_err() {
+            return internal_error;
+        }
+
+        audit(
+            &clonned_state,
+            &req,
+            AuditRecord::new(
+                AuditActor::User(admin_uid),
+                AuditAction::UserDelete,
+                AuditOutcome::Success,
+            )
+            .target(AuditTarget::User(auth_uid)),
+        )
+        .await;
+    }
+
+    HttpResponse::Ok().json(JsonMessage { message: "ok" })
+}
@@ -0,0 +1,70 @@
+use actix_web::{
+    get,
+    web::{self, Data, Query},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::user_error_response;
+use crate::{
+    api::{
+        errors::{invalid_data, JsonMessage},
+        middlewares::authorize::RequirePermission,
+    },
+    services::{auth::JwtAccessData, dto::user::UsersQuery, permissions::Permission},
+    state::AppState,
+};
+
+#[get("/me")]
+pub(super) async fn get_me(req: HttpRequest, state: Data<AppState>) -> impl Responder {
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let block_result = web::block(move || state.user_service().get_current_user(&user.uid)).await;
+
+    match block_result {
+        Ok(Ok(current_user)) => HttpResponse::Ok().json(current_user),
+        Ok(Err(err)) => user_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[get("", wrap = "RequirePermission::new(Permission::UsersManage)")]
+pub(super) async fn get_users(query: Query<UsersQuery>, state: Data<AppState>) -> impl Responder {
+    if query.validate().is_err() {
+        return invalid_data();
+    }
+
+    let block_result = web::block(move || state.user_service().get_users(&query)).await;
+
+    match block_result {
+        Ok(Ok(users)) => HttpResponse::Ok().json(users),
+        Ok(Err(err)) => user_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[get("/{uid}", wrap = "RequirePermission::new(Permission::UsersManage)")]
+pub(super) async fn get_user(path: web::Path<Uuid>, state: Data<AppState>) -> impl Responder {
+    let profile_uid = path.into_inner();
+    let block_result = web::block(move || state.user_service().get_user(&profile_uid)).await;
+
+    match block_result {
+        Ok(Ok(user)) => HttpResponse::Ok().json(user),
+        Ok(Err(err)) => user_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
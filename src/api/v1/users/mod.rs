@@ -0,0 +1,95 @@
+mod delete;
+mod get;
+mod patch;
+mod post;
+
+use std::sync::Arc;
+
+use crate::{
+    api::errors::{invalid_data, JsonMessage},
+    config::Config,
+    db::DbError,
+    services::{auth::AuthService, user::UserServiceError},
+    state::AppState,
+};
+
+use actix_web::{web, HttpResponse};
+use uuid::Uuid;
+
+pub(super) fn configure(_: Arc<Config>) -> impl Fn(&mut web::ServiceConfig) {
+    move |cfg| {
+        cfg.service(get::get_me)
+            .service(patch::update_me)
+            .service(get::get_users)
+            .service(get::get_user)
+            .service(patch::change_role)
+            .service(post::force_logout)
+            .service(post::unlock)
+            .service(post::block)
+            .service(post::unblock)
+            .service(delete::delete_user);
+    }
+}
+
+fn user_error_response(err: DbError<UserServiceError<()>>) -> HttpResponse {
+    match err {
+        DbError::Execution(UserServiceError::NotFound) => {
+            HttpResponse::NotFound().json(JsonMessage {
+                message: "user_not_found",
+            })
+        }
+        DbError::Execution(UserServiceError::AvatarNotFound) => {
+            HttpResponse::NotFound().json(JsonMessage {
+                message: "file_not_found",
+            })
+        }
+        DbError::Execution(UserServiceError::PassportNotFound) => {
+            HttpResponse::NotFound().json(JsonMessage {
+                message: "passport_not_found",
+            })
+        }
+        DbError::Execution(UserServiceError::PassportVerified) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "passport_already_verified",
+            })
+        }
+        DbError::Execution(UserServiceError::SelfModification) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "self_modification",
+            })
+        }
+        DbError::Execution(UserServiceError::LawRoleChange) => {
+            HttpResponse::Conflict().json(JsonMessage {
+                message: "law_role_change",
+            })
+        }
+        DbError::Execution(UserServiceError::InvalidPage) => invalid_data(),
+        _ => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+/// Access tokens issued so far are rejected, the next refresh picks up the
+/// current role
+pub(super) fn deny_access_tokens(state: &AppState, auth_uid: &Uuid) -> Result<(), ()> {
+    let now = chrono::Utc::now();
+    let access_tokens_ttl = (now + AuthService::access_token_lifetime()).timestamp() as usize;
+
+    state
+        .redis()
+        .deny_user_access_tokens(auth_uid, now.timestamp_millis() as usize, access_tokens_ttl)
+        .map(|_| ())
+        .map_err(|_| ())
+}
+
+/// Logs the user out everywhere, refresh tokens included
+fn end_sessions(state: &AppState, auth_uid: &Uuid) -> Result<(), ()> {
+    deny_access_tokens(state, auth_uid)?;
+
+    state
+        .session_service()
+        .revoke_all(auth_uid)
+        .map(|_| ())
+        .map_err(|_| ())
+}
@@ -0,0 +1,125 @@
+use actix_web::{
+    patch,
+    web::{self, Data, Json},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{deny_access_tokens, user_error_response};
+use crate::{
+    api::{
+        audit::audit,
+        errors::{invalid_data, JsonMessage},
+        middlewares::authorize::RequirePermission,
+    },
+    services::{
+        audit::{AuditAction, AuditActor, AuditOutcome, AuditRecord, AuditTarget},
+        auth::JwtAccessData,
+        dto::user::{ChangeRoleDto, UpdateCurrentUserDto},
+        permissions::Permission,
+    },
+    state::AppState,
+};
+
+#[patch("/me")]
+pub(super) async fn update_me(
+    req: HttpRequest,
+    json: Json<UpdateCurrentUserDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    if json.validate().is_err() {
+        return invalid_data();
+    }
+
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let user = user.unwrap();
+    let uid = user.uid;
+    let clonned_state = state.clone();
+    let block_result =
+        web::block(move || state.user_service().update_current_user(&user.uid, &json)).await;
+
+    match block_result {
+        Ok(Ok(current_user)) => {
+            audit(
+                &clonned_state,
+                &req,
+                AuditRecord::own_account(uid, AuditAction::ProfileUpdate, AuditOutcome::Success),
+            )
+            .await;
+
+            HttpResponse::Ok().json(current_user)
+        }
+        Ok(Err(err)) => user_error_response(err),
+        Err(_) => HttpResponse::InternalServerError().json(JsonMessage {
+            message: "internal_error",
+        }),
+    }
+}
+
+#[patch(
+    "/{uid}/role",
+    wrap = "RequirePermission::new(Permission::UsersManage)"
+)]
+pub(super) async fn change_role(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    json: Json<ChangeRoleDto>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let internal_error = HttpResponse::InternalServerError().json(JsonMessage {
+        message: "internal_error",
+    });
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let admin_uid = user.unwrap().uid;
+    let profile_uid = path.into_inner();
+    let role = json.role;
+    let clonned_state = state.clone();
+    let block_result = web::block(move || {
+        state
+            .user_service()
+            .change_role(&admin_uid, &profile_uid, role)
+    })
+    .await;
+
+    let auth_uid = match block_result {
+        Ok(Ok(auth_uid)) => auth_uid,
+        Ok(Err(err)) => return user_error_response(err),
+        Err(_) => return internal_error,
+    };
+
+    if let Some(auth_uid) = auth_uid {
+        if deny_access_tokens(&clonned_state, &auth_uid).is_err() {
+            return internal_error;
+        }
+
+        audit(
+            &clonned_state,
+            &req,
+            AuditRecord::new(
+                AuditActor::User(admin_uid),
+                AuditAction::UserRoleChange,
+                AuditOutcome::Success,
+            )
+            .target(AuditTarget::User(auth_uid))
+            .details(format!("role: {}", <&str>::from(role))),
+        )
+        .await;
+    }
+
+    HttpResponse::Ok().json(JsonMessage { message: "ok" })
+}
@@ -0,0 +1,190 @@
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use uuid::Uuid;
+
+use super::{end_sessions, user_error_response};
+use crate::{
+    api::{
+        audit::{audit, request_actor},
+        errors::JsonMessage,
+        middlewares::authorize::RequirePermission,
+    },
+    db::DbError,
+    services::{
+        audit::{AuditAction, AuditActor, AuditOutcome, AuditRecord, AuditTarget},
+        auth::{AuthServiceError, JwtAccessData},
+        permissions::Permission,
+    },
+    state::AppState,
+};
+
+#[post(
+    "/{uid}/force-logout",
+    wrap = "RequirePermission::new(Permission::UsersForceLogout)"
+)]
+pub(super) async fn force_logout(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let internal_error = HttpResponse::InternalServerError().json(JsonMessage {
+        message: "internal_error",
+    });
+    let profile_uid = path.into_inner();
+    let clonned_state = state.clone();
+    let block_result =
+        web::block(move || state.auth_service().find_auth_uid_by_profile(&profile_uid)).await;
+
+    if block_result.is_err() {
+        return internal_error;
+    }
+
+    let auth_uid = match block_result.unwrap() {
+        Ok(auth_uid) => auth_uid,
+        Err(DbError::Execution(AuthServiceError::UserNotFound)) => {
+            return HttpResponse::NotFound().json(JsonMessage {
+                message: "user_not_found",
+            })
+        }
+        Err(_) => return internal_error,
+    };
+
+    if end_sessions(&clonned_state, &auth_uid).is_err() {
+        return internal_error;
+    }
+
+    audit(
+        &clonned_state,
+        &req,
+        AuditRecord::new(request_actor(&req), AuditAction::UserForceLogout, AuditOutcome::Success)
+            .target(AuditTarget::User(auth_uid)),
+    )
+    .await;
+
+    HttpResponse::Ok().json(JsonMessage { message: "ok" })
+}
+
+#[post("/{uid}/unlock", wrap = "RequirePermission::new(Permission::UsersUnlock)")]
+pub(super) async fn unlock(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    state: Data<AppState>,
+) -> impl Responder {
+    let internal_error = HttpResponse::InternalServerError().json(JsonMessage {
+        message: "internal_error",
+    });
+    let profile_uid = path.into_inner();
+    let clonned_state = state.clone();
+    let block_result =
+        web::block(move || state.auth_service().find_auth_uid_by_profile(&profile_uid)).await;
+
+    if block_result.is_err() {
+        return internal_error;
+    }
+
+    let auth_uid = match block_result.unwrap() {
+        Ok(auth_uid) => auth_uid,
+        Err(DbError::Execution(AuthServiceError::UserNotFound)) => {
+            return HttpResponse::NotFound().json(JsonMessage {
+                message: "user_not_found",
+            })
+        }
+        Err(_) => return internal_error,
+    };
+
+    if clonned_state
+        .login_throttle_service()
+        .unlock_account(&auth_uid)
+        .is_err()
+    {
+        return internal_error;
+    }
+
+    audit(
+        &clonned_state,
+        &req,
+        AuditRecord::new(request_actor(&req), AuditAction::UserUnlock, AuditOutcome::Success)
+            .target(AuditTarget::User(auth_uid)),
+    )
+    .await;
+
+    HttpResponse::Ok().json(JsonMessage { message: "ok" })
+}
+
+#[post("/{uid}/block", wrap = "RequirePermission::new(Permission::UsersManage)")]
+pub(super) async fn block(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    state: Data<AppState>,
+) -> impl Responder {
+    set_blocked(req, path.into_inner(), state, true).await
+}
+
+#[post("/{uid}/unblock", wrap = "RequirePermission::new(Permission::UsersManage)")]
+pub(super) async fn unblock(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    state: Data<AppState>,
+) -> impl Responder {
+    set_blocked(req, path.into_inner(), state, false).await
+}
+
+async fn set_blocked(
+    req: HttpRequest,
+    profile_uid: Uuid,
+    state: Data<AppState>,
+    blocked: bool,
+) -> HttpResponse {
+    let internal_error = HttpResponse::InternalServerError().json(JsonMessage {
+        message: "internal_error",
+    });
+    let user = req.extensions().get::<JwtAccessData>().cloned();
+
+    if user.is_none() {
+        return HttpResponse::Forbidden().json(JsonMessage {
+            message: "token_not_found",
+        });
+    }
+
+    let admin_uid = user.unwrap().uid;
+    let clonned_state = state.clone();
+    let block_result = web::block(move || {
+        state
+            .user_service()
+            .set_blocked(&admin_uid, &profile_uid, blocked)
+    })
+    .await;
+
+    let auth_uid = match block_result {
+        Ok(Ok(auth_uid)) => auth_uid,
+        Ok(Err(err)) => return user_error_response(err),
+        Err(_) => return internal_error,
+    };
+
+    if let Some(auth_uid) = auth_uid {
+        if blocked && end_sessions(&clonned_state, &auth_uid).is_err() {
+            return internal_error;
+        }
+
+        audit(
+            &clonned_state,
+            &req,
+            AuditRecord::new(
+                AuditActor::User(admin_uid),
+                if blocked {
+                    AuditAction::UserBlock
+                } else {
+                    AuditAction::UserUnblock
+                },
+                AuditOutcome::Success,
+            )
+            .target(AuditTarget::User(auth_uid)),
+        )
+        .await;
+    }
+
+    HttpResponse::Ok().json(JsonMessage { message: "ok" })
+}
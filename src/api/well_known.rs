@@ -0,0 +1,8 @@
+use actix_web::{get, web::Data, HttpResponse, Responder};
+
+use crate::{services::auth::SecretsProvider, state::AppState};
+
+#[get("/.well-known/jwks.json")]
+pub(super) async fn jwks(state: Data<AppState>) -> impl Responder {
+    HttpResponse::Ok().json(state.config().access_keys().jwks())
+}
@@ -1,4 +1,5 @@
 use redis::{Client, Connection};
+use uuid::Uuid;
 
 #[derive(Debug)]
 pub enum CacheError<T> {
@@ -9,6 +10,11 @@ pub enum CacheError<T> {
     ExpireSet,
     GetPair,
     Remove,
+    SetAdd,
+    SetMembers,
+    SetRemove,
+    Increment,
+    Transaction,
 }
 
 pub struct Cache {
@@ -51,7 +57,7 @@ impl Cache {
             redis::cmd("SET")
                 .arg(key)
                 .arg(value)
-                .query(conn)
+                .query::<()>(conn)
                 .map_err(|err| {
                     log::error!("{:?}", err);
                     CacheError::AddPair
@@ -59,7 +65,7 @@ impl Cache {
             redis::cmd("EXPIREAT")
                 .arg(key)
                 .arg(ttl)
-                .query(conn)
+                .query::<()>(conn)
                 .map_err(|err| {
                     log::error!("{:?}", err);
                     CacheError::ExpireSet
@@ -80,6 +86,19 @@ impl Cache {
         })
     }
 
+    /// Gets the value and removes the key in one step, so only one caller
+    /// ever sees it
+    pub fn take_pair(&self, key: &str) -> Result<Option<String>, CacheError<CacheError<()>>> {
+        self.apply(|conn| {
+            let value: Option<String> = redis::cmd("GETDEL").arg(key).query(conn).map_err(|err| {
+                log::error!("{:?}", err);
+                CacheError::GetPair
+            })?;
+
+            Ok(value)
+        })
+    }
+
     pub fn remove(&self, key: &str) -> Result<(), CacheError<CacheError<()>>> {
         self.apply(|conn| {
             let _: Option<i32> = redis::cmd("DEL").arg(key).query(conn).map_err(|err| {
@@ -90,4 +109,320 @@ impl Cache {
             Ok(())
         })
     }
+
+    pub fn increment(&self, key: &str, ttl: usize) -> Result<i64, CacheError<CacheError<()>>> {
+        self.apply(|conn| {
+            let value: i64 = redis::cmd("INCR").arg(key).query(conn).map_err(|err| {
+                log::error!("{:?}", err);
+                CacheError::Increment
+            })?;
+            redis::cmd("EXPIREAT")
+                .arg(key)
+                .arg(ttl)
+                .query::<()>(conn)
+                .map_err(|err| {
+                    log::error!("{:?}", err);
+                    CacheError::ExpireSet
+                })?;
+
+            Ok(value)
+        })
+    }
+
+    pub fn add_to_set(
+        &self,
+        key: &str,
+        member: &str,
+        ttl: usize,
+    ) -> Result<bool, CacheError<CacheError<()>>> {
+        self.apply(|conn| {
+            redis::cmd("SADD")
+                .arg(key)
+                .arg(member)
+                .query::<()>(conn)
+                .map_err(|err| {
+                    log::error!("{:?}", err);
+                    CacheError::SetAdd
+                })?;
+            redis::cmd("EXPIREAT")
+                .arg(key)
+                .arg(ttl)
+                .query::<()>(conn)
+                .map_err(|err| {
+                    log::error!("{:?}", err);
+                    CacheError::ExpireSet
+                })?;
+
+            Ok(true)
+        })
+    }
+
+    pub fn get_set(&self, key: &str) -> Result<Vec<String>, CacheError<CacheError<()>>> {
+        self.apply(|conn| {
+            let members: Vec<String> =
+                redis::cmd("SMEMBERS").arg(key).query(conn).map_err(|err| {
+                    log::error!("{:?}", err);
+                    CacheError::SetMembers
+                })?;
+
+            Ok(members)
+        })
+    }
+
+    pub fn remove_from_set(&self, key: &str, member: &str) -> Result<(), CacheError<CacheError<()>>> {
+        self.apply(|conn| {
+            let _: Option<i32> = redis::cmd("SREM")
+                .arg(key)
+                .arg(member)
+                .query(conn)
+                .map_err(|err| {
+                    log::error!("{:?}", err);
+                    CacheError::SetRemove
+                })?;
+
+            Ok(())
+        })
+    }
+
+    pub fn set_refresh_family(
+        &self,
+        family: &Uuid,
+        refresh_token: &str,
+        ttl: usize,
+    ) -> Result<bool, CacheError<CacheError<()>>> {
+        self.add_pair(&Self::refresh_family_key(family), refresh_token, ttl)
+    }
+
+    pub fn get_refresh_family(
+        &self,
+        family: &Uuid,
+    ) -> Result<Option<String>, CacheError<CacheError<()>>> {
+        self.get_pair(&Self::refresh_family_key(family))
+    }
+
+    /// Moves the family from `current` to the next refresh token and its
+    /// access token in one transaction. When the family no longer points at
+    /// `current`, which is how a reused or concurrently rotated token looks,
+    /// the whole family is revoked and `false` is returned.
+    pub fn rotate_refresh_family(
+        &self,
+        family: &Uuid,
+        current: &str,
+        next_refresh_token: &str,
+        next_access_token: &str,
+        ttl: usize,
+    ) -> Result<bool, CacheError<CacheError<()>>> {
+        let family_key = Self::refresh_family_key(family);
+        let rotated = self.apply(|conn| {
+            redis::transaction(conn, &[&family_key], |conn, pipe| {
+                let latest: Option<String> = redis::cmd("GET").arg(&family_key).query(conn)?;
+
+                if latest.as_deref() != Some(current) {
+                    return Ok(Some(false));
+                }
+
+                pipe.cmd("SET")
+                    .arg(&family_key)
+                    .arg(next_refresh_token)
+                    .ignore()
+                    .cmd("EXPIREAT")
+                    .arg(&family_key)
+                    .arg(ttl)
+                    .ignore()
+                    .cmd("SET")
+                    .arg(next_refresh_token)
+                    .arg(next_access_token)
+                    .ignore()
+                    .cmd("EXPIREAT")
+                    .arg(next_refresh_token)
+                    .arg(ttl)
+                    .ignore()
+                    .cmd("DEL")
+                    .arg(current)
+                    .ignore()
+                    .query::<Option<()>>(conn)
+                    .map(|committed| committed.map(|_| true))
+            })
+            .map_err(|err| {
+                log::error!("{:?}", err);
+                CacheError::Transaction
+            })
+        })?;
+
+        if !rotated {
+            self.revoke_refresh_family(family)?;
+        }
+
+        Ok(rotated)
+    }
+
+    /// Removes the family together with its current refresh token, so neither
+    /// the latest nor any previously rotated token can be exchanged anymore.
+    pub fn revoke_refresh_family(&self, family: &Uuid) -> Result<(), CacheError<CacheError<()>>> {
+        if let Some(refresh_token) = self.get_refresh_family(family)? {
+            self.remove(&refresh_token)?;
+        }
+
+        self.remove(&Self::refresh_family_key(family))
+    }
+
+    fn refresh_family_key(family: &Uuid) -> String {
+        format!("refresh_family:{}", family)
+    }
+
+    pub fn deny_access_token(&self, jti: &Uuid, exp: usize) -> Result<bool, CacheError<CacheError<()>>> {
+        self.add_pair(&format!("denied_access_token:{}", jti), "1", exp)
+    }
+
+    /// Denies every access token of the user issued before `issued_before_ms`.
+    /// The record only has to outlive the access tokens themselves.
+    pub fn deny_user_access_tokens(
+        &self,
+        uid: &Uuid,
+        issued_before_ms: usize,
+        ttl: usize,
+    ) -> Result<bool, CacheError<CacheError<()>>> {
+        self.add_pair(
+            &format!("denied_user_tokens:{}", uid),
+            &issued_before_ms.to_string(),
+            ttl,
+        )
+    }
+
+    pub fn deny_session_access_tokens(
+        &self,
+        sid: &Uuid,
+        ttl: usize,
+    ) -> Result<bool, CacheError<CacheError<()>>> {
+        self.add_pair(&format!("denied_session_tokens:{}", sid), "1", ttl)
+    }
+
+    pub fn is_access_token_denied(
+        &self,
+        jti: &Uuid,
+        sid: &Uuid,
+        uid: &Uuid,
+        issued_at_ms: usize,
+    ) -> Result<bool, CacheError<CacheError<()>>> {
+        self.apply(|conn| {
+            let (token, session, issued_before): (Option<String>, Option<String>, Option<String>) =
+                redis::cmd("MGET")
+                    .arg(format!("denied_access_token:{}", jti))
+                    .arg(format!("denied_session_tokens:{}", sid))
+                    .arg(format!("denied_user_tokens:{}", uid))
+                    .query(conn)
+                    .map_err(|err| {
+                        log::error!("{:?}", err);
+                        CacheError::GetPair
+                    })?;
+            let issued_before = issued_before.and_then(|value| value.parse::<usize>().ok());
+
+            Ok(token.is_some()
+                || session.is_some()
+                || issued_before.is_some_and(|issued_before| issued_at_ms < issued_before))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    const TTL: usize = 4_102_444_800;
+
+    /// Family issued at login with its first pair of tokens
+    fn login(cache: &Cache) -> (Uuid, String) {
+        let family = Uuid::new_v4();
+        let refresh_token = format!("refresh-{}", Uuid::new_v4());
+
+        cache.add_pair(&refresh_token, "access-0", TTL).unwrap();
+        cache
+            .set_refresh_family(&family, &refresh_token, TTL)
+            .unwrap();
+
+        (family, refresh_token)
+    }
+
+    fn rotate(cache: &Cache, family: &Uuid, current: &str, next: &str) -> bool {
+        cache
+            .rotate_refresh_family(family, current, next, &format!("access-{}", next), TTL)
+            .unwrap()
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn rotated_token_is_exchanged() {
+        let cache = test_support::cache();
+        let (family, first) = login(&cache);
+        let second = format!("refresh-{}", Uuid::new_v4());
+        let third = format!("refresh-{}", Uuid::new_v4());
+
+        assert!(rotate(&cache, &family, &first, &second));
+        assert_eq!(cache.get_pair(&first).unwrap(), None);
+        assert_eq!(
+            cache.get_pair(&second).unwrap(),
+            Some(format!("access-{}", second))
+        );
+        assert_eq!(
+            cache.get_refresh_family(&family).unwrap(),
+            Some(second.clone())
+        );
+        assert!(rotate(&cache, &family, &second, &third));
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn reused_token_revokes_the_family() {
+        let cache = test_support::cache();
+        let (family, first) = login(&cache);
+        let second = format!("refresh-{}", Uuid::new_v4());
+
+        assert!(rotate(&cache, &family, &first, &second));
+        // A second request with the same token lost the race or replays it
+        assert!(!rotate(&cache, &family, &first, "refresh-forked"));
+
+        assert_eq!(cache.get_refresh_family(&family).unwrap(), None);
+        assert_eq!(cache.get_pair(&second).unwrap(), None);
+        assert_eq!(cache.get_pair("refresh-forked").unwrap(), None);
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn concurrent_refreshes_do_not_fork_the_family() {
+        let cache = test_support::cache();
+        let (family, first) = login(&cache);
+        let rotations = (0..8)
+            .map(|_| {
+                let (cache, first) = (cache.clone(), first.clone());
+
+                std::thread::spawn(move || {
+                    let next = format!("refresh-{}", Uuid::new_v4());
+
+                    rotate(&cache, &family, &first, &next)
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|rotation| rotation.join().unwrap())
+            .filter(|rotated| *rotated)
+            .count();
+
+        assert!(rotations <= 1);
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn revoked_family_refuses_the_latest_token() {
+        let cache = test_support::cache();
+        let (family, first) = login(&cache);
+        let next = format!("refresh-{}", Uuid::new_v4());
+
+        cache.revoke_refresh_family(&family).unwrap();
+
+        assert_eq!(cache.get_pair(&first).unwrap(), None);
+        assert!(!rotate(&cache, &family, &first, &next));
+        assert_eq!(cache.get_pair(&next).unwrap(), None);
+        assert_eq!(cache.get_refresh_family(&family).unwrap(), None);
+    }
 }
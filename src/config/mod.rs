@@ -1,17 +1,53 @@
-use std::env;
+use std::{env, str::FromStr};
 
-use crate::services::auth::{SaltProvider, SecretsProvider};
+use data_encoding::BASE64;
+use jsonwebtoken::Algorithm;
+
+use crate::{
+    db::models::custom_types::user_profiles_roles::UserProfilesRoles,
+    services::{
+        auth::{keys::JwtKeys, PasswordHashProvider, SecretsProvider},
+        ledger::LedgerKeysProvider,
+        mfa::{keys::TotpKeys, MfaPolicyProvider, TotpKeysProvider},
+        oidc::OidcSettings,
+        passports::{keys::PassportKeys, PassportKeysProvider},
+        verification::AccountLinksProvider,
+    },
+};
 
 use super::db::DbUrlProvider;
 
+// Pinned instead of taken from the argon2 crate, a default changing with a
+// crate update would flag every stored hash for rehashing. Keep in sync with
+// .env.example
+const DEFAULT_ARGON2_MEMORY_COST: u32 = 65536;
+const DEFAULT_ARGON2_TIME_COST: u32 = 3;
+const DEFAULT_ARGON2_PARALLELISM: u32 = 1;
+
 pub struct Config {
     db_url: String,
     host: String,
     port: u16,
     salt: String,
-    jwt_secret_access: String,
+    argon2_mem_cost: u32,
+    argon2_time_cost: u32,
+    argon2_lanes: u32,
+    jwt_access_keys: JwtKeys,
+    ledger_keys: JwtKeys,
     jwt_secret_refresh: String,
     redis_url: String,
+    mfa_issuer: String,
+    mfa_required_roles: Vec<UserProfilesRoles>,
+    totp_keys: TotpKeys,
+    smtp_url: Option<String>,
+    mail_from: String,
+    mail_dir: String,
+    app_url: String,
+    oidc: Option<OidcSettings>,
+    ledger_checkpoint_interval: u64,
+    payments_reconcile_interval: u64,
+    passport_keys: PassportKeys,
+    passport_reencrypt_batch: i64,
 }
 
 impl Config {
@@ -25,6 +61,170 @@ impl Config {
     pub fn redis_url(&self) -> &str {
         &self.redis_url
     }
+
+    pub fn smtp_url(&self) -> Option<&str> {
+        self.smtp_url.as_deref()
+    }
+
+    pub fn mail_from(&self) -> &str {
+        &self.mail_from
+    }
+
+    pub fn mail_dir(&self) -> &str {
+        &self.mail_dir
+    }
+
+    pub fn oidc(&self) -> Option<&OidcSettings> {
+        self.oidc.as_ref()
+    }
+
+    /// Seconds between signed checkpoints of the court case ledger
+    pub fn ledger_checkpoint_interval(&self) -> u64 {
+        self.ledger_checkpoint_interval
+    }
+
+    /// Seconds between lookups of payments left pending at the provider
+    pub fn payments_reconcile_interval(&self) -> u64 {
+        self.payments_reconcile_interval
+    }
+
+    pub fn passport_reencrypt_batch(&self) -> i64 {
+        self.passport_reencrypt_batch
+    }
+}
+
+impl Config {
+    /// Comma separated kid=key pairs of base64 encoded keys and the id of
+    /// the current one, the first key when `kid_name` is unset
+    fn key_ring(keys_name: &str, kid_name: &str) -> (String, Vec<(String, Vec<u8>)>) {
+        let keys = env::var(keys_name)
+            .ok()
+            .filter(|e| !e.is_empty())
+            .unwrap_or_else(|| panic!("{} must be set", keys_name))
+            .split(',')
+            .map(|e| {
+                let (kid, key) = e
+                    .split_once('=')
+                    .unwrap_or_else(|| panic!("{} must be a list of kid=key pairs", keys_name));
+                let key = BASE64
+                    .decode(key.trim().as_bytes())
+                    .unwrap_or_else(|_| panic!("{} must be base64", keys_name));
+
+                (kid.trim().to_owned(), key)
+            })
+            .collect::<Vec<(String, Vec<u8>)>>();
+
+        let current_kid = env::var(kid_name)
+            .ok()
+            .filter(|e| !e.is_empty())
+            .unwrap_or_else(|| keys[0].0.clone());
+
+        (current_kid, keys)
+    }
+
+    fn totp_keys() -> TotpKeys {
+        let (current_kid, master_keys) = Self::key_ring("MFA_KEYS", "MFA_KEY_ID");
+
+        TotpKeys::new(&current_kid, &master_keys)
+            .expect("MFA_KEYS must hold 32 byte keys including MFA_KEY_ID")
+    }
+
+    fn passport_keys() -> PassportKeys {
+        let (current_kid, master_keys) = Self::key_ring("PASSPORT_KEYS", "PASSPORT_KEY_ID");
+        let index_key = env::var("PASSPORT_INDEX_KEY")
+            .ok()
+            .filter(|e| !e.is_empty())
+            .map(|e| {
+                BASE64
+                    .decode(e.trim().as_bytes())
+                    .expect("PASSPORT_INDEX_KEY must be base64")
+            })
+            .expect("PASSPORT_INDEX_KEY must be set");
+
+        PassportKeys::new(&current_kid, &master_keys, &index_key)
+            .expect("PASSPORT_KEYS must hold 32 byte keys including PASSPORT_KEY_ID")
+    }
+
+    fn jwt_access_keys() -> JwtKeys {
+        let algorithm = env::var("JWT_ALGORITHM")
+            .map(|e| Algorithm::from_str(&e).expect("JWT_ALGORITHM is not supported"))
+            .unwrap_or(Algorithm::HS256);
+
+        if algorithm == Algorithm::HS256 {
+            let secret = env::var("JWT_SECRET_ACCESS").unwrap_or_else(|_| {
+                log::warn!("JWT_SECRET_ACCESS not specified. Default value is not secure");
+
+                "notsecuresecretaccess".to_string()
+            });
+
+            return JwtKeys::from_secret(secret.as_bytes());
+        }
+
+        JwtKeys::from_pem_files(
+            algorithm,
+            &env::var("JWT_KEY_ID").expect("JWT_KEY_ID must be set"),
+            &env::var("JWT_PRIVATE_KEY_PATH").expect("JWT_PRIVATE_KEY_PATH must be set"),
+            &Self::public_key_paths("JWT_PUBLIC_KEYS"),
+        )
+        .expect("JWT keys loading error")
+    }
+
+    /// Checkpoints of the court case ledger are signed apart from the tokens,
+    /// so rotating or leaking one key doesn't touch the other
+    fn ledger_keys() -> JwtKeys {
+        let algorithm = env::var("LEDGER_ALGORITHM")
+            .map(|e| Algorithm::from_str(&e).expect("LEDGER_ALGORITHM is not supported"))
+            .unwrap_or(Algorithm::EdDSA);
+
+        JwtKeys::from_pem_files(
+            algorithm,
+            &env::var("LEDGER_KEY_ID").expect("LEDGER_KEY_ID must be set"),
+            &env::var("LEDGER_PRIVATE_KEY_PATH").expect("LEDGER_PRIVATE_KEY_PATH must be set"),
+            &Self::public_key_paths("LEDGER_PUBLIC_KEYS"),
+        )
+        .expect("Ledger keys loading error")
+    }
+
+    /// `name` holds a comma separated list of kid=path pairs
+    fn public_key_paths(name: &str) -> Vec<(String, String)> {
+        env::var(name)
+            .unwrap_or_else(|_| panic!("{} must be set", name))
+            .split(',')
+            .map(|e| {
+                let (kid, path) = e
+                    .split_once('=')
+                    .unwrap_or_else(|| panic!("{} must be a list of kid=path pairs", name));
+
+                (kid.trim().to_owned(), path.trim().to_owned())
+            })
+            .collect()
+    }
+}
+
+impl Config {
+    fn oidc_settings() -> Option<OidcSettings> {
+        let issuer_url = env::var("OIDC_ISSUER_URL").ok().filter(|e| !e.is_empty())?;
+        let default_role = env::var("OIDC_DEFAULT_ROLE")
+            .map(|e| {
+                e.parse()
+                    .expect("OIDC_DEFAULT_ROLE contains an unknown role")
+            })
+            .unwrap_or(UserProfilesRoles::Employee);
+
+        if !OidcSettings::is_allowed_role(default_role) {
+            panic!("OIDC_DEFAULT_ROLE must be employee or law");
+        }
+
+        Some(OidcSettings {
+            issuer_url,
+            client_id: env::var("OIDC_CLIENT_ID").expect("OIDC_CLIENT_ID must be set"),
+            client_secret: env::var("OIDC_CLIENT_SECRET").ok().filter(|e| !e.is_empty()),
+            redirect_url: env::var("OIDC_REDIRECT_URL").expect("OIDC_REDIRECT_URL must be set"),
+            scopes: env::var("OIDC_SCOPES").unwrap_or("openid email profile".into()),
+            role_claim: env::var("OIDC_ROLE_CLAIM").unwrap_or("roles".into()),
+            default_role,
+        })
+    }
 }
 
 impl DbUrlProvider for Config {
@@ -33,15 +233,24 @@ impl DbUrlProvider for Config {
     }
 }
 
-impl SaltProvider for Config {
-    fn salt(&self) -> &[u8] {
+impl PasswordHashProvider for Config {
+    fn argon2_config(&self) -> argon2::Config<'_> {
+        argon2::Config {
+            mem_cost: self.argon2_mem_cost,
+            time_cost: self.argon2_time_cost,
+            lanes: self.argon2_lanes,
+            ..argon2::Config::rfc9106_low_mem()
+        }
+    }
+
+    fn legacy_salt(&self) -> &[u8] {
         self.salt.as_bytes()
     }
 }
 
 impl SecretsProvider for Config {
-    fn access_secret(&self) -> &[u8] {
-        self.jwt_secret_access.as_bytes()
+    fn access_keys(&self) -> &JwtKeys {
+        &self.jwt_access_keys
     }
 
     fn refresh_secret(&self) -> &[u8] {
@@ -49,6 +258,40 @@ impl SecretsProvider for Config {
     }
 }
 
+impl LedgerKeysProvider for Config {
+    fn ledger_keys(&self) -> &JwtKeys {
+        &self.ledger_keys
+    }
+}
+
+impl MfaPolicyProvider for Config {
+    fn mfa_issuer(&self) -> &str {
+        &self.mfa_issuer
+    }
+
+    fn mfa_required_for(&self, role: UserProfilesRoles) -> bool {
+        self.mfa_required_roles.contains(&role)
+    }
+}
+
+impl TotpKeysProvider for Config {
+    fn totp_keys(&self) -> &TotpKeys {
+        &self.totp_keys
+    }
+}
+
+impl PassportKeysProvider for Config {
+    fn passport_keys(&self) -> &PassportKeys {
+        &self.passport_keys
+    }
+}
+
+impl AccountLinksProvider for Config {
+    fn app_url(&self) -> &str {
+        &self.app_url
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -57,22 +300,55 @@ impl Default for Config {
             port: env::var("PORT")
                 .map(|e| e.parse().unwrap_or(7878))
                 .unwrap_or(7878),
-            salt: env::var("SALT").unwrap_or_else(|_| {
-                log::warn!("SALT not specified. Default value is not secure");
-
-                "notsecuresalt".to_string()
-            }),
-            jwt_secret_access: env::var("JWT_SECRET_ACCESS").unwrap_or_else(|_| {
-                log::warn!("JWT_SECRET_ACCESS not specified. Default value is not secure");
-
-                "notsecuresecretaccess".to_string()
-            }),
+            salt: env::var("SALT").unwrap_or("notsecuresalt".into()),
+            argon2_mem_cost: env::var("ARGON2_MEMORY_COST")
+                .map(|e| e.parse().expect("ARGON2_MEMORY_COST must be a number"))
+                .unwrap_or(DEFAULT_ARGON2_MEMORY_COST),
+            argon2_time_cost: env::var("ARGON2_TIME_COST")
+                .map(|e| e.parse().expect("ARGON2_TIME_COST must be a number"))
+                .unwrap_or(DEFAULT_ARGON2_TIME_COST),
+            argon2_lanes: env::var("ARGON2_PARALLELISM")
+                .map(|e| e.parse().expect("ARGON2_PARALLELISM must be a number"))
+                .unwrap_or(DEFAULT_ARGON2_PARALLELISM),
+            jwt_access_keys: Self::jwt_access_keys(),
+            ledger_keys: Self::ledger_keys(),
             jwt_secret_refresh: env::var("JWT_SECRET_REFRESH").unwrap_or_else(|_| {
                 log::warn!("JWT_SECRET_REFRESH not specified. Default value is not secure");
 
                 "notsecuresecretrefresh".to_string()
             }),
             redis_url: env::var("REDIS_URL").expect("REDIS_URL must be set"),
+            mfa_issuer: env::var("MFA_ISSUER").unwrap_or("security-db-server".into()),
+            mfa_required_roles: env::var("MFA_REQUIRED_ROLES")
+                .map(|e| {
+                    e.split(',')
+                        .map(|role| role.trim())
+                        .filter(|role| !role.is_empty())
+                        .map(|role| {
+                            role.parse()
+                                .expect("MFA_REQUIRED_ROLES contains an unknown role")
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            totp_keys: Self::totp_keys(),
+            smtp_url: env::var("SMTP_URL").ok().filter(|e| !e.is_empty()),
+            mail_from: env::var("MAIL_FROM").unwrap_or("no-reply@localhost".into()),
+            mail_dir: env::var("MAIL_DIR").unwrap_or("mail".into()),
+            app_url: env::var("APP_URL")
+                .map(|e| e.trim_end_matches('/').to_owned())
+                .unwrap_or("http://localhost:3000".into()),
+            oidc: Self::oidc_settings(),
+            ledger_checkpoint_interval: env::var("LEDGER_CHECKPOINT_INTERVAL")
+                .map(|e| e.parse().expect("LEDGER_CHECKPOINT_INTERVAL must be a number"))
+                .unwrap_or(3600),
+            payments_reconcile_interval: env::var("PAYMENTS_RECONCILE_INTERVAL")
+                .map(|e| e.parse().expect("PAYMENTS_RECONCILE_INTERVAL must be a number"))
+                .unwrap_or(300),
+            passport_keys: Self::passport_keys(),
+            passport_reencrypt_batch: env::var("PASSPORT_REENCRYPT_BATCH")
+                .map(|e| e.parse().expect("PASSPORT_REENCRYPT_BATCH must be a number"))
+                .unwrap_or(500),
         }
     }
 }
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS mfa_recovery_codes;
+
+ALTER TABLE auth_data DROP COLUMN "totp_enabled";
+ALTER TABLE auth_data DROP COLUMN "totp_secret";
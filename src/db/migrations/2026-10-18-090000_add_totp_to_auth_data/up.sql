@@ -0,0 +1,10 @@
+-- Your SQL goes here
+ALTER TABLE auth_data ADD COLUMN "totp_secret" VARCHAR(64);
+ALTER TABLE auth_data ADD COLUMN "totp_enabled" BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS mfa_recovery_codes (
+  "uid" UUID DEFAULT uuid_generate_v4() NOT NULL PRIMARY KEY,
+  "auth_uid" UUID NOT NULL REFERENCES auth_data("uid") ON DELETE CASCADE,
+  "code_hash" CHAR(64) NOT NULL,
+  "used_at" TIMESTAMP
+);
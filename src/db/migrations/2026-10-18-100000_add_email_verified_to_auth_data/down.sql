@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE auth_data DROP COLUMN "email_verified";
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE auth_data ADD COLUMN "email_verified" BOOLEAN NOT NULL DEFAULT FALSE;
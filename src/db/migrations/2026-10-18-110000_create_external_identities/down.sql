@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS external_identities;
+
+DELETE FROM auth_data WHERE "password" IS NULL;
+ALTER TABLE auth_data ALTER COLUMN "password" SET NOT NULL;
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+ALTER TABLE auth_data ALTER COLUMN "password" DROP NOT NULL;
+
+CREATE TABLE IF NOT EXISTS external_identities (
+  "uid" UUID DEFAULT uuid_generate_v4() NOT NULL PRIMARY KEY,
+  "auth_uid" UUID NOT NULL REFERENCES auth_data("uid") ON DELETE CASCADE,
+  "issuer" VARCHAR(255) NOT NULL,
+  "subject" VARCHAR(255) NOT NULL,
+  "created_at" TIMESTAMP NOT NULL DEFAULT NOW(),
+  UNIQUE ("issuer", "subject")
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS api_keys;
+DROP TABLE IF EXISTS service_accounts;
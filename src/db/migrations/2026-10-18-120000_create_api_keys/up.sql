@@ -0,0 +1,20 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS service_accounts (
+  "uid" UUID DEFAULT uuid_generate_v4() NOT NULL PRIMARY KEY,
+  "name" VARCHAR(255) NOT NULL UNIQUE,
+  "created_at" TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS api_keys (
+  "uid" UUID DEFAULT uuid_generate_v4() NOT NULL PRIMARY KEY,
+  "service_account_uid" UUID NOT NULL REFERENCES service_accounts("uid") ON DELETE CASCADE,
+  "name" VARCHAR(255) NOT NULL,
+  "prefix" VARCHAR(16) NOT NULL,
+  "key_hash" CHAR(64) NOT NULL UNIQUE,
+  "scopes" TEXT[] NOT NULL DEFAULT '{}',
+  "created_by" UUID REFERENCES auth_data("uid") ON DELETE SET NULL,
+  "expires_at" TIMESTAMP,
+  "last_used_at" TIMESTAMP,
+  "revoked_at" TIMESTAMP,
+  "created_at" TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,27 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS audit_events (
+  "uid" UUID DEFAULT uuid_generate_v4() NOT NULL PRIMARY KEY,
+  "actor_kind" VARCHAR(16) NOT NULL,
+  "actor_uid" UUID,
+  "action" VARCHAR(64) NOT NULL,
+  "target_kind" VARCHAR(32),
+  "target_uid" UUID,
+  "ip" VARCHAR(64),
+  "outcome" VARCHAR(16) NOT NULL,
+  "details" VARCHAR(255),
+  "created_at" TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX audit_events_created_at_idx ON audit_events ("created_at");
+CREATE INDEX audit_events_actor_uid_idx ON audit_events ("actor_uid");
+CREATE INDEX audit_events_target_uid_idx ON audit_events ("target_uid");
+
+CREATE OR REPLACE FUNCTION audit_events_append_only() RETURNS TRIGGER AS $$
+BEGIN
+  RAISE EXCEPTION 'audit_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_events_append_only
+  BEFORE UPDATE OR DELETE ON audit_events
+  FOR EACH ROW EXECUTE FUNCTION audit_events_append_only();
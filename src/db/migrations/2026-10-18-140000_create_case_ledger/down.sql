@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER IF EXISTS court_sides_ledger ON court_sides;
+DROP TRIGGER IF EXISTS court_cases_ledger ON court_cases;
+DROP FUNCTION IF EXISTS court_sides_ledger();
+DROP FUNCTION IF EXISTS court_cases_ledger();
+DROP FUNCTION IF EXISTS case_ledger_append(TEXT, UUID, UUID, TEXT, TEXT);
+DROP TABLE IF EXISTS case_ledger_checkpoints;
+DROP TABLE IF EXISTS case_ledger;
+DROP FUNCTION IF EXISTS case_ledger_append_only();
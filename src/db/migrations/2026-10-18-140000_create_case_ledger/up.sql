@@ -0,0 +1,141 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS case_ledger (
+  "seq" BIGINT NOT NULL PRIMARY KEY,
+  "entity" VARCHAR(16) NOT NULL,
+  "entity_uid" UUID NOT NULL,
+  "court_case_uid" UUID NOT NULL,
+  "operation" VARCHAR(8) NOT NULL,
+  "payload" TEXT NOT NULL,
+  "prev_hash" CHAR(64) NOT NULL,
+  "entry_hash" CHAR(64) NOT NULL UNIQUE,
+  "created_at" TIMESTAMP NOT NULL
+);
+
+CREATE INDEX case_ledger_court_case_uid_idx ON case_ledger ("court_case_uid");
+
+CREATE TABLE IF NOT EXISTS case_ledger_checkpoints (
+  "seq" BIGINT NOT NULL PRIMARY KEY,
+  "entry_hash" CHAR(64) NOT NULL,
+  "key_id" VARCHAR(64),
+  "signature" TEXT NOT NULL,
+  "created_at" TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE OR REPLACE FUNCTION case_ledger_append_only() RETURNS TRIGGER AS $$
+BEGIN
+  RAISE EXCEPTION '% is append-only', TG_TABLE_NAME;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER case_ledger_append_only
+  BEFORE UPDATE OR DELETE ON case_ledger
+  FOR EACH ROW EXECUTE FUNCTION case_ledger_append_only();
+
+CREATE TRIGGER case_ledger_checkpoints_append_only
+  BEFORE UPDATE OR DELETE ON case_ledger_checkpoints
+  FOR EACH ROW EXECUTE FUNCTION case_ledger_append_only();
+
+-- The hash input must stay in sync with `LedgerService::entry_hash`
+CREATE OR REPLACE FUNCTION case_ledger_append(
+  p_entity TEXT,
+  p_entity_uid UUID,
+  p_court_case_uid UUID,
+  p_operation TEXT,
+  p_payload TEXT
+) RETURNS VOID AS $$
+DECLARE
+  last_seq BIGINT;
+  last_hash CHAR(64);
+  entry_created_at TIMESTAMP := clock_timestamp()::TIMESTAMP;
+BEGIN
+  -- Entries are chained one by one, concurrent writers wait for each other
+  PERFORM pg_advisory_xact_lock(hashtext('case_ledger'));
+
+  SELECT "seq", "entry_hash" INTO last_seq, last_hash
+    FROM case_ledger ORDER BY "seq" DESC LIMIT 1;
+
+  IF last_seq IS NULL THEN
+    last_seq := 0;
+    last_hash := repeat('0', 64);
+  END IF;
+
+  INSERT INTO case_ledger (
+    "seq", "entity", "entity_uid", "court_case_uid", "operation", "payload",
+    "prev_hash", "entry_hash", "created_at"
+  ) VALUES (
+    last_seq + 1, p_entity, p_entity_uid, p_court_case_uid, p_operation, p_payload,
+    last_hash,
+    encode(sha256(convert_to(concat_ws('|',
+      (last_seq + 1)::TEXT, last_hash, p_entity, p_entity_uid::TEXT,
+      p_court_case_uid::TEXT, p_operation, p_payload,
+      to_char(entry_created_at, 'YYYY-MM-DD"T"HH24:MI:SS.US')
+    ), 'UTF8')), 'hex'),
+    entry_created_at
+  );
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION court_cases_ledger() RETURNS TRIGGER AS $$
+DECLARE
+  r court_cases;
+BEGIN
+  IF TG_OP = 'UPDATE' AND (NEW.number, NEW.judge_fullname, NEW.decision, NEW.kind)
+    IS NOT DISTINCT FROM (OLD.number, OLD.judge_fullname, OLD.decision, OLD.kind) THEN
+    RETURN NULL;
+  END IF;
+
+  IF TG_OP = 'DELETE' THEN
+    r := OLD;
+  ELSE
+    r := NEW;
+  END IF;
+
+  PERFORM case_ledger_append(
+    'court_case', r.uid, r.uid, lower(TG_OP),
+    json_build_object(
+      'number', r.number,
+      'judge_fullname', r.judge_fullname,
+      'decision', r.decision,
+      'kind', r.kind
+    )::TEXT
+  );
+
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER court_cases_ledger
+  AFTER INSERT OR UPDATE OR DELETE ON court_cases
+  FOR EACH ROW EXECUTE FUNCTION court_cases_ledger();
+
+CREATE OR REPLACE FUNCTION court_sides_ledger() RETURNS TRIGGER AS $$
+DECLARE
+  r court_sides;
+BEGIN
+  IF TG_OP = 'UPDATE' AND (NEW.court_case_uid, NEW.user_uid, NEW.kind, NEW.case_status)
+    IS NOT DISTINCT FROM (OLD.court_case_uid, OLD.user_uid, OLD.kind, OLD.case_status) THEN
+    RETURN NULL;
+  END IF;
+
+  IF TG_OP = 'DELETE' THEN
+    r := OLD;
+  ELSE
+    r := NEW;
+  END IF;
+
+  PERFORM case_ledger_append(
+    'court_side', r.uid, r.court_case_uid, lower(TG_OP),
+    json_build_object(
+      'user_uid', r.user_uid,
+      'kind', r.kind,
+      'case_status', r.case_status
+    )::TEXT
+  );
+
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER court_sides_ledger
+  AFTER INSERT OR UPDATE OR DELETE ON court_sides
+  FOR EACH ROW EXECUTE FUNCTION court_sides_ledger();
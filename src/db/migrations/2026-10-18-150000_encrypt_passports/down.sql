@@ -0,0 +1,13 @@
+-- This file should undo anything in `up.sql`
+-- Encrypted rows can't be converted back here, decrypt them before reverting
+DROP INDEX IF EXISTS passports_key_id_idx;
+DROP INDEX IF EXISTS passports_number_index_idx;
+
+ALTER TABLE passports DROP COLUMN IF EXISTS "key_id";
+ALTER TABLE passports DROP COLUMN IF EXISTS "data_key";
+ALTER TABLE passports DROP COLUMN IF EXISTS "number_index";
+
+ALTER TABLE passports ALTER COLUMN "birthday_date" TYPE DATE USING "birthday_date"::DATE;
+ALTER TABLE passports ALTER COLUMN "registration_place" TYPE VARCHAR;
+ALTER TABLE passports ALTER COLUMN "series" TYPE CHAR(4);
+ALTER TABLE passports ALTER COLUMN "number" TYPE CHAR(6);
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+-- Rows keep plaintext until the server encrypts them on startup, they are
+-- the ones without a data key
+ALTER TABLE passports ALTER COLUMN "number" TYPE TEXT;
+ALTER TABLE passports ALTER COLUMN "series" TYPE TEXT;
+ALTER TABLE passports ALTER COLUMN "registration_place" TYPE TEXT;
+ALTER TABLE passports ALTER COLUMN "birthday_date" TYPE TEXT USING to_char("birthday_date", 'YYYY-MM-DD');
+
+ALTER TABLE passports ADD COLUMN "number_index" CHAR(64);
+ALTER TABLE passports ADD COLUMN "data_key" TEXT;
+ALTER TABLE passports ADD COLUMN "key_id" VARCHAR(32);
+
+CREATE INDEX passports_number_index_idx ON passports ("number_index");
+CREATE INDEX passports_key_id_idx ON passports ("key_id");
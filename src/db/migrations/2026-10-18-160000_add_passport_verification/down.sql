@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER IF EXISTS passport_history_append_only ON passport_history;
+DROP FUNCTION IF EXISTS passport_history_append_only();
+DROP TABLE IF EXISTS passport_history;
+
+ALTER TABLE passports DROP COLUMN IF EXISTS "verified_by";
+ALTER TABLE passports DROP COLUMN IF EXISTS "verified_at";
@@ -0,0 +1,25 @@
+-- Your SQL goes here
+ALTER TABLE passports ADD COLUMN "verified_at" TIMESTAMP;
+ALTER TABLE passports ADD COLUMN "verified_by" UUID REFERENCES auth_data ("uid") ON DELETE SET NULL;
+
+-- Names of changed fields only, values stay encrypted in `passports`
+CREATE TABLE IF NOT EXISTS passport_history (
+  "uid" UUID DEFAULT uuid_generate_v4() NOT NULL PRIMARY KEY,
+  "passport_uid" UUID NOT NULL REFERENCES passports ("uid"),
+  "changed_by" UUID,
+  "action" VARCHAR(16) NOT NULL,
+  "fields" VARCHAR(255) NOT NULL,
+  "created_at" TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX passport_history_passport_uid_idx ON passport_history ("passport_uid");
+
+CREATE OR REPLACE FUNCTION passport_history_append_only() RETURNS TRIGGER AS $$
+BEGIN
+  RAISE EXCEPTION 'passport_history is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER passport_history_append_only
+  BEFORE UPDATE OR DELETE ON passport_history
+  FOR EACH ROW EXECUTE FUNCTION passport_history_append_only();
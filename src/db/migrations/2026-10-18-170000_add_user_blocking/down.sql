@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS user_profiles_deleted_at_idx;
+
+ALTER TABLE user_profiles DROP COLUMN IF EXISTS "deleted_at";
+ALTER TABLE user_profiles DROP COLUMN IF EXISTS "blocked_at";
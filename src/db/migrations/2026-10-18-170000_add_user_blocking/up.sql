@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE user_profiles ADD COLUMN "blocked_at" TIMESTAMP;
+ALTER TABLE user_profiles ADD COLUMN "deleted_at" TIMESTAMP;
+
+CREATE INDEX user_profiles_deleted_at_idx ON user_profiles ("deleted_at");
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS law_applications;
+DROP TYPE IF EXISTS law_applications_statuses;
@@ -0,0 +1,25 @@
+-- Your SQL goes here
+CREATE TYPE law_applications_statuses AS ENUM (
+  'pending',
+  'approved',
+  'rejected'
+);
+
+CREATE TABLE IF NOT EXISTS law_applications (
+  "uid" UUID DEFAULT uuid_generate_v4() NOT NULL PRIMARY KEY,
+  "profile_uid" UUID NOT NULL REFERENCES user_profiles ("uid") ON DELETE CASCADE,
+  "itn" VARCHAR(15) NOT NULL,
+  "start_activity_date" TIMESTAMP NOT NULL,
+  "status" law_applications_statuses NOT NULL DEFAULT 'pending',
+  "reason" VARCHAR(255),
+  "reviewed_by" UUID REFERENCES auth_data ("uid") ON DELETE SET NULL,
+  "reviewed_at" TIMESTAMP,
+  "created_at" TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX law_applications_status_idx ON law_applications ("status");
+
+-- A user waits for one decision at a time
+CREATE UNIQUE INDEX law_applications_pending_idx
+  ON law_applications ("profile_uid")
+  WHERE "status" = 'pending';
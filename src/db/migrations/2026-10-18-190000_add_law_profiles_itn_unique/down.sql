@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE law_profiles DROP CONSTRAINT IF EXISTS law_profiles_itn_key;
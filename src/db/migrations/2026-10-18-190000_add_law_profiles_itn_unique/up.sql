@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE law_profiles ADD CONSTRAINT law_profiles_itn_key UNIQUE ("itn");
@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS services_law_uid_idx;
+
+ALTER TABLE services DROP COLUMN IF EXISTS "created_at";
+ALTER TABLE services DROP COLUMN IF EXISTS "archived_at";
+ALTER TABLE services DROP COLUMN IF EXISTS "currency";
+
+ALTER TABLE services ADD COLUMN "cost" DOUBLE PRECISION;
+UPDATE services SET "cost" = "cost_minor" / 100.0;
+ALTER TABLE services ALTER COLUMN "cost" SET NOT NULL;
+ALTER TABLE services DROP COLUMN IF EXISTS "cost_minor";
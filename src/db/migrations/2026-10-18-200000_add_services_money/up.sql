@@ -0,0 +1,13 @@
+-- Your SQL goes here
+-- Costs are kept in minor units (kopecks, cents) so sums never drift
+ALTER TABLE services ADD COLUMN "cost_minor" BIGINT;
+UPDATE services SET "cost_minor" = ROUND("cost" * 100)::BIGINT;
+ALTER TABLE services ALTER COLUMN "cost_minor" SET NOT NULL;
+ALTER TABLE services ADD CONSTRAINT services_cost_minor_check CHECK ("cost_minor" >= 0);
+ALTER TABLE services DROP COLUMN "cost";
+
+ALTER TABLE services ADD COLUMN "currency" VARCHAR(3) NOT NULL DEFAULT 'RUB';
+ALTER TABLE services ADD COLUMN "archived_at" TIMESTAMP;
+ALTER TABLE services ADD COLUMN "created_at" TIMESTAMP NOT NULL DEFAULT NOW();
+
+CREATE INDEX services_law_uid_idx ON services ("law_uid") WHERE "archived_at" IS NULL;
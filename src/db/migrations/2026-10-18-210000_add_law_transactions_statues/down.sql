@@ -0,0 +1,19 @@
+-- This file should undo anything in `up.sql`
+ALTER TYPE law_transactions_statues RENAME TO law_transactions_statues_old;
+
+CREATE TYPE law_transactions_statues AS ENUM (
+  'started',
+  'processing',
+  'completed'
+);
+
+ALTER TABLE law_transactions
+  ALTER COLUMN "status" TYPE law_transactions_statues
+  USING (
+    CASE
+      WHEN "status"::TEXT IN ('declined', 'cancelled') THEN 'completed'
+      ELSE "status"::TEXT
+    END
+  )::law_transactions_statues;
+
+DROP TYPE law_transactions_statues_old;
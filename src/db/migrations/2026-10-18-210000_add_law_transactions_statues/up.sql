@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TYPE law_transactions_statues ADD VALUE IF NOT EXISTS 'declined';
+ALTER TYPE law_transactions_statues ADD VALUE IF NOT EXISTS 'cancelled';
@@ -0,0 +1,14 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS law_transactions_law_uid_idx;
+DROP INDEX IF EXISTS law_transactions_client_uid_idx;
+
+ALTER TABLE law_transactions ALTER COLUMN "status" DROP DEFAULT;
+ALTER TABLE law_transactions DROP COLUMN IF EXISTS "cancelled_at";
+ALTER TABLE law_transactions DROP COLUMN IF EXISTS "completed_at";
+ALTER TABLE law_transactions DROP COLUMN IF EXISTS "declined_at";
+ALTER TABLE law_transactions DROP COLUMN IF EXISTS "accepted_at";
+ALTER TABLE law_transactions DROP COLUMN IF EXISTS "reason";
+ALTER TABLE law_transactions DROP COLUMN IF EXISTS "currency";
+ALTER TABLE law_transactions DROP COLUMN IF EXISTS "cost_minor";
+ALTER TABLE law_transactions DROP COLUMN IF EXISTS "service_uid";
+ALTER TABLE law_transactions DROP COLUMN IF EXISTS "law_uid";
@@ -0,0 +1,19 @@
+-- Your SQL goes here
+ALTER TABLE law_transactions ADD COLUMN "law_uid" UUID REFERENCES user_profiles ("uid") ON DELETE SET NULL;
+ALTER TABLE law_transactions ADD COLUMN "service_uid" UUID REFERENCES services ("uid") ON DELETE SET NULL;
+
+-- Price agreed on when the client engaged, later catalog edits do not change it
+ALTER TABLE law_transactions ADD COLUMN "cost_minor" BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE law_transactions ADD COLUMN "currency" VARCHAR(3) NOT NULL DEFAULT 'RUB';
+ALTER TABLE law_transactions ALTER COLUMN "cost_minor" DROP DEFAULT;
+ALTER TABLE law_transactions ALTER COLUMN "currency" DROP DEFAULT;
+
+ALTER TABLE law_transactions ADD COLUMN "reason" VARCHAR(255);
+ALTER TABLE law_transactions ADD COLUMN "accepted_at" TIMESTAMP;
+ALTER TABLE law_transactions ADD COLUMN "declined_at" TIMESTAMP;
+ALTER TABLE law_transactions ADD COLUMN "completed_at" TIMESTAMP;
+ALTER TABLE law_transactions ADD COLUMN "cancelled_at" TIMESTAMP;
+ALTER TABLE law_transactions ALTER COLUMN "status" SET DEFAULT 'started';
+
+CREATE INDEX law_transactions_client_uid_idx ON law_transactions ("client_uid");
+CREATE INDEX law_transactions_law_uid_idx ON law_transactions ("law_uid");
@@ -0,0 +1,61 @@
+-- Your SQL goes here
+CREATE TYPE invoices_statuses AS ENUM (
+  'issued',
+  'partially_paid',
+  'paid',
+  'void'
+);
+
+-- Amounts are in minor units of "currency", like services and law_transactions
+CREATE TABLE IF NOT EXISTS invoices (
+  "uid" UUID DEFAULT uuid_generate_v4() NOT NULL PRIMARY KEY,
+  "number" BIGSERIAL NOT NULL UNIQUE,
+  "transaction_uid" UUID NOT NULL REFERENCES law_transactions ("uid"),
+  "client_uid" UUID REFERENCES user_profiles ("uid") ON DELETE SET NULL,
+  "law_uid" UUID REFERENCES user_profiles ("uid") ON DELETE SET NULL,
+  "currency" VARCHAR(3) NOT NULL,
+  "subtotal_minor" BIGINT NOT NULL,
+  "discount_minor" BIGINT NOT NULL,
+  "tax_minor" BIGINT NOT NULL,
+  "total_minor" BIGINT NOT NULL,
+  "paid_minor" BIGINT NOT NULL DEFAULT 0,
+  "status" invoices_statuses NOT NULL DEFAULT 'issued',
+  "issued_by" UUID REFERENCES auth_data ("uid") ON DELETE SET NULL,
+  "voided_at" TIMESTAMP,
+  "created_at" TIMESTAMP NOT NULL DEFAULT NOW(),
+  CONSTRAINT invoices_paid_minor_check CHECK ("paid_minor" >= 0 AND "paid_minor" <= "total_minor")
+);
+
+CREATE INDEX invoices_transaction_uid_idx ON invoices ("transaction_uid");
+CREATE INDEX invoices_client_uid_idx ON invoices ("client_uid");
+
+CREATE TABLE IF NOT EXISTS invoice_items (
+  "uid" UUID DEFAULT uuid_generate_v4() NOT NULL PRIMARY KEY,
+  "invoice_uid" UUID NOT NULL REFERENCES invoices ("uid") ON DELETE CASCADE,
+  "position" INTEGER NOT NULL,
+  "service_uid" UUID REFERENCES services ("uid") ON DELETE SET NULL,
+  "name" VARCHAR(255) NOT NULL,
+  "quantity" INTEGER NOT NULL CHECK ("quantity" > 0),
+  "unit_price_minor" BIGINT NOT NULL,
+  "discount_minor" BIGINT NOT NULL,
+  -- Basis points, 2000 is 20%
+  "tax_rate_bp" INTEGER NOT NULL,
+  "tax_minor" BIGINT NOT NULL,
+  "total_minor" BIGINT NOT NULL,
+  UNIQUE ("invoice_uid", "position")
+);
+
+CREATE TABLE IF NOT EXISTS payments (
+  "uid" UUID DEFAULT uuid_generate_v4() NOT NULL PRIMARY KEY,
+  "invoice_uid" UUID NOT NULL REFERENCES invoices ("uid"),
+  "amount_minor" BIGINT NOT NULL CHECK ("amount_minor" > 0),
+  -- "manual" for payments recorded by employees, the provider name otherwise
+  "provider" VARCHAR(32) NOT NULL,
+  "reference" VARCHAR(255),
+  "note" VARCHAR(255),
+  "recorded_by" UUID REFERENCES auth_data ("uid") ON DELETE SET NULL,
+  "paid_at" TIMESTAMP NOT NULL,
+  "created_at" TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX payments_invoice_uid_idx ON payments ("invoice_uid");
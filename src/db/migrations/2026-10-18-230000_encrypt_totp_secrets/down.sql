@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+-- Sealed secrets can't be converted back here, disable TOTP for them before
+-- reverting
+ALTER TABLE auth_data DROP COLUMN IF EXISTS "totp_key_id";
+ALTER TABLE auth_data ALTER COLUMN "totp_secret" TYPE VARCHAR(64);
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- Secrets without a key id are plaintext, they are sealed on the next
+-- successful verification
+ALTER TABLE auth_data ALTER COLUMN "totp_secret" TYPE TEXT;
+ALTER TABLE auth_data ADD COLUMN "totp_key_id" VARCHAR(32);
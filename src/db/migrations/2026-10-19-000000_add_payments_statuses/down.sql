@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DELETE FROM payments WHERE "status" <> 'succeeded';
+DROP INDEX IF EXISTS payments_pending_idx;
+ALTER TABLE payments DROP COLUMN IF EXISTS "status";
+DROP TYPE IF EXISTS payments_statuses;
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+-- Provider charges are written as pending before the provider is called and
+-- settled afterwards, the uid of the row is the idempotency key of the charge
+CREATE TYPE payments_statuses AS ENUM (
+  'pending',
+  'succeeded',
+  'failed'
+);
+
+ALTER TABLE payments ADD COLUMN "status" payments_statuses NOT NULL DEFAULT 'succeeded';
+
+CREATE INDEX payments_pending_idx ON payments ("invoice_uid") WHERE "status" = 'pending';
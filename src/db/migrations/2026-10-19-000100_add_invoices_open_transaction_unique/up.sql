@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- An engagement is billed once, a void invoice may be replaced
+CREATE UNIQUE INDEX invoices_open_transaction_uid_key ON invoices ("transaction_uid")
+  WHERE "status" <> 'void';
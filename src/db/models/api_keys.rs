@@ -0,0 +1,26 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::service_accounts::ServiceAccount;
+
+#[derive(Queryable, Associations, Selectable, Identifiable, Debug, Serialize)]
+#[diesel(belongs_to(ServiceAccount, foreign_key = service_account_uid))]
+#[diesel(table_name = crate::db::orm::schema::api_keys)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(uid))]
+pub struct ApiKey {
+    pub uid: Uuid,
+    pub service_account_uid: Uuid,
+    pub name: String,
+    pub prefix: String,
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+    pub scopes: Vec<String>,
+    pub created_by: Option<Uuid>,
+    pub expires_at: Option<NaiveDateTime>,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub revoked_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
@@ -0,0 +1,21 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize)]
+#[diesel(table_name = crate::db::orm::schema::audit_events)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(uid))]
+pub struct AuditEvent {
+    pub uid: Uuid,
+    pub actor_kind: String,
+    pub actor_uid: Option<Uuid>,
+    pub action: String,
+    pub target_kind: Option<String>,
+    pub target_uid: Option<Uuid>,
+    pub ip: Option<String>,
+    pub outcome: String,
+    pub details: Option<String>,
+    pub created_at: NaiveDateTime,
+}
@@ -13,5 +13,11 @@ pub struct AuthData {
     pub profile_uid: Uuid,
     pub email: String,
     pub username: String,
-    pub password: String,
+    /// `None` for accounts managed by an external identity provider
+    pub password: Option<String>,
+    /// Sealed with the key `totp_key_id`, plaintext without it
+    pub totp_secret: Option<String>,
+    pub totp_enabled: bool,
+    pub email_verified: bool,
+    pub totp_key_id: Option<String>,
 }
@@ -0,0 +1,22 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Entry written by the `court_cases_ledger` and `court_sides_ledger`
+/// triggers, `payload` is the JSON state of the entity after the change
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize)]
+#[diesel(table_name = crate::db::orm::schema::case_ledger)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(seq))]
+pub struct CaseLedgerEntry {
+    pub seq: i64,
+    pub entity: String,
+    pub entity_uid: Uuid,
+    pub court_case_uid: Uuid,
+    pub operation: String,
+    pub payload: String,
+    pub prev_hash: String,
+    pub entry_hash: String,
+    pub created_at: NaiveDateTime,
+}
@@ -0,0 +1,15 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize)]
+#[diesel(table_name = crate::db::orm::schema::case_ledger_checkpoints)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(seq))]
+pub struct CaseLedgerCheckpoint {
+    pub seq: i64,
+    pub entry_hash: String,
+    pub key_id: Option<String>,
+    pub signature: String,
+    pub created_at: NaiveDateTime,
+}
@@ -0,0 +1,186 @@
+use std::{fmt, str::FromStr};
+
+use diesel::{
+    deserialize::{self, FromSql, FromSqlRow},
+    expression::AsExpression,
+    pg::{Pg, PgValue},
+    serialize::{self, Output, ToSql},
+    sql_types::BigInt,
+};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+const MINOR_DIGITS: usize = 2;
+const MINOR_IN_MAJOR: i64 = 100;
+
+/// Exact amount of money in minor units (kopecks, cents). Goes over the
+/// wire as a decimal string like `"1500.50"` so clients never round it
+/// through floats. Only currencies with two minor digits are accepted, see
+/// `SUPPORTED_CURRENCIES` in `services::dto::catalog`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, AsExpression, FromSqlRow)]
+#[diesel(sql_type = BigInt)]
+pub struct Amount(i64);
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+
+    pub const fn from_minor(minor: i64) -> Self {
+        Self(minor)
+    }
+
+    pub fn minor(self) -> i64 {
+        self.0
+    }
+
+    pub fn checked_add(self, other: Amount) -> Option<Amount> {
+        self.0.checked_add(other.0).map(Amount)
+    }
+
+    pub fn checked_sub(self, other: Amount) -> Option<Amount> {
+        self.0.checked_sub(other.0).map(Amount)
+    }
+
+    pub fn checked_mul(self, quantity: i64) -> Option<Amount> {
+        self.0.checked_mul(quantity).map(Amount)
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseAmountError;
+
+impl fmt::Display for ParseAmountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected a non-negative decimal with at most {} fraction digits",
+            MINOR_DIGITS
+        )
+    }
+}
+
+impl FromStr for Amount {
+    type Err = ParseAmountError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (major, minor) = value.split_once('.').unwrap_or((value, ""));
+
+        let all_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+
+        if major.is_empty()
+            || !all_digits(major)
+            || !all_digits(minor)
+            || minor.len() > MINOR_DIGITS
+            || (value.contains('.') && minor.is_empty())
+        {
+            return Err(ParseAmountError);
+        }
+
+        let major = major.parse::<i64>().map_err(|_| ParseAmountError)?;
+        let minor = format!("{:0<width$}", minor, width = MINOR_DIGITS)
+            .parse::<i64>()
+            .map_err(|_| ParseAmountError)?;
+
+        major
+            .checked_mul(MINOR_IN_MAJOR)
+            .and_then(|major| major.checked_add(minor))
+            .map(Amount)
+            .ok_or(ParseAmountError)
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        let minor_in_major = MINOR_IN_MAJOR as u64;
+
+        write!(
+            f,
+            "{}{}.{:0width$}",
+            sign,
+            abs / minor_in_major,
+            abs % minor_in_major,
+            width = MINOR_DIGITS
+        )
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+impl ToSql<BigInt, Pg> for Amount {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        <i64 as ToSql<BigInt, Pg>>::to_sql(&self.0, out)
+    }
+}
+
+impl FromSql<BigInt, Pg> for Amount {
+    fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+        <i64 as FromSql<BigInt, Pg>>::from_sql(bytes).map(Amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(value: &str) -> Option<i64> {
+        value.parse::<Amount>().ok().map(Amount::minor)
+    }
+
+    #[test]
+    fn parses_decimal_strings() {
+        assert_eq!(parse("1500.50"), Some(150_050));
+        assert_eq!(parse("1500.5"), Some(150_050));
+        assert_eq!(parse("1500"), Some(150_000));
+        assert_eq!(parse("0.01"), Some(1));
+        assert_eq!(parse("007.10"), Some(710));
+        assert_eq!(parse("92233720368547758.07"), Some(i64::MAX));
+    }
+
+    #[test]
+    fn rejects_malformed_strings() {
+        for value in [
+            "", ".5", "1.", "1.234", "-1", "+1", "1,5", " 1", "1e3", "1.-5",
+        ] {
+            assert_eq!(parse(value), None, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn rejects_overflow() {
+        assert_eq!(parse("92233720368547758.08"), None);
+        assert_eq!(parse("99999999999999999999"), None);
+    }
+
+    #[test]
+    fn displays_two_fraction_digits() {
+        assert_eq!(Amount::from_minor(150_050).to_string(), "1500.50");
+        assert_eq!(Amount::from_minor(1).to_string(), "0.01");
+        assert_eq!(Amount::ZERO.to_string(), "0.00");
+        assert_eq!(Amount::from_minor(-150).to_string(), "-1.50");
+        assert_eq!(
+            Amount::from_minor(i64::MIN).to_string(),
+            "-92233720368547758.08"
+        );
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for minor in [0, 5, 99, 100, 123_456, i64::MAX] {
+            let amount = Amount::from_minor(minor);
+
+            assert_eq!(amount.to_string().parse::<Amount>().unwrap(), amount);
+        }
+    }
+}
@@ -4,10 +4,10 @@ use diesel::{
     pg::{Pg, PgValue},
     serialize::{self, IsNull, Output, ToSql},
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::io::Write;
 
-#[derive(Debug, AsExpression, FromSqlRow, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow, Serialize, Deserialize)]
 #[diesel(sql_type = crate::db::orm::schema::sql_types::CourtCasesKinds)]
 pub enum CourtCasesKinds {
     #[serde(rename = "administrative")]
@@ -38,7 +38,7 @@ impl<'a> From<CourtCasesKinds> for &'a str {
     }
 }
 
-impl ToSql<crate::db::orm::schema::sql_types::CourtCasesDecisions, Pg> for CourtCasesKinds {
+impl ToSql<crate::db::orm::schema::sql_types::CourtCasesKinds, Pg> for CourtCasesKinds {
     fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
         match *self {
             CourtCasesKinds::Administrative => out.write_all(b"administrative")?,
@@ -51,7 +51,7 @@ impl ToSql<crate::db::orm::schema::sql_types::CourtCasesDecisions, Pg> for Court
     }
 }
 
-impl FromSql<crate::db::orm::schema::sql_types::CourtCasesDecisions, Pg> for CourtCasesKinds {
+impl FromSql<crate::db::orm::schema::sql_types::CourtCasesKinds, Pg> for CourtCasesKinds {
     fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
         match bytes.as_bytes() {
             b"administrative" => Ok(CourtCasesKinds::Administrative),
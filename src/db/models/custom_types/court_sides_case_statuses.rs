@@ -8,7 +8,7 @@ use diesel::{
 };
 use serde::Serialize;
 
-#[derive(Debug, AsExpression, FromSqlRow, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow, Serialize)]
 #[diesel(sql_type = crate::db::orm::schema::sql_types::CourtSidesCaseStatuses)]
 pub enum CourtSidesCaseStatuses {
     #[serde(rename = "winning")]
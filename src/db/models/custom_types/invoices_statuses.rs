@@ -0,0 +1,55 @@
+use std::io::Write;
+
+use diesel::{
+    deserialize::{self, FromSql, FromSqlRow},
+    expression::AsExpression,
+    pg::{Pg, PgValue},
+    serialize::{self, IsNull, Output, ToSql},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow, Serialize, Deserialize)]
+#[diesel(sql_type = crate::db::orm::schema::sql_types::InvoicesStatuses)]
+pub enum InvoicesStatuses {
+    #[serde(rename = "issued")]
+    Issued,
+
+    #[serde(rename = "partially_paid")]
+    PartiallyPaid,
+
+    #[serde(rename = "paid")]
+    Paid,
+
+    #[serde(rename = "void")]
+    Void,
+}
+
+impl<'a> From<InvoicesStatuses> for &'a str {
+    fn from(value: InvoicesStatuses) -> &'a str {
+        match value {
+            InvoicesStatuses::Issued => "issued",
+            InvoicesStatuses::PartiallyPaid => "partially_paid",
+            InvoicesStatuses::Paid => "paid",
+            InvoicesStatuses::Void => "void",
+        }
+    }
+}
+
+impl ToSql<crate::db::orm::schema::sql_types::InvoicesStatuses, Pg> for InvoicesStatuses {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(<&str>::from(*self).as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<crate::db::orm::schema::sql_types::InvoicesStatuses, Pg> for InvoicesStatuses {
+    fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"issued" => Ok(InvoicesStatuses::Issued),
+            b"partially_paid" => Ok(InvoicesStatuses::PartiallyPaid),
+            b"paid" => Ok(InvoicesStatuses::Paid),
+            b"void" => Ok(InvoicesStatuses::Void),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
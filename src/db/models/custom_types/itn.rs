@@ -0,0 +1,109 @@
+use diesel::{
+    deserialize::{self, FromSql, FromSqlRow},
+    expression::AsExpression,
+    pg::{Pg, PgValue},
+    serialize::{self, Output, ToSql},
+    sql_types::Varchar,
+};
+use serde::{Deserialize, Serialize};
+
+const ITN_10_WEIGHTS: [u32; 9] = [2, 4, 10, 3, 5, 9, 4, 6, 8];
+const ITN_11_WEIGHTS: [u32; 10] = [7, 2, 4, 10, 3, 5, 9, 4, 6, 8];
+const ITN_12_WEIGHTS: [u32; 11] = [3, 7, 2, 4, 10, 3, 5, 9, 4, 6, 8];
+
+/// Taxpayer identification number (INN). Organisations have 10 digits with
+/// one check digit, individuals 12 digits with two. Values read from the
+/// database are taken as they are, new ones are checked with `is_valid`.
+#[derive(Debug, Clone, PartialEq, Eq, AsExpression, FromSqlRow, Serialize, Deserialize)]
+#[diesel(sql_type = Varchar)]
+#[serde(transparent)]
+pub struct Itn(String);
+
+impl Itn {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_valid(&self) -> bool {
+        let digits = match self
+            .0
+            .chars()
+            .map(|c| c.to_digit(10))
+            .collect::<Option<Vec<u32>>>()
+        {
+            Some(digits) => digits,
+            None => return false,
+        };
+
+        match digits.len() {
+            10 => Self::check_digit(&digits, &ITN_10_WEIGHTS) == digits[9],
+            12 => {
+                Self::check_digit(&digits, &ITN_11_WEIGHTS) == digits[10]
+                    && Self::check_digit(&digits, &ITN_12_WEIGHTS) == digits[11]
+            }
+            _ => false,
+        }
+    }
+
+    fn check_digit(digits: &[u32], weights: &[u32]) -> u32 {
+        let sum: u32 = digits
+            .iter()
+            .zip(weights)
+            .map(|(digit, weight)| digit * weight)
+            .sum();
+
+        sum % 11 % 10
+    }
+}
+
+impl ToSql<Varchar, Pg> for Itn {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        <String as ToSql<Varchar, Pg>>::to_sql(&self.0, out)
+    }
+}
+
+impl FromSql<Varchar, Pg> for Itn {
+    fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+        <String as FromSql<Varchar, Pg>>::from_sql(bytes).map(Itn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn itn(value: &str) -> Itn {
+        Itn(value.to_owned())
+    }
+
+    #[test]
+    fn accepts_valid_numbers() {
+        assert!(itn("7707083893").is_valid());
+        assert!(itn("7736050003").is_valid());
+        assert!(itn("500100732259").is_valid());
+    }
+
+    #[test]
+    fn rejects_wrong_check_digits() {
+        assert!(!itn("7707083894").is_valid());
+        // Only the second of the two check digits is off
+        assert!(!itn("500100732258").is_valid());
+        assert!(!itn("500100732269").is_valid());
+    }
+
+    #[test]
+    fn rejects_other_lengths() {
+        assert!(!itn("").is_valid());
+        assert!(!itn("770708389").is_valid());
+        assert!(!itn("77070838930").is_valid());
+        assert!(!itn("5001007322590").is_valid());
+    }
+
+    #[test]
+    fn rejects_non_digits() {
+        assert!(!itn("77070838 3").is_valid());
+        assert!(!itn("+707083893").is_valid());
+        assert!(!itn("50010073225a").is_valid());
+        assert!(!itn("٧٧٠٧٠٨٣٨٩٣").is_valid());
+    }
+}
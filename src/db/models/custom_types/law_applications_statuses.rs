@@ -0,0 +1,48 @@
+use std::io::Write;
+
+use diesel::{
+    deserialize::{self, FromSql, FromSqlRow},
+    expression::AsExpression,
+    pg::{Pg, PgValue},
+    serialize::{self, IsNull, Output, ToSql},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow, Serialize, Deserialize)]
+#[diesel(sql_type = crate::db::orm::schema::sql_types::LawApplicationsStatuses)]
+pub enum LawApplicationsStatuses {
+    #[serde(rename = "pending")]
+    Pending,
+
+    #[serde(rename = "approved")]
+    Approved,
+
+    #[serde(rename = "rejected")]
+    Rejected,
+}
+
+impl ToSql<crate::db::orm::schema::sql_types::LawApplicationsStatuses, Pg>
+    for LawApplicationsStatuses
+{
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        match *self {
+            LawApplicationsStatuses::Pending => out.write_all(b"pending")?,
+            LawApplicationsStatuses::Approved => out.write_all(b"approved")?,
+            LawApplicationsStatuses::Rejected => out.write_all(b"rejected")?,
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<crate::db::orm::schema::sql_types::LawApplicationsStatuses, Pg>
+    for LawApplicationsStatuses
+{
+    fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"pending" => Ok(LawApplicationsStatuses::Pending),
+            b"approved" => Ok(LawApplicationsStatuses::Approved),
+            b"rejected" => Ok(LawApplicationsStatuses::Rejected),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
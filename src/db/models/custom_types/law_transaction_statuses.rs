@@ -4,10 +4,10 @@ use diesel::{
     pg::{Pg, PgValue},
     serialize::{self, IsNull, Output, ToSql},
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::io::Write;
 
-#[derive(Debug, AsExpression, FromSqlRow, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow, Serialize, Deserialize)]
 #[diesel(sql_type = crate::db::orm::schema::sql_types::LawTransactionsStatues)]
 pub enum LawTransactionsStatues {
     #[serde(rename = "started")]
@@ -16,8 +16,14 @@ pub enum LawTransactionsStatues {
     #[serde(rename = "processing")]
     Processing,
 
-    #[serde(rename = "complete")]
-    Complete
+    #[serde(rename = "completed")]
+    Completed,
+
+    #[serde(rename = "declined")]
+    Declined,
+
+    #[serde(rename = "cancelled")]
+    Cancelled
 }
 
 impl<'a> From<LawTransactionsStatues> for &'a str {
@@ -25,7 +31,9 @@ impl<'a> From<LawTransactionsStatues> for &'a str {
         match value {
             LawTransactionsStatues::Started => "started",
             LawTransactionsStatues::Processing => "processing",
-            LawTransactionsStatues::Complete => "complete"
+            LawTransactionsStatues::Completed => "completed",
+            LawTransactionsStatues::Declined => "declined",
+            LawTransactionsStatues::Cancelled => "cancelled"
         }
     }
 }
@@ -35,7 +43,9 @@ impl ToSql<crate::db::orm::schema::sql_types::LawTransactionsStatues, Pg> for La
         match *self {
             LawTransactionsStatues::Started => out.write_all(b"started")?,
             LawTransactionsStatues::Processing => out.write_all(b"processing")?,
-            LawTransactionsStatues::Complete => out.write_all(b"complete")?,
+            LawTransactionsStatues::Completed => out.write_all(b"completed")?,
+            LawTransactionsStatues::Declined => out.write_all(b"declined")?,
+            LawTransactionsStatues::Cancelled => out.write_all(b"cancelled")?,
         }
         Ok(IsNull::No)
     }
@@ -46,7 +56,9 @@ impl FromSql<crate::db::orm::schema::sql_types::LawTransactionsStatues, Pg> for
         match bytes.as_bytes() {
             b"started" => Ok(LawTransactionsStatues::Started),
             b"processing" => Ok(LawTransactionsStatues::Processing),
-            b"complete" => Ok(LawTransactionsStatues::Complete),
+            b"completed" => Ok(LawTransactionsStatues::Completed),
+            b"declined" => Ok(LawTransactionsStatues::Declined),
+            b"cancelled" => Ok(LawTransactionsStatues::Cancelled),
             _ => Err("Unrecognized enum variant".into()),
         }
     }
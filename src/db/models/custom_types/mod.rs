@@ -3,4 +3,9 @@ pub mod court_cases_decisions;
 pub mod court_cases_kinds;
 pub mod court_sides_kinds;
 pub mod court_sides_case_statuses;
-pub mod law_transaction_statuses;
\ No newline at end of file
+pub mod law_transaction_statuses;
+pub mod law_applications_statuses;
+pub mod itn;
+pub mod amount;
+pub mod invoices_statuses;
+pub mod payments_statuses;
\ No newline at end of file
@@ -0,0 +1,51 @@
+use std::io::Write;
+
+use diesel::{
+    deserialize::{self, FromSql, FromSqlRow},
+    expression::AsExpression,
+    pg::{Pg, PgValue},
+    serialize::{self, IsNull, Output, ToSql},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow, Serialize, Deserialize)]
+#[diesel(sql_type = crate::db::orm::schema::sql_types::PaymentsStatuses)]
+pub enum PaymentsStatuses {
+    /// The provider is being charged
+    #[serde(rename = "pending")]
+    Pending,
+
+    #[serde(rename = "succeeded")]
+    Succeeded,
+
+    #[serde(rename = "failed")]
+    Failed,
+}
+
+impl<'a> From<PaymentsStatuses> for &'a str {
+    fn from(value: PaymentsStatuses) -> &'a str {
+        match value {
+            PaymentsStatuses::Pending => "pending",
+            PaymentsStatuses::Succeeded => "succeeded",
+            PaymentsStatuses::Failed => "failed",
+        }
+    }
+}
+
+impl ToSql<crate::db::orm::schema::sql_types::PaymentsStatuses, Pg> for PaymentsStatuses {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(<&str>::from(*self).as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<crate::db::orm::schema::sql_types::PaymentsStatuses, Pg> for PaymentsStatuses {
+    fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"pending" => Ok(PaymentsStatuses::Pending),
+            b"succeeded" => Ok(PaymentsStatuses::Succeeded),
+            b"failed" => Ok(PaymentsStatuses::Failed),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
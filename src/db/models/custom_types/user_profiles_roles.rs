@@ -1,4 +1,4 @@
-use std::io::Write;
+use std::{io::Write, str::FromStr};
 
 use diesel::{
     deserialize::{self, FromSql, FromSqlRow},
@@ -6,9 +6,9 @@ use diesel::{
     pg::{Pg, PgValue},
     serialize::{self, IsNull, Output, ToSql},
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, AsExpression, FromSqlRow, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow, Serialize, Deserialize)]
 #[diesel(sql_type = crate::db::orm::schema::sql_types::UserProfilesRoles)]
 pub enum UserProfilesRoles {
     #[serde(rename = "user")]
@@ -20,7 +20,7 @@ pub enum UserProfilesRoles {
     #[serde(rename = "law")]
     Law,
 
-    #[serde(rename = "employee")]
+    #[serde(rename = "admin")]
     Admin,
 }
 
@@ -35,6 +35,20 @@ impl<'a> From<UserProfilesRoles> for &'a str {
     }
 }
 
+impl FromStr for UserProfilesRoles {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "user" => Ok(UserProfilesRoles::User),
+            "employee" => Ok(UserProfilesRoles::Employee),
+            "law" => Ok(UserProfilesRoles::Law),
+            "admin" => Ok(UserProfilesRoles::Admin),
+            _ => Err(()),
+        }
+    }
+}
+
 impl ToSql<crate::db::orm::schema::sql_types::UserProfilesRoles, Pg> for UserProfilesRoles {
     fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
         match *self {
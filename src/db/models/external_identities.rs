@@ -0,0 +1,18 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use super::auth_data::AuthData;
+
+#[derive(Queryable, Associations, Selectable, Identifiable, Debug)]
+#[diesel(belongs_to(AuthData, foreign_key = auth_uid))]
+#[diesel(table_name = crate::db::orm::schema::external_identities)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(uid))]
+pub struct ExternalIdentity {
+    pub uid: Uuid,
+    pub auth_uid: Uuid,
+    pub issuer: String,
+    pub subject: String,
+    pub created_at: NaiveDateTime,
+}
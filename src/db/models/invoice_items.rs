@@ -0,0 +1,31 @@
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::custom_types::amount::Amount;
+use super::invoices::Invoice;
+
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug, Serialize)]
+#[diesel(belongs_to(Invoice, foreign_key = invoice_uid))]
+#[diesel(table_name = crate::db::orm::schema::invoice_items)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(uid))]
+pub struct InvoiceItem {
+    pub uid: Uuid,
+    pub invoice_uid: Uuid,
+    pub position: i32,
+    pub service_uid: Option<Uuid>,
+    /// Name of the service when the invoice was issued
+    pub name: String,
+    pub quantity: i32,
+    #[diesel(column_name = unit_price_minor)]
+    pub unit_price: Amount,
+    #[diesel(column_name = discount_minor)]
+    pub discount: Amount,
+    /// Basis points, 2000 is 20%
+    pub tax_rate_bp: i32,
+    #[diesel(column_name = tax_minor)]
+    pub tax: Amount,
+    #[diesel(column_name = total_minor)]
+    pub total: Amount,
+}
@@ -0,0 +1,35 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::custom_types::{amount::Amount, invoices_statuses::InvoicesStatuses};
+
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize)]
+#[diesel(table_name = crate::db::orm::schema::invoices)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(uid))]
+pub struct Invoice {
+    pub uid: Uuid,
+    /// Sequential number printed for accountants
+    pub number: i64,
+    pub transaction_uid: Uuid,
+    pub client_uid: Option<Uuid>,
+    pub law_uid: Option<Uuid>,
+    pub currency: String,
+    #[diesel(column_name = subtotal_minor)]
+    pub subtotal: Amount,
+    #[diesel(column_name = discount_minor)]
+    pub discount: Amount,
+    #[diesel(column_name = tax_minor)]
+    pub tax: Amount,
+    /// `subtotal - discount + tax`
+    #[diesel(column_name = total_minor)]
+    pub total: Amount,
+    #[diesel(column_name = paid_minor)]
+    pub paid: Amount,
+    pub status: InvoicesStatuses,
+    pub issued_by: Option<Uuid>,
+    pub voided_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
@@ -0,0 +1,25 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::custom_types::{itn::Itn, law_applications_statuses::LawApplicationsStatuses};
+use super::user_profiles::UserProfile;
+
+#[derive(Queryable, Associations, Selectable, Identifiable, Debug, Serialize)]
+#[diesel(belongs_to(UserProfile, foreign_key = profile_uid))]
+#[diesel(table_name = crate::db::orm::schema::law_applications)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(uid))]
+pub struct LawApplication {
+    pub uid: Uuid,
+    pub profile_uid: Uuid,
+    pub itn: Itn,
+    pub start_activity_date: NaiveDateTime,
+    pub status: LawApplicationsStatuses,
+    /// Given by the reviewer, required for rejections
+    pub reason: Option<String>,
+    pub reviewed_by: Option<Uuid>,
+    pub reviewed_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
@@ -3,12 +3,14 @@ use diesel::prelude::*;
 use serde::Serialize;
 use uuid::Uuid;
 
+use super::custom_types::itn::Itn;
+
 #[derive(Queryable, Identifiable, Selectable, Debug, Serialize)]
 #[diesel(table_name = crate::db::orm::schema::law_profiles)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 #[diesel(primary_key(uid))]
 pub struct LawProfile {
     pub uid: Uuid,
-    pub itn: String,
+    pub itn: Itn,
     pub start_activity_date: NaiveDateTime,
 }
@@ -3,7 +3,7 @@ use uuid::Uuid;
 use diesel::prelude::*;
 use serde::Serialize;
 
-use super::custom_types::law_transaction_statuses::LawTransactionsStatues;
+use super::custom_types::{amount::Amount, law_transaction_statuses::LawTransactionsStatues};
 
 
 #[derive(Queryable, Identifiable, Selectable, Debug, Serialize)]
@@ -12,9 +12,21 @@ use super::custom_types::law_transaction_statuses::LawTransactionsStatues;
 #[diesel(belongs_to(super::user_profiles::UserProfiles, foreign_key = client_uid))]
 #[diesel(primary_key(uid))]
 pub struct LawTransactions {
-    uid: Uuid,
-    court_case_uid: Option<Uuid>,
-    client_uid: Option<Uuid>,
-    status: LawTransactionsStatues,
-    created_at: NaiveDateTime
+    pub uid: Uuid,
+    pub court_case_uid: Option<Uuid>,
+    pub client_uid: Option<Uuid>,
+    pub status: LawTransactionsStatues,
+    pub created_at: NaiveDateTime,
+    pub law_uid: Option<Uuid>,
+    pub service_uid: Option<Uuid>,
+    /// Price of the service when the client engaged the lawyer
+    #[diesel(column_name = cost_minor)]
+    pub cost: Amount,
+    pub currency: String,
+    /// Given when the deal is declined or cancelled
+    pub reason: Option<String>,
+    pub accepted_at: Option<NaiveDateTime>,
+    pub declined_at: Option<NaiveDateTime>,
+    pub completed_at: Option<NaiveDateTime>,
+    pub cancelled_at: Option<NaiveDateTime>,
 }
\ No newline at end of file
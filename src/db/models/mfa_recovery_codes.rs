@@ -0,0 +1,17 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use super::auth_data::AuthData;
+
+#[derive(Queryable, Associations, Selectable, Identifiable, Debug)]
+#[diesel(belongs_to(AuthData, foreign_key = auth_uid))]
+#[diesel(table_name = crate::db::orm::schema::mfa_recovery_codes)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(uid))]
+pub struct MfaRecoveryCode {
+    pub uid: Uuid,
+    pub auth_uid: Uuid,
+    pub code_hash: String,
+    pub used_at: Option<NaiveDateTime>,
+}
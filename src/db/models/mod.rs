@@ -10,4 +10,17 @@ pub mod message_files;
 pub mod service;
 pub mod court_cases;
 pub mod court_sides;
-pub mod law_transactions;
\ No newline at end of file
+pub mod law_transactions;
+pub mod mfa_recovery_codes;
+pub mod external_identities;
+pub mod service_accounts;
+pub mod api_keys;
+pub mod audit_events;
+pub mod case_ledger;
+pub mod case_ledger_checkpoints;
+pub mod passport_history;
+
+pub mod law_applications;
+pub mod invoices;
+pub mod invoice_items;
+pub mod payments;
\ No newline at end of file
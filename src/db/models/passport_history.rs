@@ -0,0 +1,19 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize)]
+#[diesel(table_name = crate::db::orm::schema::passport_history)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(uid))]
+pub struct PassportHistoryEntry {
+    pub uid: Uuid,
+    pub passport_uid: Uuid,
+    /// `auth_data` uid
+    pub changed_by: Option<Uuid>,
+    pub action: String,
+    /// Comma separated names of the changed fields
+    pub fields: String,
+    pub created_at: NaiveDateTime,
+}
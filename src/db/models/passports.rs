@@ -1,8 +1,10 @@
-use chrono::NaiveDate;
+use chrono::NaiveDateTime;
 use diesel::prelude::*;
 use serde::Serialize;
 use uuid::Uuid;
 
+/// `number`, `series`, `registration_place` and `birthday_date` are
+/// ciphertexts once `data_key` is set, see `PassportKeys::open`
 #[derive(Queryable, Debug, Serialize, Selectable)]
 #[diesel(table_name = crate::db::orm::schema::passports)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
@@ -11,8 +13,21 @@ pub struct Passport {
     pub first_name: String,
     pub second_name: String,
     pub patronymic: Option<String>,
+    #[serde(skip)]
     pub number: Option<String>,
+    #[serde(skip)]
     pub series: Option<String>,
+    #[serde(skip)]
     pub registration_place: Option<String>,
-    pub birthday_date: NaiveDate,
+    #[serde(skip)]
+    pub birthday_date: String,
+    #[serde(skip)]
+    pub number_index: Option<String>,
+    #[serde(skip)]
+    pub data_key: Option<String>,
+    #[serde(skip)]
+    pub key_id: Option<String>,
+    pub verified_at: Option<NaiveDateTime>,
+    /// `auth_data` uid of the employee
+    pub verified_by: Option<Uuid>,
 }
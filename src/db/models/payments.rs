@@ -0,0 +1,31 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::custom_types::{amount::Amount, payments_statuses::PaymentsStatuses};
+use super::invoices::Invoice;
+
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug, Serialize)]
+#[diesel(belongs_to(Invoice, foreign_key = invoice_uid))]
+#[diesel(table_name = crate::db::orm::schema::payments)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(uid))]
+pub struct Payment {
+    pub uid: Uuid,
+    pub invoice_uid: Uuid,
+    #[diesel(column_name = amount_minor)]
+    pub amount: Amount,
+    /// `manual` or the name of the payment provider
+    pub provider: String,
+    pub reference: Option<String>,
+    pub note: Option<String>,
+    /// Employee who recorded a manual payment
+    pub recorded_by: Option<Uuid>,
+    /// When the provider accepted the charge or the payment was made
+    /// outside the system
+    pub paid_at: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+    /// Only succeeded payments count towards `paid` of the invoice
+    pub status: PaymentsStatuses,
+}
@@ -1,7 +1,10 @@
+use chrono::NaiveDateTime;
 use uuid::Uuid;
 use diesel::prelude::*;
 use serde::Serialize;
 
+use super::custom_types::amount::Amount;
+
 #[derive(Queryable, Identifiable, Selectable, Debug, Serialize)]
 #[diesel(table_name = crate::db::orm::schema::services)]
 #[diesel(belongs_to(super::user_profiles::UserProfile, foreign_key = law_uid))]
@@ -10,5 +13,11 @@ pub struct Service {
     pub uid: Uuid,
     pub law_uid: Uuid,
     pub name: String,
-    pub cost: f64,
+    #[diesel(column_name = cost_minor)]
+    pub cost: Amount,
+    /// ISO 4217 code
+    pub currency: String,
+    /// Archived services stay attached to past deals but are not offered
+    pub archived_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
 }
\ No newline at end of file
@@ -22,4 +22,7 @@ pub struct UserProfile {
     pub avatar_uid: Option<Uuid>,
     pub role: UserProfilesRoles,
     pub created_at: NaiveDateTime,
+    pub blocked_at: Option<NaiveDateTime>,
+    /// Profiles are kept for court cases and transactions referring to them
+    pub deleted_at: Option<NaiveDateTime>,
 }
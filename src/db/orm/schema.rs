@@ -17,15 +17,67 @@ pub mod sql_types {
     #[diesel(postgres_type(name = "court_sides_kinds"))]
     pub struct CourtSidesKinds;
 
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "invoices_statuses"))]
+    pub struct InvoicesStatuses;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "law_applications_statuses"))]
+    pub struct LawApplicationsStatuses;
+
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "law_transactions_statues"))]
     pub struct LawTransactionsStatues;
 
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "payments_statuses"))]
+    pub struct PaymentsStatuses;
+
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "user_profiles_roles"))]
     pub struct UserProfilesRoles;
 }
 
+diesel::table! {
+    api_keys (uid) {
+        uid -> Uuid,
+        service_account_uid -> Uuid,
+        #[max_length = 255]
+        name -> Varchar,
+        #[max_length = 16]
+        prefix -> Varchar,
+        #[max_length = 64]
+        key_hash -> Bpchar,
+        scopes -> Array<Text>,
+        created_by -> Nullable<Uuid>,
+        expires_at -> Nullable<Timestamp>,
+        last_used_at -> Nullable<Timestamp>,
+        revoked_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    audit_events (uid) {
+        uid -> Uuid,
+        #[max_length = 16]
+        actor_kind -> Varchar,
+        actor_uid -> Nullable<Uuid>,
+        #[max_length = 64]
+        action -> Varchar,
+        #[max_length = 32]
+        target_kind -> Nullable<Varchar>,
+        target_uid -> Nullable<Uuid>,
+        #[max_length = 64]
+        ip -> Nullable<Varchar>,
+        #[max_length = 16]
+        outcome -> Varchar,
+        #[max_length = 255]
+        details -> Nullable<Varchar>,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     auth_data (uid) {
         uid -> Uuid,
@@ -35,7 +87,42 @@ diesel::table! {
         #[max_length = 255]
         username -> Varchar,
         #[max_length = 255]
-        password -> Varchar,
+        password -> Nullable<Varchar>,
+        totp_secret -> Nullable<Text>,
+        totp_enabled -> Bool,
+        email_verified -> Bool,
+        #[max_length = 32]
+        totp_key_id -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    case_ledger (seq) {
+        seq -> Int8,
+        #[max_length = 16]
+        entity -> Varchar,
+        entity_uid -> Uuid,
+        court_case_uid -> Uuid,
+        #[max_length = 8]
+        operation -> Varchar,
+        payload -> Text,
+        #[max_length = 64]
+        prev_hash -> Bpchar,
+        #[max_length = 64]
+        entry_hash -> Bpchar,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    case_ledger_checkpoints (seq) {
+        seq -> Int8,
+        #[max_length = 64]
+        entry_hash -> Bpchar,
+        #[max_length = 64]
+        key_id -> Nullable<Varchar>,
+        signature -> Text,
+        created_at -> Timestamp,
     }
 }
 
@@ -81,6 +168,18 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    external_identities (uid) {
+        uid -> Uuid,
+        auth_uid -> Uuid,
+        #[max_length = 255]
+        issuer -> Varchar,
+        #[max_length = 255]
+        subject -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     files (uid) {
         uid -> Uuid,
@@ -90,6 +189,66 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    invoice_items (uid) {
+        uid -> Uuid,
+        invoice_uid -> Uuid,
+        position -> Int4,
+        service_uid -> Nullable<Uuid>,
+        #[max_length = 255]
+        name -> Varchar,
+        quantity -> Int4,
+        unit_price_minor -> Int8,
+        discount_minor -> Int8,
+        tax_rate_bp -> Int4,
+        tax_minor -> Int8,
+        total_minor -> Int8,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::InvoicesStatuses;
+
+    invoices (uid) {
+        uid -> Uuid,
+        number -> Int8,
+        transaction_uid -> Uuid,
+        client_uid -> Nullable<Uuid>,
+        law_uid -> Nullable<Uuid>,
+        #[max_length = 3]
+        currency -> Varchar,
+        subtotal_minor -> Int8,
+        discount_minor -> Int8,
+        tax_minor -> Int8,
+        total_minor -> Int8,
+        paid_minor -> Int8,
+        status -> InvoicesStatuses,
+        issued_by -> Nullable<Uuid>,
+        voided_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::LawApplicationsStatuses;
+
+    law_applications (uid) {
+        uid -> Uuid,
+        profile_uid -> Uuid,
+        #[max_length = 15]
+        itn -> Varchar,
+        start_activity_date -> Timestamp,
+        status -> LawApplicationsStatuses,
+        #[max_length = 255]
+        reason -> Nullable<Varchar>,
+        reviewed_by -> Nullable<Uuid>,
+        reviewed_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     law_profiles (uid) {
         uid -> Uuid,
@@ -109,6 +268,17 @@ diesel::table! {
         client_uid -> Nullable<Uuid>,
         status -> LawTransactionsStatues,
         created_at -> Timestamp,
+        law_uid -> Nullable<Uuid>,
+        service_uid -> Nullable<Uuid>,
+        cost_minor -> Int8,
+        #[max_length = 3]
+        currency -> Varchar,
+        #[max_length = 255]
+        reason -> Nullable<Varchar>,
+        accepted_at -> Nullable<Timestamp>,
+        declined_at -> Nullable<Timestamp>,
+        completed_at -> Nullable<Timestamp>,
+        cancelled_at -> Nullable<Timestamp>,
     }
 }
 
@@ -129,18 +299,76 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    mfa_recovery_codes (uid) {
+        uid -> Uuid,
+        auth_uid -> Uuid,
+        #[max_length = 64]
+        code_hash -> Bpchar,
+        used_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    passport_history (uid) {
+        uid -> Uuid,
+        passport_uid -> Uuid,
+        changed_by -> Nullable<Uuid>,
+        #[max_length = 16]
+        action -> Varchar,
+        #[max_length = 255]
+        fields -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     passports (uid) {
         uid -> Uuid,
         first_name -> Varchar,
         second_name -> Varchar,
         patronymic -> Nullable<Varchar>,
-        #[max_length = 6]
-        number -> Nullable<Bpchar>,
-        #[max_length = 4]
-        series -> Nullable<Bpchar>,
-        registration_place -> Nullable<Varchar>,
-        birthday_date -> Date,
+        number -> Nullable<Text>,
+        series -> Nullable<Text>,
+        registration_place -> Nullable<Text>,
+        birthday_date -> Text,
+        #[max_length = 64]
+        number_index -> Nullable<Bpchar>,
+        data_key -> Nullable<Text>,
+        #[max_length = 32]
+        key_id -> Nullable<Varchar>,
+        verified_at -> Nullable<Timestamp>,
+        verified_by -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::PaymentsStatuses;
+
+    payments (uid) {
+        uid -> Uuid,
+        invoice_uid -> Uuid,
+        amount_minor -> Int8,
+        #[max_length = 32]
+        provider -> Varchar,
+        #[max_length = 255]
+        reference -> Nullable<Varchar>,
+        #[max_length = 255]
+        note -> Nullable<Varchar>,
+        recorded_by -> Nullable<Uuid>,
+        paid_at -> Timestamp,
+        created_at -> Timestamp,
+        status -> PaymentsStatuses,
+    }
+}
+
+diesel::table! {
+    service_accounts (uid) {
+        uid -> Uuid,
+        #[max_length = 255]
+        name -> Varchar,
+        created_at -> Timestamp,
     }
 }
 
@@ -150,7 +378,11 @@ diesel::table! {
         law_uid -> Uuid,
         #[max_length = 255]
         name -> Varchar,
-        cost -> Float8,
+        cost_minor -> Int8,
+        #[max_length = 3]
+        currency -> Varchar,
+        archived_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
     }
 }
 
@@ -165,35 +397,64 @@ diesel::table! {
         avatar_uid -> Nullable<Uuid>,
         role -> UserProfilesRoles,
         created_at -> Timestamp,
+        blocked_at -> Nullable<Timestamp>,
+        deleted_at -> Nullable<Timestamp>,
     }
 }
 
+diesel::joinable!(api_keys -> auth_data (created_by));
+diesel::joinable!(api_keys -> service_accounts (service_account_uid));
 diesel::joinable!(auth_data -> user_profiles (profile_uid));
 diesel::joinable!(chats -> user_profiles (creator_uid));
 diesel::joinable!(court_sides -> court_cases (court_case_uid));
 diesel::joinable!(court_sides -> user_profiles (user_uid));
+diesel::joinable!(external_identities -> auth_data (auth_uid));
+diesel::joinable!(invoice_items -> invoices (invoice_uid));
+diesel::joinable!(invoice_items -> services (service_uid));
+diesel::joinable!(invoices -> auth_data (issued_by));
+diesel::joinable!(invoices -> law_transactions (transaction_uid));
+diesel::joinable!(law_applications -> auth_data (reviewed_by));
+diesel::joinable!(law_applications -> user_profiles (profile_uid));
 diesel::joinable!(law_transactions -> court_cases (court_case_uid));
+diesel::joinable!(law_transactions -> services (service_uid));
 diesel::joinable!(law_transactions -> user_profiles (client_uid));
 diesel::joinable!(message_files -> files (file_uid));
 diesel::joinable!(message_files -> messages (message_uid));
 diesel::joinable!(messages -> chats (chat_uid));
 diesel::joinable!(messages -> user_profiles (sender_uid));
+diesel::joinable!(mfa_recovery_codes -> auth_data (auth_uid));
+diesel::joinable!(passport_history -> passports (passport_uid));
+diesel::joinable!(payments -> auth_data (recorded_by));
+diesel::joinable!(payments -> invoices (invoice_uid));
+diesel::joinable!(passports -> auth_data (verified_by));
 diesel::joinable!(services -> user_profiles (law_uid));
 diesel::joinable!(user_profiles -> files (avatar_uid));
 diesel::joinable!(user_profiles -> law_profiles (law_profile));
 diesel::joinable!(user_profiles -> passports (passport_uid));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    api_keys,
+    audit_events,
     auth_data,
+    case_ledger,
+    case_ledger_checkpoints,
     chats,
     court_cases,
     court_sides,
+    external_identities,
     files,
+    invoice_items,
+    invoices,
+    law_applications,
     law_profiles,
     law_transactions,
     message_files,
     messages,
+    mfa_recovery_codes,
+    passport_history,
     passports,
+    payments,
+    service_accounts,
     services,
     user_profiles,
 );
@@ -4,13 +4,33 @@ mod config;
 mod db;
 mod services;
 mod state;
+#[cfg(test)]
+mod test_support;
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use dotenvy::dotenv;
 
-use crate::services::{auth::AuthService, user::UserService};
-use actix_web::{error, middleware::Logger, web, App, HttpServer, http::header::{self, ContentType}};
+use crate::services::{
+    api_keys::ApiKeyService,
+    audit::AuditService,
+    auth::AuthService,
+    billing::BillingService,
+    catalog::CatalogService,
+    law_applications::LawApplicationService,
+    ledger::LedgerService,
+    login_throttle::LoginThrottleService,
+    mailer::{FileMailer, Mailer, SmtpMailer},
+    mfa::MfaService,
+    oidc::OidcService,
+    passports::PassportService,
+    payments::{LocalPaymentProvider, PaymentProvider},
+    session::SessionService,
+    transactions::TransactionService,
+    user::UserService,
+    verification::VerificationService,
+};
+use actix_web::{error, middleware::Logger, web, App, HttpServer, http::header};
 use api::errors::invalid_data;
 use cache::Cache;
 use config::Config;
@@ -30,7 +50,24 @@ async fn main() -> std::io::Result<()> {
     let config = Arc::new(Config::default());
     let clonned_config = config.clone();
     let db = Arc::new(Db::new(config.db_url()).expect("Db instance error"));
-    let cache = Cache::new(config.redis_url()).expect("Redis instance error");
+    let cache = Arc::new(Cache::new(config.redis_url()).expect("Redis instance error"));
+
+    let mailer: Arc<dyn Mailer> = match config.smtp_url() {
+        Some(url) => {
+            Arc::new(SmtpMailer::new(url, config.mail_from()).expect("SMTP mailer error"))
+        }
+        None => {
+            log::warn!(
+                "SMTP_URL not specified. Emails are written to {} instead",
+                config.mail_dir()
+            );
+
+            Arc::new(FileMailer::new(config.mail_dir()))
+        }
+    };
+
+    // No real provider is connected yet
+    let payment_provider: Arc<dyn PaymentProvider> = Arc::new(LocalPaymentProvider);
 
     log::info!("Running migrations...");
 
@@ -39,6 +76,19 @@ async fn main() -> std::io::Result<()> {
     let data = web::Data::new(AppState::new(
         AuthService::new(db.clone()),
         UserService::new(db.clone()),
+        SessionService::new(cache.clone()),
+        LoginThrottleService::new(cache.clone()),
+        MfaService::new(db.clone(), cache.clone()),
+        OidcService::new(cache.clone(), config.oidc().cloned()),
+        VerificationService::new(db.clone(), cache.clone(), mailer),
+        ApiKeyService::new(db.clone()),
+        AuditService::new(db.clone()),
+        LedgerService::new(db.clone()),
+        PassportService::new(db.clone()),
+        LawApplicationService::new(db.clone()),
+        CatalogService::new(db.clone()),
+        TransactionService::new(db.clone()),
+        BillingService::new(db.clone(), payment_provider),
         config.clone(),
         cache,
     ));
@@ -49,6 +99,99 @@ async fn main() -> std::io::Result<()> {
             log::error!("{:?}", err);
             error::InternalError::from_response(err, invalid_data()).into()
         });
+    let query_cfg = web::QueryConfig::default().error_handler(|err, _req| {
+        log::error!("{:?}", err);
+        error::InternalError::from_response(err, invalid_data()).into()
+    });
+
+    let reencrypt_state = data.clone();
+    actix_web::rt::spawn(async move {
+        let mut reencrypted = 0;
+        let mut failed = vec![];
+        let mut after = None;
+
+        loop {
+            let state = reencrypt_state.clone();
+            let block_result = web::block(move || {
+                state.passport_service().reencrypt_batch(
+                    state.config(),
+                    after,
+                    state.config().passport_reencrypt_batch(),
+                )
+            })
+            .await;
+
+            match block_result {
+                Ok(Ok(batch)) => {
+                    reencrypted += batch.updated;
+                    failed.extend(batch.failed);
+
+                    match batch.last_uid {
+                        Some(last_uid) => after = Some(last_uid),
+                        None => break,
+                    }
+                }
+                _ => {
+                    log::error!("Passport re-encryption stopped");
+                    break;
+                }
+            }
+        }
+
+        if reencrypted > 0 {
+            log::info!("{} passports re-encrypted", reencrypted);
+        }
+
+        if !failed.is_empty() {
+            log::error!(
+                "{} passports could not be re-encrypted and still need their old keys: {:?}",
+                failed.len(),
+                failed
+            );
+        }
+    });
+
+    let checkpoint_state = data.clone();
+    actix_web::rt::spawn(async move {
+        let period = Duration::from_secs(checkpoint_state.config().ledger_checkpoint_interval());
+        let mut interval = actix_web::rt::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            let state = checkpoint_state.clone();
+            let block_result =
+                web::block(move || state.ledger_service().create_checkpoint(state.config())).await;
+
+            match block_result {
+                Ok(Ok(Some(checkpoint))) => {
+                    log::info!("Court case ledger checkpoint signed at {}", checkpoint.seq)
+                }
+                Ok(Ok(None)) => (),
+                _ => log::error!("Court case ledger checkpoint failed"),
+            }
+        }
+    });
+
+    let reconcile_state = data.clone();
+    actix_web::rt::spawn(async move {
+        let period = Duration::from_secs(reconcile_state.config().payments_reconcile_interval());
+        let mut interval = actix_web::rt::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            let state = reconcile_state.clone();
+            let block_result =
+                web::block(move || state.billing_service().reconcile_pending()).await;
+
+            match block_result {
+                Ok(Ok(0)) => (),
+                Ok(Ok(settled)) => log::info!("{} pending payments reconciled", settled),
+                _ => log::error!("Pending payments reconciliation failed"),
+            }
+        }
+    });
 
     log::info!("Starting server at {}:{}", config.host(), config.port());
 
@@ -60,6 +203,7 @@ async fn main() -> std::io::Result<()> {
                 header::AUTHORIZATION,
                 header::ACCEPT,
                 header::CONTENT_TYPE,
+                header::HeaderName::from_static("x-api-key"),
                 header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
                 header::CONTENT_TYPE
               ])
@@ -69,8 +213,10 @@ async fn main() -> std::io::Result<()> {
         App::new()
             .wrap(cors)
             .app_data(json_cfg.clone())
+            .app_data(query_cfg.clone())
             .app_data(data.clone())
             .wrap(Logger::default())
+            .configure(api::configure_well_known)
             .service(web::scope("/api").configure(api::configure(clonned_config.clone())))
     })
     .bind((config.host(), config.port()))?
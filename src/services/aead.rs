@@ -0,0 +1,62 @@
+use data_encoding::BASE64;
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    rand::{SecureRandom, SystemRandom},
+};
+
+#[derive(Debug)]
+pub enum AeadError {
+    InvalidKey,
+    Encrypt,
+    Decrypt,
+}
+
+/// AES-256-GCM key from 32 bytes
+pub fn key(key: &[u8]) -> Result<LessSafeKey, AeadError> {
+    UnboundKey::new(&AES_256_GCM, key)
+        .map(LessSafeKey::new)
+        .map_err(|_| AeadError::InvalidKey)
+}
+
+/// Seals `plaintext` under a random nonce. The result is the base64 of the
+/// nonce followed by the ciphertext, `aad` binds it to where it is stored.
+pub fn encrypt(
+    rng: &SystemRandom,
+    key: &LessSafeKey,
+    aad: &str,
+    plaintext: &[u8],
+) -> Result<String, AeadError> {
+    let mut nonce = [0u8; NONCE_LEN];
+
+    rng.fill(&mut nonce).map_err(|_| AeadError::Encrypt)?;
+
+    let mut in_out = plaintext.to_vec();
+
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(aad.as_bytes()),
+        &mut in_out,
+    )
+    .map_err(|_| AeadError::Encrypt)?;
+
+    Ok(BASE64.encode(&[nonce.as_slice(), &in_out].concat()))
+}
+
+pub fn decrypt(key: &LessSafeKey, aad: &str, ciphertext: &str) -> Result<Vec<u8>, AeadError> {
+    let sealed = BASE64
+        .decode(ciphertext.as_bytes())
+        .map_err(|_| AeadError::Decrypt)?;
+
+    if sealed.len() < NONCE_LEN {
+        return Err(AeadError::Decrypt);
+    }
+
+    let (nonce, sealed) = sealed.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| AeadError::Decrypt)?;
+    let mut in_out = sealed.to_vec();
+    let plaintext = key
+        .open_in_place(nonce, Aad::from(aad.as_bytes()), &mut in_out)
+        .map_err(|_| AeadError::Decrypt)?;
+
+    Ok(plaintext.to_vec())
+}
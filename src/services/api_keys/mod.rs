@@ -0,0 +1,220 @@
+use std::sync::Arc;
+
+use chrono::NaiveDateTime;
+use data_encoding::HEXLOWER;
+use diesel::{insert_into, prelude::*, update};
+use rand::RngCore;
+use ring::digest;
+use uuid::Uuid;
+
+use super::{
+    dto::api_keys::{IssuedApiKey, ServiceAccountWithKeys},
+    permissions::Permission,
+};
+use crate::db::{
+    models::{api_keys::ApiKey, service_accounts::ServiceAccount},
+    orm::schema::{api_keys, service_accounts},
+    Db, DbError, DbProvider,
+};
+
+const KEY_PREFIX: &str = "sdb_";
+const LAST_USED_PRECISION_MINUTES: i64 = 1;
+
+#[derive(Debug)]
+pub enum ApiKeyServiceError {
+    AlreadyExists,
+    NotFound,
+    InvalidScope,
+    Creation,
+    Query,
+    Update,
+}
+
+/// The service account a request was authenticated for with an API key
+#[derive(Clone, Debug)]
+pub struct ApiKeyPrincipal {
+    pub key_uid: Uuid,
+    pub service_account_uid: Uuid,
+    pub scopes: Vec<Permission>,
+}
+
+/// Long-lived keys of integrations. Only a hash of the key is stored, the
+/// key itself is shown once on creation.
+pub struct ApiKeyService {
+    db: Arc<Db>,
+}
+
+impl ApiKeyService {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+
+    pub fn create_service_account(
+        &self,
+        name: &str,
+    ) -> Result<ServiceAccount, DbError<ApiKeyServiceError>> {
+        self.db.apply(|conn| {
+            insert_into(service_accounts::dsl::service_accounts)
+                .values(service_accounts::dsl::name.eq(name))
+                .get_result(conn)
+                .map_err(|err| match err {
+                    diesel::result::Error::DatabaseError(
+                        diesel::result::DatabaseErrorKind::UniqueViolation,
+                        _,
+                    ) => ApiKeyServiceError::AlreadyExists,
+                    _ => ApiKeyServiceError::Creation,
+                })
+        })
+    }
+
+    pub fn get_service_accounts(
+        &self,
+    ) -> Result<Vec<ServiceAccountWithKeys>, DbError<ApiKeyServiceError>> {
+        self.db.apply(|conn| {
+            let accounts = service_accounts::table
+                .order(service_accounts::dsl::name)
+                .load::<ServiceAccount>(conn)
+                .map_err(|_| ApiKeyServiceError::Query)?;
+            let keys = ApiKey::belonging_to(&accounts)
+                .order(api_keys::dsl::created_at.desc())
+                .load::<ApiKey>(conn)
+                .map_err(|_| ApiKeyServiceError::Query)?
+                .grouped_by(&accounts);
+
+            Ok(accounts
+                .into_iter()
+                .zip(keys)
+                .map(|(account, keys)| ServiceAccountWithKeys { account, keys })
+                .collect())
+        })
+    }
+
+    pub fn issue_key(
+        &self,
+        service_account_uid: &Uuid,
+        name: &str,
+        scopes: &[String],
+        expires_at: Option<NaiveDateTime>,
+        created_by: &Uuid,
+    ) -> Result<IssuedApiKey, DbError<ApiKeyServiceError>> {
+        if scopes.iter().any(|scope| scope.parse::<Permission>().is_err()) {
+            return Err(DbError::Execution(ApiKeyServiceError::InvalidScope));
+        }
+
+        let mut secret = [0_u8; 32];
+
+        rand::thread_rng().fill_bytes(&mut secret);
+
+        let key = format!("{}{}", KEY_PREFIX, HEXLOWER.encode(&secret));
+        let prefix = key[..KEY_PREFIX.len() + 8].to_owned();
+
+        self.db.apply(|conn| {
+            let exists = diesel::select(diesel::dsl::exists(
+                service_accounts::dsl::service_accounts.find(service_account_uid),
+            ))
+            .get_result::<bool>(conn)
+            .map_err(|_| ApiKeyServiceError::Query)?;
+
+            if !exists {
+                return Err(ApiKeyServiceError::NotFound);
+            }
+
+            let api_key: ApiKey = insert_into(api_keys::dsl::api_keys)
+                .values((
+                    api_keys::dsl::service_account_uid.eq(service_account_uid),
+                    api_keys::dsl::name.eq(name),
+                    api_keys::dsl::prefix.eq(&prefix),
+                    api_keys::dsl::key_hash.eq(Self::hash_key(&key)),
+                    api_keys::dsl::scopes.eq(scopes),
+                    api_keys::dsl::created_by.eq(created_by),
+                    api_keys::dsl::expires_at.eq(expires_at),
+                ))
+                .get_result(conn)
+                .map_err(|_| ApiKeyServiceError::Creation)?;
+
+            Ok(IssuedApiKey {
+                api_key,
+                key: key.clone(),
+            })
+        })
+    }
+
+    pub fn revoke_key(
+        &self,
+        service_account_uid: &Uuid,
+        key_uid: &Uuid,
+    ) -> Result<(), DbError<ApiKeyServiceError>> {
+        self.db.apply(|conn| {
+            let revoked = update(api_keys::dsl::api_keys.find(key_uid))
+                .filter(api_keys::dsl::service_account_uid.eq(service_account_uid))
+                .filter(api_keys::dsl::revoked_at.is_null())
+                .set(api_keys::dsl::revoked_at.eq(chrono::Utc::now().naive_utc()))
+                .execute(conn)
+                .map_err(|_| ApiKeyServiceError::Update)?;
+
+            if revoked == 0 {
+                return Err(ApiKeyServiceError::NotFound);
+            }
+
+            Ok(())
+        })
+    }
+
+    /// `None` for unknown, revoked and expired keys. Last use is recorded
+    /// with a minute precision to keep writes off the hot path.
+    pub fn authenticate(
+        &self,
+        key: &str,
+    ) -> Result<Option<ApiKeyPrincipal>, DbError<ApiKeyServiceError>> {
+        if !key.starts_with(KEY_PREFIX) {
+            return Ok(None);
+        }
+
+        let now = chrono::Utc::now().naive_utc();
+
+        self.db.apply(|conn| {
+            let api_key = api_keys::table
+                .filter(api_keys::dsl::key_hash.eq(Self::hash_key(key)))
+                .filter(api_keys::dsl::revoked_at.is_null())
+                .filter(
+                    api_keys::dsl::expires_at
+                        .is_null()
+                        .or(api_keys::dsl::expires_at.gt(now)),
+                )
+                .first::<ApiKey>(conn)
+                .optional()
+                .map_err(|_| ApiKeyServiceError::Query)?;
+
+            let api_key = match api_key {
+                Some(api_key) => api_key,
+                None => return Ok(None),
+            };
+
+            let last_used_before = now - chrono::Duration::minutes(LAST_USED_PRECISION_MINUTES);
+
+            if api_key
+                .last_used_at
+                .is_none_or(|last_used_at| last_used_at < last_used_before)
+            {
+                update(api_keys::dsl::api_keys.find(api_key.uid))
+                    .set(api_keys::dsl::last_used_at.eq(now))
+                    .execute(conn)
+                    .map_err(|_| ApiKeyServiceError::Update)?;
+            }
+
+            Ok(Some(ApiKeyPrincipal {
+                key_uid: api_key.uid,
+                service_account_uid: api_key.service_account_uid,
+                scopes: api_key
+                    .scopes
+                    .iter()
+                    .filter_map(|scope| scope.parse().ok())
+                    .collect(),
+            }))
+        })
+    }
+
+    fn hash_key(key: &str) -> String {
+        HEXLOWER.encode(digest::digest(&digest::SHA256, key.as_bytes()).as_ref())
+    }
+}
@@ -0,0 +1,337 @@
+use std::sync::Arc;
+
+use diesel::{insert_into, prelude::*};
+use uuid::Uuid;
+
+use super::{dto::audit::AuditEventsQuery, pagination::Pagination};
+use crate::db::{
+    models::audit_events::AuditEvent, orm::schema::audit_events, Db, DbError, DbProvider,
+};
+
+#[derive(Debug)]
+pub enum AuditServiceError {
+    Insert,
+    InvalidPage,
+    Query,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum AuditActor {
+    Anonymous,
+    /// `auth_data` uid of a person
+    User(Uuid),
+    ServiceAccount(Uuid),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+    Denied,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum AuditAction {
+    Register,
+    Login,
+    LoginMfa,
+    LoginOidc,
+    Logout,
+    RefreshTokenReuse,
+    MfaEnable,
+    MfaDisable,
+    MfaRecoveryCodes,
+    PasswordResetRequest,
+    PasswordReset,
+    EmailVerify,
+    EmailVerificationResend,
+    PasswordChange,
+    UsernameChange,
+    EmailChange,
+    ProfileUpdate,
+    SessionRevoke,
+    OtherSessionsRevoke,
+    UserForceLogout,
+    UserUnlock,
+    UserRoleChange,
+    UserBlock,
+    UserUnblock,
+    UserDelete,
+    LawsDelete,
+    InvoiceCreate,
+    InvoiceVoid,
+    InvoiceExport,
+    PaymentRecord,
+    PaymentCharge,
+    LawApplicationCreate,
+    LawApplicationApprove,
+    LawApplicationReject,
+    LawTransactionCreate,
+    LawTransactionAccept,
+    LawTransactionDecline,
+    LawTransactionComplete,
+    LawTransactionCancel,
+    ServiceCreate,
+    ServiceUpdate,
+    ServiceArchive,
+    ServiceAccountCreate,
+    ApiKeyIssue,
+    ApiKeyRevoke,
+    PermissionDenied,
+    PassportView,
+    PassportSearch,
+    PassportUpdate,
+    PassportVerify,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum AuditTarget {
+    /// `auth_data` uid
+    User(Uuid),
+    Session(Uuid),
+    Law(Uuid),
+    Invoice(Uuid),
+    LawApplication(Uuid),
+    LawTransaction(Uuid),
+    Service(Uuid),
+    ServiceAccount(Uuid),
+    ApiKey(Uuid),
+    Passport(Uuid),
+}
+
+impl AuditActor {
+    fn kind(self) -> &'static str {
+        match self {
+            AuditActor::Anonymous => "anonymous",
+            AuditActor::User(_) => "user",
+            AuditActor::ServiceAccount(_) => "service",
+        }
+    }
+
+    fn uid(self) -> Option<Uuid> {
+        match self {
+            AuditActor::Anonymous => None,
+            AuditActor::User(uid) | AuditActor::ServiceAccount(uid) => Some(uid),
+        }
+    }
+}
+
+impl AuditOutcome {
+    fn as_str(self) -> &'static str {
+        match self {
+            AuditOutcome::Success => "success",
+            AuditOutcome::Failure => "failure",
+            AuditOutcome::Denied => "denied",
+        }
+    }
+}
+
+impl AuditAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            AuditAction::Register => "auth.register",
+            AuditAction::Login => "auth.login",
+            AuditAction::LoginMfa => "auth.login_mfa",
+            AuditAction::LoginOidc => "auth.login_oidc",
+            AuditAction::Logout => "auth.logout",
+            AuditAction::RefreshTokenReuse => "auth.refresh_reuse",
+            AuditAction::MfaEnable => "mfa.enable",
+            AuditAction::MfaDisable => "mfa.disable",
+            AuditAction::MfaRecoveryCodes => "mfa.recovery_codes",
+            AuditAction::PasswordResetRequest => "password.reset_request",
+            AuditAction::PasswordReset => "password.reset",
+            AuditAction::EmailVerify => "email.verify",
+            AuditAction::EmailVerificationResend => "email.verification_resend",
+            AuditAction::PasswordChange => "account.password_change",
+            AuditAction::UsernameChange => "account.username_change",
+            AuditAction::EmailChange => "account.email_change",
+            AuditAction::ProfileUpdate => "account.profile_update",
+            AuditAction::SessionRevoke => "sessions.revoke",
+            AuditAction::OtherSessionsRevoke => "sessions.revoke_others",
+            AuditAction::UserForceLogout => "users.force_logout",
+            AuditAction::UserUnlock => "users.unlock",
+            AuditAction::UserRoleChange => "users.role_change",
+            AuditAction::UserBlock => "users.block",
+            AuditAction::UserUnblock => "users.unblock",
+            AuditAction::UserDelete => "users.delete",
+            AuditAction::LawsDelete => "laws.delete",
+            AuditAction::InvoiceCreate => "invoices.create",
+            AuditAction::InvoiceVoid => "invoices.void",
+            AuditAction::InvoiceExport => "invoices.export",
+            AuditAction::PaymentRecord => "payments.record",
+            AuditAction::PaymentCharge => "payments.charge",
+            AuditAction::LawApplicationCreate => "law_applications.create",
+            AuditAction::LawApplicationApprove => "law_applications.approve",
+            AuditAction::LawApplicationReject => "law_applications.reject",
+            AuditAction::LawTransactionCreate => "law_transactions.create",
+            AuditAction::LawTransactionAccept => "law_transactions.accept",
+            AuditAction::LawTransactionDecline => "law_transactions.decline",
+            AuditAction::LawTransactionComplete => "law_transactions.complete",
+            AuditAction::LawTransactionCancel => "law_transactions.cancel",
+            AuditAction::ServiceCreate => "services.create",
+            AuditAction::ServiceUpdate => "services.update",
+            AuditAction::ServiceArchive => "services.archive",
+            AuditAction::ServiceAccountCreate => "service_accounts.create",
+            AuditAction::ApiKeyIssue => "api_keys.issue",
+            AuditAction::ApiKeyRevoke => "api_keys.revoke",
+            AuditAction::PermissionDenied => "permissions.denied",
+            AuditAction::PassportView => "passports.view",
+            AuditAction::PassportSearch => "passports.search",
+            AuditAction::PassportUpdate => "passports.update",
+            AuditAction::PassportVerify => "passports.verify",
+        }
+    }
+}
+
+impl AuditTarget {
+    fn kind(self) -> &'static str {
+        match self {
+            AuditTarget::User(_) => "user",
+            AuditTarget::Session(_) => "session",
+            AuditTarget::Law(_) => "law",
+            AuditTarget::Invoice(_) => "invoice",
+            AuditTarget::LawApplication(_) => "law_application",
+            AuditTarget::LawTransaction(_) => "law_transaction",
+            AuditTarget::Service(_) => "service",
+            AuditTarget::ServiceAccount(_) => "service_account",
+            AuditTarget::ApiKey(_) => "api_key",
+            AuditTarget::Passport(_) => "passport",
+        }
+    }
+
+    fn uid(self) -> Uuid {
+        match self {
+            AuditTarget::User(uid)
+            | AuditTarget::Session(uid)
+            | AuditTarget::Law(uid)
+            | AuditTarget::Invoice(uid)
+            | AuditTarget::LawApplication(uid)
+            | AuditTarget::LawTransaction(uid)
+            | AuditTarget::Service(uid)
+            | AuditTarget::ServiceAccount(uid)
+            | AuditTarget::ApiKey(uid)
+            | AuditTarget::Passport(uid) => uid,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct AuditRecord {
+    pub actor: AuditActor,
+    pub action: AuditAction,
+    pub outcome: AuditOutcome,
+    pub target: Option<AuditTarget>,
+    pub ip: Option<String>,
+    pub details: Option<String>,
+}
+
+impl AuditRecord {
+    pub fn new(actor: AuditActor, action: AuditAction, outcome: AuditOutcome) -> Self {
+        Self {
+            actor,
+            action,
+            outcome,
+            target: None,
+            ip: None,
+            details: None,
+        }
+    }
+
+    /// Event of a user acting on their own account
+    pub fn own_account(uid: Uuid, action: AuditAction, outcome: AuditOutcome) -> Self {
+        Self::new(AuditActor::User(uid), action, outcome).target(AuditTarget::User(uid))
+    }
+
+    pub fn target(mut self, target: AuditTarget) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    pub fn details(mut self, details: impl Into<String>) -> Self {
+        self.details = Some(details.into());
+        self
+    }
+}
+
+/// Append-only trail of security relevant actions, the table rejects updates
+/// and deletes by itself.
+pub struct AuditService {
+    db: Arc<Db>,
+}
+
+impl AuditService {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+
+    pub fn record(&self, record: &AuditRecord) -> Result<(), DbError<AuditServiceError>> {
+        let details = record
+            .details
+            .as_deref()
+            .map(|details| details.chars().take(255).collect::<String>());
+
+        self.db.apply(|conn| {
+            insert_into(audit_events::dsl::audit_events)
+                .values((
+                    audit_events::dsl::actor_kind.eq(record.actor.kind()),
+                    audit_events::dsl::actor_uid.eq(record.actor.uid()),
+                    audit_events::dsl::action.eq(record.action.as_str()),
+                    audit_events::dsl::target_kind.eq(record.target.map(AuditTarget::kind)),
+                    audit_events::dsl::target_uid.eq(record.target.map(AuditTarget::uid)),
+                    audit_events::dsl::ip.eq(&record.ip),
+                    audit_events::dsl::outcome.eq(record.outcome.as_str()),
+                    audit_events::dsl::details.eq(&details),
+                ))
+                .execute(conn)
+                .map_err(|err| {
+                    log::error!("{}", err);
+                    AuditServiceError::Insert
+                })?;
+
+            Ok(())
+        })
+    }
+
+    pub fn get_events(
+        &self,
+        filters: &AuditEventsQuery,
+    ) -> Result<Vec<AuditEvent>, DbError<AuditServiceError>> {
+        let pagination = Pagination::new(filters.page, filters.per_page)
+            .ok_or(DbError::Execution(AuditServiceError::InvalidPage))?;
+
+        self.db.apply(|conn| {
+            let mut query = audit_events::table.into_boxed();
+
+            if let Some(actor_uid) = filters.actor_uid {
+                query = query.filter(audit_events::dsl::actor_uid.eq(actor_uid));
+            }
+
+            if let Some(action) = &filters.action {
+                query = query.filter(audit_events::dsl::action.eq(action));
+            }
+
+            if let Some(target_uid) = filters.target_uid {
+                query = query.filter(audit_events::dsl::target_uid.eq(target_uid));
+            }
+
+            if let Some(outcome) = &filters.outcome {
+                query = query.filter(audit_events::dsl::outcome.eq(outcome));
+            }
+
+            if let Some(from) = filters.from {
+                query = query.filter(audit_events::dsl::created_at.ge(from));
+            }
+
+            if let Some(to) = filters.to {
+                query = query.filter(audit_events::dsl::created_at.lt(to));
+            }
+
+            query
+                .order(audit_events::dsl::created_at.desc())
+                .offset(pagination.offset)
+                .limit(pagination.per_page)
+                .load(conn)
+                .map_err(|_| AuditServiceError::Query)
+        })
+    }
+}
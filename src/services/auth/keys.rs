@@ -0,0 +1,244 @@
+use std::fs;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+        OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    },
+    Algorithm, DecodingKey, EncodingKey, Header,
+};
+use simple_asn1::{oid, ASN1Block};
+
+#[derive(Debug)]
+pub enum JwtKeysError {
+    Read,
+    InvalidKey,
+    UnsupportedAlgorithm,
+    SigningKeyNotPublished,
+    Sign,
+}
+
+struct VerificationKey {
+    kid: Option<String>,
+    algorithm: Algorithm,
+    key: DecodingKey,
+    jwk: Option<Jwk>,
+}
+
+/// Keys used to sign and verify access tokens. Only asymmetric keys are
+/// published in the JWK set, an HMAC secret never leaves the server.
+pub struct JwtKeys {
+    signing_kid: Option<String>,
+    algorithm: Algorithm,
+    encoding_key: EncodingKey,
+    verification_keys: Vec<VerificationKey>,
+}
+
+impl JwtKeys {
+    pub fn from_secret(secret: &[u8]) -> Self {
+        Self {
+            signing_kid: None,
+            algorithm: Algorithm::HS256,
+            encoding_key: EncodingKey::from_secret(secret),
+            verification_keys: vec![VerificationKey {
+                kid: None,
+                algorithm: Algorithm::HS256,
+                key: DecodingKey::from_secret(secret),
+                jwk: None,
+            }],
+        }
+    }
+
+    /// `public_keys` are `(kid, path)` pairs of SPKI PEM files. Retired keys
+    /// stay there while tokens signed by them are in use, and the public part
+    /// of the signing key must be among them.
+    pub fn from_pem_files(
+        algorithm: Algorithm,
+        signing_kid: &str,
+        private_key_path: &str,
+        public_keys: &[(String, String)],
+    ) -> Result<Self, JwtKeysError> {
+        let private_key = Self::read(private_key_path)?;
+        let encoding_key = match algorithm {
+            Algorithm::RS256 => EncodingKey::from_rsa_pem(&private_key),
+            Algorithm::EdDSA => EncodingKey::from_ed_pem(&private_key),
+            _ => return Err(JwtKeysError::UnsupportedAlgorithm),
+        }
+        .map_err(|err| {
+            log::error!("{}: {}", private_key_path, err);
+            JwtKeysError::InvalidKey
+        })?;
+
+        let verification_keys = public_keys
+            .iter()
+            .map(|(kid, path)| Self::read_public_key(kid, path))
+            .collect::<Result<Vec<VerificationKey>, JwtKeysError>>()?;
+
+        let signing_key_published = verification_keys.iter().any(|key| {
+            key.kid.as_deref() == Some(signing_kid) && key.algorithm == algorithm
+        });
+
+        if !signing_key_published {
+            return Err(JwtKeysError::SigningKeyNotPublished);
+        }
+
+        Ok(Self {
+            signing_kid: Some(signing_kid.to_owned()),
+            algorithm,
+            encoding_key,
+            verification_keys,
+        })
+    }
+
+    pub fn header(&self) -> Header {
+        let mut header = Header::new(self.algorithm);
+
+        header.kid = self.signing_kid.clone();
+
+        header
+    }
+
+    pub fn encoding_key(&self) -> &EncodingKey {
+        &self.encoding_key
+    }
+
+    pub fn signing_kid(&self) -> Option<&str> {
+        self.signing_kid.as_deref()
+    }
+
+    /// Detached signature of `message` with the current signing key
+    pub fn sign(&self, message: &[u8]) -> Result<String, JwtKeysError> {
+        jsonwebtoken::crypto::sign(message, &self.encoding_key, self.algorithm).map_err(|err| {
+            log::error!("{}", err);
+            JwtKeysError::Sign
+        })
+    }
+
+    /// Checks a signature made by `sign`, retired keys are accepted as long
+    /// as they are configured
+    pub fn verify(&self, kid: Option<&str>, signature: &str, message: &[u8]) -> bool {
+        self.decoding_key(kid)
+            .map(|(key, algorithm)| {
+                jsonwebtoken::crypto::verify(signature, message, key, algorithm).unwrap_or(false)
+            })
+            .unwrap_or(false)
+    }
+
+    pub fn decoding_key(&self, kid: Option<&str>) -> Option<(&DecodingKey, Algorithm)> {
+        self.verification_keys
+            .iter()
+            .find(|key| key.kid.as_deref() == kid)
+            .map(|key| (&key.key, key.algorithm))
+    }
+
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self
+                .verification_keys
+                .iter()
+                .filter_map(|key| key.jwk.clone())
+                .collect(),
+        }
+    }
+
+    fn read(path: &str) -> Result<Vec<u8>, JwtKeysError> {
+        fs::read(path).map_err(|err| {
+            log::error!("{}: {}", path, err);
+            JwtKeysError::Read
+        })
+    }
+
+    fn read_public_key(kid: &str, path: &str) -> Result<VerificationKey, JwtKeysError> {
+        let invalid_key = || {
+            log::error!("{}: not a supported SPKI public key", path);
+            JwtKeysError::InvalidKey
+        };
+        let pem = pem::parse(Self::read(path)?).map_err(|_| invalid_key())?;
+
+        if pem.tag() != "PUBLIC KEY" {
+            return Err(invalid_key());
+        }
+
+        // SubjectPublicKeyInfo: SEQUENCE { SEQUENCE { OID, ... }, BIT STRING }
+        let (key_oid, public_key) = match simple_asn1::from_der(pem.contents())
+            .map_err(|_| invalid_key())?
+            .as_slice()
+        {
+            [ASN1Block::Sequence(_, spki)] => match spki.as_slice() {
+                [ASN1Block::Sequence(_, algorithm), ASN1Block::BitString(_, _, public_key)] => {
+                    match algorithm.first() {
+                        Some(ASN1Block::ObjectIdentifier(_, key_oid)) => {
+                            (key_oid.clone(), public_key.clone())
+                        }
+                        _ => return Err(invalid_key()),
+                    }
+                }
+                _ => return Err(invalid_key()),
+            },
+            _ => return Err(invalid_key()),
+        };
+
+        let common = CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_id: Some(kid.to_owned()),
+            ..Default::default()
+        };
+
+        if key_oid == oid!(1, 2, 840, 113549, 1, 1, 1) {
+            // RSAPublicKey: SEQUENCE { INTEGER modulus, INTEGER exponent }
+            let (n, e) = match simple_asn1::from_der(&public_key)
+                .map_err(|_| invalid_key())?
+                .as_slice()
+            {
+                [ASN1Block::Sequence(_, components)] => match components.as_slice() {
+                    [ASN1Block::Integer(_, n), ASN1Block::Integer(_, e)] => {
+                        (n.to_bytes_be().1, e.to_bytes_be().1)
+                    }
+                    _ => return Err(invalid_key()),
+                },
+                _ => return Err(invalid_key()),
+            };
+
+            return Ok(VerificationKey {
+                kid: Some(kid.to_owned()),
+                algorithm: Algorithm::RS256,
+                key: DecodingKey::from_rsa_raw_components(&n, &e),
+                jwk: Some(Jwk {
+                    common: CommonParameters {
+                        key_algorithm: Some(KeyAlgorithm::RS256),
+                        ..common
+                    },
+                    algorithm: AlgorithmParameters::RSA(RSAKeyParameters {
+                        key_type: RSAKeyType::RSA,
+                        n: URL_SAFE_NO_PAD.encode(n),
+                        e: URL_SAFE_NO_PAD.encode(e),
+                    }),
+                }),
+            });
+        }
+
+        if key_oid == oid!(1, 3, 101, 112) {
+            let x = URL_SAFE_NO_PAD.encode(&public_key);
+
+            return Ok(VerificationKey {
+                kid: Some(kid.to_owned()),
+                algorithm: Algorithm::EdDSA,
+                key: DecodingKey::from_ed_components(&x).map_err(|_| invalid_key())?,
+                jwk: Some(Jwk {
+                    common: CommonParameters {
+                        key_algorithm: Some(KeyAlgorithm::EdDSA),
+                        ..common
+                    },
+                    algorithm: AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                        key_type: OctetKeyPairType::OctetKeyPair,
+                        curve: EllipticCurve::Ed25519,
+                        x,
+                    }),
+                }),
+            });
+        }
+
+        Err(JwtKeysError::UnsupportedAlgorithm)
+    }
+}
@@ -1,20 +1,33 @@
 use std::sync::Arc;
 
-use crate::db::models;
-use crate::db::{orm::schema::auth_data, Db, DbError, DbProvider};
+use crate::db::models::{self, custom_types::user_profiles_roles::UserProfilesRoles};
+use crate::db::{
+    orm::schema::{auth_data, external_identities, user_profiles},
+    Db, DbError, DbProvider,
+};
 use argon2::{self, Config};
+use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine};
 use diesel::insert_into;
 use diesel::prelude::*;
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use rand::RngCore;
+use jsonwebtoken::{decode, decode_header, encode, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use self::keys::JwtKeys;
 use super::dto::auth::AuthorizationDto;
+use super::mfa::MfaPolicyProvider;
+use super::oidc::{OidcIdentity, OidcSettings};
 use super::{
-    dto::{auth::RegistrationDto, user::PassportOrmData},
+    dto::{auth::RegistrationDto, passport::PassportSecrets, user::PassportOrmData},
+    passports::PassportKeysProvider,
     user::{UserService, UserServiceError},
 };
 
+pub mod keys;
+
+const SALT_LENGTH: usize = 16;
+
 #[derive(Debug)]
 pub enum AuthServiceError<T> {
     AuthDataCreation(T),
@@ -30,6 +43,13 @@ pub enum AuthServiceError<T> {
     AlreadyExists,
     InvalidToken,
     TokenExpired,
+    AccountLocked,
+    TooManyAttempts,
+    LoginThrottle,
+    ExternalAccount,
+    ExternalIdentityLink,
+    ExternalLinkNotAllowed,
+    AccountBlocked,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -37,25 +57,73 @@ pub struct JwtAccessData {
     pub uid: Uuid,
     pub sub: String,
     pub username: String,
-    pub role: String,
+    pub role: UserProfilesRoles,
+    /// Law transactions may only be created once the email is verified
+    #[serde(default)]
+    pub email_verified: bool,
+    pub sid: Uuid,
+    pub jti: Uuid,
+    pub iat: usize,
+    /// `iat` in milliseconds, a force logout in the same second as a login
+    /// must not deny the new token
+    #[serde(default)]
+    pub iat_ms: Option<usize>,
     pub exp: usize,
 }
 
+impl JwtAccessData {
+    /// Tokens issued before `iat_ms` was added only carry seconds
+    pub fn issued_at_ms(&self) -> usize {
+        self.iat_ms.unwrap_or(self.iat * 1000)
+    }
+}
+
 #[derive(Serialize, Deserialize)]
-struct JwtRefreshData {
-    uid: Uuid,
-    exp: usize,
+pub struct JwtRefreshData {
+    pub uid: Uuid,
+    pub family: Uuid,
+    pub exp: usize,
 }
 
-pub trait SaltProvider {
-    fn salt(&self) -> &[u8];
+pub enum AuthorizationResult {
+    Authorized(TokensData),
+    MfaRequired { uid: Uuid, enrollment: bool },
+}
+
+pub struct TokensData {
+    pub uid: Uuid,
+    pub access_token: String,
+    pub refresh_token: String,
+    pub exp: usize,
+    pub refresh_exp: usize,
+    pub family: Uuid,
+}
+
+pub trait PasswordHashProvider {
+    fn argon2_config(&self) -> Config<'_>;
+
+    /// The salt every password used to be hashed with before per-user salts
+    fn legacy_salt(&self) -> &[u8];
 }
 
 pub trait SecretsProvider {
-    fn access_secret(&self) -> &[u8];
+    fn access_keys(&self) -> &JwtKeys;
     fn refresh_secret(&self) -> &[u8];
 }
 
+#[derive(Debug)]
+pub enum LoginAttemptsError {
+    Locked,
+    Backoff,
+    Storage,
+}
+
+pub trait LoginAttemptsGuard {
+    fn check(&self, uid: &Uuid) -> Result<(), LoginAttemptsError>;
+    fn failed(&self, uid: &Uuid);
+    fn succeeded(&self, uid: &Uuid);
+}
+
 pub struct AuthService {
     db: Arc<Db>,
 }
@@ -69,30 +137,56 @@ impl AuthService {
         &self,
         dto: AuthorizationDto,
         config: &T,
-    ) -> Result<(String, String, usize, usize), DbError<AuthServiceError<()>>>
+        attempts_guard: &impl LoginAttemptsGuard,
+    ) -> Result<AuthorizationResult, DbError<AuthServiceError<()>>>
     where
-        T: SaltProvider + SecretsProvider,
+        T: PasswordHashProvider + SecretsProvider + MfaPolicyProvider,
     {
         self.db.apply(move |conn| {
             let data = AuthService::find_by_email_or_username(conn, &dto.email_or_username)
                 .map_err(|_| AuthServiceError::UserNotFound)?;
-            let hashed_password = Self::verify_password(dto.password.as_bytes(), &data.password)
+
+            Self::check_attempts(attempts_guard, &data.uid)?;
+
+            let password_hash = data
+                .password
+                .as_deref()
+                .ok_or(AuthServiceError::ExternalAccount)?;
+            let hashed_password = Self::verify_password(dto.password.as_bytes(), password_hash)
                 .map_err(|_| AuthServiceError::PasswordVerify)?;
 
-            let user = UserService::find_user_by_pk(conn, &data.profile_uid)
-                .map_err(|_| AuthServiceError::UserNotFound)?;
+            if hashed_password && Self::needs_rehash(password_hash, config) {
+                Self::rehash_password(conn, &data.uid, dto.password.as_bytes(), config);
+            }
 
             if !hashed_password {
+                attempts_guard.failed(&data.uid);
+
                 return Err(AuthServiceError::InvalidPassword);
             }
 
+            let user = Self::find_active_user(conn, &data.profile_uid)?;
+
+            // Failures are only reset once the second factor is passed as well
+            if data.totp_enabled || config.mfa_required_for(user.role) {
+                return Ok(AuthorizationResult::MfaRequired {
+                    uid: data.uid,
+                    enrollment: !data.totp_enabled,
+                });
+            }
+
+            attempts_guard.succeeded(&data.uid);
+
             Self::generate_tokens(
                 data.uid,
                 &data.username,
                 &data.email,
-                user.role.into(),
+                user.role,
+                data.email_verified,
+                Uuid::new_v4(),
                 config,
             )
+            .map(AuthorizationResult::Authorized)
             .map_err(|err| match err {
                 AuthServiceError::AccessTokenGeneration => AuthServiceError::AccessTokenGeneration,
                 AuthServiceError::RefreshTokenGeneration => {
@@ -103,22 +197,113 @@ impl AuthService {
         })
     }
 
+    /// Issues tokens for a new session of a user who passed the second factor
+    pub fn issue_tokens(
+        &self,
+        uid: &Uuid,
+        secrets_provider: &impl SecretsProvider,
+    ) -> Result<TokensData, DbError<AuthServiceError<()>>> {
+        self.db.apply(move |conn| {
+            let data = AuthService::find_by_pk(conn, uid)?;
+            let user = Self::find_active_user(conn, &data.profile_uid)?;
+
+            Self::generate_tokens(
+                data.uid,
+                &data.username,
+                &data.email,
+                user.role,
+                data.email_verified,
+                Uuid::new_v4(),
+                secrets_provider,
+            )
+        })
+    }
+
+    /// Logs in a user vouched for by the identity provider. On the first login
+    /// the identity is linked to the staff account with the same verified
+    /// email, or a new account without a password is created for it. Locked
+    /// accounts and the second factor are handled the same as for passwords.
+    pub fn authorize_external_user<T>(
+        &self,
+        identity: &OidcIdentity,
+        config: &T,
+        attempts_guard: &impl LoginAttemptsGuard,
+    ) -> Result<AuthorizationResult, DbError<AuthServiceError<()>>>
+    where
+        T: SecretsProvider + MfaPolicyProvider,
+    {
+        self.db.transaction(move |conn| {
+            let linked_uid = external_identities::dsl::external_identities
+                .filter(external_identities::dsl::issuer.eq(&identity.issuer))
+                .filter(external_identities::dsl::subject.eq(&identity.subject))
+                .select(external_identities::dsl::auth_uid)
+                .first::<Uuid>(conn)
+                .optional()
+                .map_err(|_| AuthServiceError::ExternalIdentityLink)?;
+
+            let uid = match linked_uid {
+                Some(uid) => uid,
+                None => Self::link_external_identity(conn, identity)?,
+            };
+
+            let data = AuthService::find_by_pk(conn, &uid)?;
+
+            Self::check_attempts(attempts_guard, &data.uid)?;
+
+            let user = Self::find_active_user(conn, &data.profile_uid)?;
+
+            if data.totp_enabled || config.mfa_required_for(user.role) {
+                return Ok(AuthorizationResult::MfaRequired {
+                    uid: data.uid,
+                    enrollment: !data.totp_enabled,
+                });
+            }
+
+            attempts_guard.succeeded(&data.uid);
+
+            Self::generate_tokens(
+                data.uid,
+                &data.username,
+                &data.email,
+                user.role,
+                data.email_verified,
+                Uuid::new_v4(),
+                config,
+            )
+            .map(AuthorizationResult::Authorized)
+        })
+    }
+
     pub fn register_user<T>(
         &self,
         dto: RegistrationDto,
         config: &T,
-    ) -> Result<(String, String, usize, usize), DbError<AuthServiceError<diesel::result::Error>>>
+    ) -> Result<TokensData, DbError<AuthServiceError<diesel::result::Error>>>
     where
-        T: SaltProvider + SecretsProvider,
+        T: PasswordHashProvider + SecretsProvider + PassportKeysProvider,
     {
         self.db.transaction(move |conn| {
+            let passport_uid = Uuid::new_v4();
+            let sealed = config
+                .passport_keys()
+                .seal(
+                    &passport_uid,
+                    &PassportSecrets {
+                        number: None,
+                        series: None,
+                        registration_place: None,
+                        birthday_date: dto.birth_date,
+                    },
+                )
+                .map_err(|_| AuthServiceError::PassportCreation)?;
             let profile_uid = UserService::create_user(
                 conn,
                 &PassportOrmData {
+                    uid: passport_uid,
                     first_name: &dto.first_name,
                     second_name: &dto.second_name,
                     patronymic: dto.patronymic.as_deref(),
-                    birthday_date: dto.birth_date,
+                    sealed,
                 },
             )
             .map_err(|err| match err {
@@ -139,7 +324,16 @@ impl AuthService {
             let uid =
                 Self::create_auth_data(conn, &dto.username, &password, &dto.email, profile_uid)?;
 
-            Self::generate_tokens(uid, &dto.username, &dto.email, "user", config).map_err(|err| {
+            Self::generate_tokens(
+                uid,
+                &dto.username,
+                &dto.email,
+                UserProfilesRoles::User,
+                false,
+                Uuid::new_v4(),
+                config,
+            )
+            .map_err(|err| {
                 match err {
                     AuthServiceError::AccessTokenGeneration => {
                         AuthServiceError::AccessTokenGeneration
@@ -156,22 +350,23 @@ impl AuthService {
     pub fn refresh_tokens(
         &self,
         user_data: &JwtAccessData,
+        family: Uuid,
         secrets_provider: &impl SecretsProvider,
-    ) -> Result<(String, String, usize, usize), DbError<AuthServiceError<()>>> {
-        let profile_data = self.db.apply(move |conn| {
+    ) -> Result<TokensData, DbError<AuthServiceError<()>>> {
+        let (auth, profile_data) = self.db.apply(move |conn| {
             let auth = AuthService::find_by_pk(conn, &user_data.uid)?;
+            let profile_data = Self::find_active_user(conn, &auth.profile_uid)?;
 
-            UserService::find_user_by_pk(conn, &auth.profile_uid).map_err(|err| match err {
-                UserServiceError::NotFound => AuthServiceError::UserNotFound,
-                _ => AuthServiceError::Unreachable,
-            })
+            Ok((auth, profile_data))
         })?;
 
         AuthService::generate_tokens(
             user_data.uid,
-            &user_data.username,
-            &user_data.sub,
-            profile_data.role.into(),
+            &auth.username,
+            &auth.email,
+            profile_data.role,
+            auth.email_verified,
+            family,
             secrets_provider,
         )
         .map_err(|err| match err {
@@ -185,17 +380,226 @@ impl AuthService {
         })
     }
 
+    pub fn set_password(
+        &self,
+        uid: &Uuid,
+        password: &str,
+        hash_provider: &impl PasswordHashProvider,
+    ) -> Result<(), DbError<AuthServiceError<()>>> {
+        let password = Self::hash_password(password.as_bytes(), hash_provider)
+            .map_err(DbError::Execution)?;
+
+        self.db.apply(move |conn| {
+            if Self::find_by_pk(conn, uid)?.password.is_none() {
+                return Err(AuthServiceError::ExternalAccount);
+            }
+
+            diesel::update(auth_data::dsl::auth_data.find(uid))
+                .set(auth_data::dsl::password.eq(&password))
+                .execute(conn)
+                .map_err(|_| AuthServiceError::HashPassword)?;
+
+            Ok(())
+        })
+    }
+
+    pub fn change_password<T>(
+        &self,
+        uid: &Uuid,
+        current_password: &str,
+        new_password: &str,
+        config: &T,
+        attempts_guard: &impl LoginAttemptsGuard,
+    ) -> Result<(), DbError<AuthServiceError<()>>>
+    where
+        T: PasswordHashProvider,
+    {
+        self.db.apply(move |conn| {
+            Self::check_current_password(conn, uid, current_password, attempts_guard)?;
+
+            let password = Self::hash_password(new_password.as_bytes(), config)?;
+
+            diesel::update(auth_data::dsl::auth_data.find(uid))
+                .set(auth_data::dsl::password.eq(password))
+                .execute(conn)
+                .map_err(|_| AuthServiceError::HashPassword)?;
+
+            Ok(())
+        })
+    }
+
+    pub fn change_username(
+        &self,
+        uid: &Uuid,
+        current_password: &str,
+        username: &str,
+        attempts_guard: &impl LoginAttemptsGuard,
+    ) -> Result<(), DbError<AuthServiceError<()>>> {
+        self.db.apply(move |conn| {
+            Self::check_current_password(conn, uid, current_password, attempts_guard)?;
+
+            diesel::update(auth_data::dsl::auth_data.find(uid))
+                .set(auth_data::dsl::username.eq(username))
+                .execute(conn)
+                .map_err(Self::map_unique_violation)?;
+
+            Ok(())
+        })
+    }
+
+    /// The new address has to be verified again
+    pub fn change_email(
+        &self,
+        uid: &Uuid,
+        current_password: &str,
+        email: &str,
+        attempts_guard: &impl LoginAttemptsGuard,
+    ) -> Result<(), DbError<AuthServiceError<()>>> {
+        self.db.apply(move |conn| {
+            Self::check_current_password(conn, uid, current_password, attempts_guard)?;
+
+            diesel::update(auth_data::dsl::auth_data.find(uid))
+                .set((
+                    auth_data::dsl::email.eq(email),
+                    auth_data::dsl::email_verified.eq(false),
+                ))
+                .execute(conn)
+                .map_err(Self::map_unique_violation)?;
+
+            Ok(())
+        })
+    }
+
+    /// Wrong passwords count as failed logins, so a stolen access token can't
+    /// be used to guess the password.
+    fn check_current_password(
+        conn: &mut PgConnection,
+        uid: &Uuid,
+        password: &str,
+        attempts_guard: &impl LoginAttemptsGuard,
+    ) -> Result<(), AuthServiceError<()>> {
+        let data = Self::find_by_pk(conn, uid)?;
+
+        Self::check_attempts(attempts_guard, uid)?;
+
+        let password_hash = data
+            .password
+            .as_deref()
+            .ok_or(AuthServiceError::ExternalAccount)?;
+
+        if !Self::verify_password(password.as_bytes(), password_hash)? {
+            attempts_guard.failed(uid);
+
+            return Err(AuthServiceError::InvalidPassword);
+        }
+
+        attempts_guard.succeeded(uid);
+
+        Ok(())
+    }
+
+    fn link_external_identity(
+        conn: &mut PgConnection,
+        identity: &OidcIdentity,
+    ) -> Result<Uuid, AuthServiceError<()>> {
+        // An unverified email could belong to someone else
+        let existing = if identity.email_verified {
+            auth_data::table
+                .inner_join(user_profiles::table)
+                .filter(auth_data::dsl::email.eq(&identity.email))
+                .select((auth_data::dsl::uid, user_profiles::dsl::role))
+                .first::<(Uuid, UserProfilesRoles)>(conn)
+                .optional()
+                .map_err(|_| AuthServiceError::ExternalIdentityLink)?
+        } else {
+            None
+        };
+
+        let uid = match existing {
+            // The account keeps its password, the identity is one more way in
+            Some((uid, role)) if OidcSettings::is_allowed_role(role) => uid,
+            // Admins and clients are never handed over to the identity provider
+            Some((uid, role)) => {
+                log::warn!(
+                    "Security event: {} identity {} not linked to {:?} account {}",
+                    identity.issuer,
+                    identity.subject,
+                    role,
+                    uid
+                );
+
+                return Err(AuthServiceError::ExternalLinkNotAllowed);
+            }
+            None => {
+                let username = match &identity.username {
+                    Some(username) if !Self::username_exists(conn, username)? => username,
+                    _ => &identity.email,
+                };
+                let profile_uid = UserService::create_profile(conn, &None, &identity.role)
+                    .map_err(|_| AuthServiceError::ProfileCreation)?;
+
+                insert_into(auth_data::dsl::auth_data)
+                    .values((
+                        auth_data::dsl::username.eq(username),
+                        auth_data::dsl::email.eq(&identity.email),
+                        auth_data::dsl::email_verified.eq(identity.email_verified),
+                        auth_data::dsl::profile_uid.eq(profile_uid),
+                    ))
+                    .returning(auth_data::dsl::uid)
+                    .get_result(conn)
+                    .map_err(Self::map_unique_violation)?
+            }
+        };
+
+        insert_into(external_identities::dsl::external_identities)
+            .values((
+                external_identities::dsl::auth_uid.eq(uid),
+                external_identities::dsl::issuer.eq(&identity.issuer),
+                external_identities::dsl::subject.eq(&identity.subject),
+            ))
+            .execute(conn)
+            .map_err(|_| AuthServiceError::ExternalIdentityLink)?;
+
+        Ok(uid)
+    }
+
+    fn username_exists(
+        conn: &mut PgConnection,
+        username: &str,
+    ) -> Result<bool, AuthServiceError<()>> {
+        diesel::select(diesel::dsl::exists(
+            auth_data::dsl::auth_data.filter(auth_data::dsl::username.eq(username)),
+        ))
+        .get_result(conn)
+        .map_err(|_| AuthServiceError::ExternalIdentityLink)
+    }
+
+    fn check_attempts(
+        attempts_guard: &impl LoginAttemptsGuard,
+        uid: &Uuid,
+    ) -> Result<(), AuthServiceError<()>> {
+        attempts_guard.check(uid).map_err(|err| match err {
+            LoginAttemptsError::Locked => AuthServiceError::AccountLocked,
+            LoginAttemptsError::Backoff => AuthServiceError::TooManyAttempts,
+            LoginAttemptsError::Storage => AuthServiceError::LoginThrottle,
+        })
+    }
+
+    fn map_unique_violation(err: diesel::result::Error) -> AuthServiceError<()> {
+        match err {
+            diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::UniqueViolation,
+                _,
+            ) => AuthServiceError::AlreadyExists,
+            _ => AuthServiceError::Unreachable,
+        }
+    }
+
     pub fn validate_token(
         access_token: &str,
         secrets_provider: &impl SecretsProvider,
     ) -> Result<JwtAccessData, AuthServiceError<()>> {
-        decode::<JwtAccessData>(
-            access_token,
-            &DecodingKey::from_secret(secrets_provider.access_secret()),
-            &Validation::default(),
-        )
-        .map(|jwt| jwt.claims)
-        .map_err(|err| {
+        Self::decode_access_token(access_token, secrets_provider, true).map_err(|err| {
             log::error!("{}", err);
 
             match err.kind() {
@@ -226,33 +630,93 @@ impl AuthService {
 
     fn hash_password(
         password: &[u8],
-        salt_provider: &impl SaltProvider,
+        hash_provider: &impl PasswordHashProvider,
     ) -> Result<String, AuthServiceError<()>> {
-        argon2::hash_encoded(password, salt_provider.salt(), &Config::rfc9106_low_mem())
+        let mut salt = [0_u8; SALT_LENGTH];
+
+        rand::thread_rng().fill_bytes(&mut salt);
+
+        argon2::hash_encoded(password, &salt, &hash_provider.argon2_config())
             .map_err(|_| AuthServiceError::HashPassword)
     }
 
+    /// Checks whether the encoded hash (`$argon2id$v=19$m=..,t=..,p=..$salt$hash`)
+    /// was produced with other parameters than the configured ones or with
+    /// the legacy global salt.
+    fn needs_rehash(encoded: &str, hash_provider: &impl PasswordHashProvider) -> bool {
+        let config = hash_provider.argon2_config();
+        let expected_params = format!(
+            "m={},t={},p={}",
+            config.mem_cost, config.time_cost, config.lanes
+        );
+        let legacy_salt = STANDARD_NO_PAD.encode(hash_provider.legacy_salt());
+
+        match encoded.split('$').collect::<Vec<&str>>().as_slice() {
+            ["", variant, version, params, salt, hash] => {
+                salt.is_empty()
+                    || hash.is_empty()
+                    || *variant != config.variant.as_lowercase_str()
+                    || *version != format!("v={}", config.version.as_u32())
+                    || *params != expected_params
+                    || *salt == legacy_salt
+            }
+            _ => true,
+        }
+    }
+
+    /// Upgrades the stored hash while the plain password is known. A failure
+    /// only postpones the upgrade to the next login.
+    fn rehash_password(
+        conn: &mut PgConnection,
+        uid: &Uuid,
+        password: &[u8],
+        hash_provider: &impl PasswordHashProvider,
+    ) {
+        let result = Self::hash_password(password, hash_provider).and_then(|password| {
+            diesel::update(auth_data::dsl::auth_data.find(uid))
+                .set(auth_data::dsl::password.eq(password))
+                .execute(conn)
+                .map_err(|_| AuthServiceError::HashPassword)
+        });
+
+        if let Err(err) = result {
+            log::error!("Password rehash failed for {}: {:?}", uid, err);
+        }
+    }
+
     fn generate_tokens(
         uid: Uuid,
         username: &str,
         email: &str,
-        role: &str,
+        role: UserProfilesRoles,
+        email_verified: bool,
+        family: Uuid,
         secrets_provider: &impl SecretsProvider,
-    ) -> Result<(String, String, usize, usize), AuthServiceError<()>> {
+    ) -> Result<TokensData, AuthServiceError<()>> {
         let (exp, refresh_exp) = AuthService::generate_expiration_time();
+        let now = chrono::Utc::now();
         let access_token_data = JwtAccessData {
             sub: email.to_owned(),
             uid,
             username: username.to_owned(),
-            role: role.to_owned(),
+            role,
+            email_verified,
+            sid: family,
+            jti: Uuid::new_v4(),
+            iat: now.timestamp() as usize,
+            iat_ms: Some(now.timestamp_millis() as usize),
             exp,
         };
-        let refresh_token_data = Uuid::new_v4();
+        let refresh_token_data = JwtRefreshData {
+            uid: Uuid::new_v4(),
+            family,
+            exp: refresh_exp,
+        };
 
         let access_token = encode(
-            &Header::default(),
+            &secrets_provider.access_keys().header(),
             &access_token_data,
-            &EncodingKey::from_secret(secrets_provider.access_secret()),
+            secrets_provider.access_keys().encoding_key(),
         )
         .map_err(|_| AuthServiceError::AccessTokenGeneration)?;
 
@@ -263,7 +727,14 @@ impl AuthService {
         )
         .map_err(|_| AuthServiceError::RefreshTokenGeneration)?;
 
-        Ok((access_token, refresh_token, exp, refresh_exp))
+        Ok(TokensData {
+            uid,
+            access_token,
+            refresh_token,
+            exp,
+            refresh_exp,
+            family,
+        })
     }
 
     fn verify_password(
@@ -288,6 +759,38 @@ impl AuthService {
             .map_err(|_| AuthServiceError::UserNotFound)
     }
 
+    pub fn find_auth_uid_by_profile(
+        &self,
+        profile_uid: &Uuid,
+    ) -> Result<Uuid, DbError<AuthServiceError<()>>> {
+        self.db.apply(move |conn| {
+            auth_data::dsl::auth_data
+                .filter(auth_data::dsl::profile_uid.eq(profile_uid))
+                .select(auth_data::dsl::uid)
+                .first(conn)
+                .map_err(|_| AuthServiceError::UserNotFound)
+        })
+    }
+
+    /// Deleted profiles look like missing ones, blocked ones get no tokens
+    fn find_active_user(
+        conn: &mut PgConnection,
+        profile_uid: &Uuid,
+    ) -> Result<models::user_profiles::UserProfile, AuthServiceError<()>> {
+        let user = UserService::find_user_by_pk(conn, profile_uid)
+            .map_err(|_| AuthServiceError::UserNotFound)?;
+
+        if user.deleted_at.is_some() {
+            return Err(AuthServiceError::UserNotFound);
+        }
+
+        if user.blocked_at.is_some() {
+            return Err(AuthServiceError::AccountBlocked);
+        }
+
+        Ok(user)
+    }
+
     fn find_by_pk(
         conn: &mut PgConnection,
         pk: &Uuid,
@@ -302,27 +805,286 @@ impl AuthService {
         access_token: &str,
         secrets_provider: &impl SecretsProvider,
     ) -> Result<JwtAccessData, AuthServiceError<()>> {
-        let mut validation_without_exp = Validation::default();
+        Self::decode_access_token(access_token, secrets_provider, false).map_err(|err| {
+            log::error!("{}", err);
 
-        validation_without_exp.validate_exp = false;
+            AuthServiceError::InvalidToken
+        })
+    }
 
-        decode::<JwtAccessData>(
-            access_token,
-            &DecodingKey::from_secret(secrets_provider.access_secret()),
-            &validation_without_exp,
+    fn decode_access_token(
+        access_token: &str,
+        secrets_provider: &impl SecretsProvider,
+        validate_exp: bool,
+    ) -> Result<JwtAccessData, jsonwebtoken::errors::Error> {
+        let header = decode_header(access_token)?;
+        let (key, algorithm) = secrets_provider
+            .access_keys()
+            .decoding_key(header.kid.as_deref())
+            .ok_or(jsonwebtoken::errors::ErrorKind::InvalidKeyFormat)?;
+        let mut validation = Validation::new(algorithm);
+
+        validation.validate_exp = validate_exp;
+
+        decode::<JwtAccessData>(access_token, key, &validation).map(|jwt| jwt.claims)
+    }
+
+    pub fn decrypt_refresh_token(
+        refresh_token: &str,
+        secrets_provider: &impl SecretsProvider,
+    ) -> Result<JwtRefreshData, AuthServiceError<()>> {
+        decode::<JwtRefreshData>(
+            refresh_token,
+            &DecodingKey::from_secret(secrets_provider.refresh_secret()),
+            &Validation::default(),
         )
         .map(|jwt| jwt.claims)
         .map_err(|err| {
             log::error!("{}", err);
 
-            AuthServiceError::InvalidToken
+            match err.kind() {
+                jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthServiceError::TokenExpired,
+                _ => AuthServiceError::InvalidToken,
+            }
         })
     }
 
+    pub fn access_token_lifetime() -> chrono::Duration {
+        chrono::Duration::minutes(5)
+    }
+
     fn generate_expiration_time() -> (usize, usize) {
-        let exp = (chrono::Utc::now() + chrono::Duration::minutes(5)).timestamp() as usize;
+        let exp = (chrono::Utc::now() + Self::access_token_lifetime()).timestamp() as usize;
         let refresh_exp = (chrono::Utc::now() + chrono::Duration::days(30)).timestamp() as usize;
 
         (exp, refresh_exp)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        services::oidc::OidcService,
+        test_support::{self, MockIdp, TestAttempts, TestConfig},
+    };
+
+    /// Logs in through the mock provider as the owner of `email`
+    fn external_identity(idp: &MockIdp, email: &str) -> OidcIdentity {
+        let service = OidcService::new(test_support::cache(), Some(idp.settings()));
+        let url = service.authorization_url(None).unwrap();
+
+        idp.respond_with(idp.sign(&idp.claims(&url, email)));
+
+        service
+            .exchange("code", &test_support::authorization_state(&url))
+            .unwrap()
+            .0
+    }
+
+    fn auth_data(uid: &Uuid) -> models::auth_data::AuthData {
+        test_support::db()
+            .apply(|conn| auth_data::dsl::auth_data.find(uid).first(conn))
+            .unwrap()
+    }
+
+    fn is_linked(uid: &Uuid) -> bool {
+        test_support::db()
+            .apply(|conn| {
+                diesel::select(diesel::dsl::exists(
+                    external_identities::dsl::external_identities
+                        .filter(external_identities::dsl::auth_uid.eq(uid)),
+                ))
+                .get_result::<bool>(conn)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn staff_account_is_linked_and_keeps_password() {
+        let idp = MockIdp::start();
+        let service = AuthService::new(test_support::db());
+        let user = test_support::create_user(
+            &test_support::db(),
+            UserProfilesRoles::Employee,
+            Some("password"),
+        );
+        let identity = external_identity(&idp, &user.email);
+
+        for _ in 0..2 {
+            let result = service
+                .authorize_external_user(&identity, &TestConfig, &TestAttempts(None))
+                .unwrap();
+
+            assert!(
+                matches!(result, AuthorizationResult::Authorized(tokens) if tokens.uid == user.auth_uid)
+            );
+        }
+
+        assert!(is_linked(&user.auth_uid));
+        assert!(auth_data(&user.auth_uid).password.is_some());
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn admin_and_client_accounts_are_not_linked() {
+        let idp = MockIdp::start();
+        let service = AuthService::new(test_support::db());
+
+        for role in [UserProfilesRoles::Admin, UserProfilesRoles::User] {
+            let user = test_support::create_user(&test_support::db(), role, Some("password"));
+            let identity = external_identity(&idp, &user.email);
+
+            assert!(matches!(
+                service.authorize_external_user(&identity, &TestConfig, &TestAttempts(None)),
+                Err(DbError::Execution(AuthServiceError::ExternalLinkNotAllowed))
+            ));
+            assert!(!is_linked(&user.auth_uid));
+            assert!(auth_data(&user.auth_uid).password.is_some());
+        }
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn unknown_email_gets_account_without_password() {
+        let idp = MockIdp::start();
+        let service = AuthService::new(test_support::db());
+        let email = format!("{}@example.com", Uuid::new_v4().simple());
+        let identity = external_identity(&idp, &email);
+
+        let uid = match service
+            .authorize_external_user(&identity, &TestConfig, &TestAttempts(None))
+            .unwrap()
+        {
+            AuthorizationResult::Authorized(tokens) => tokens.uid,
+            AuthorizationResult::MfaRequired { .. } => panic!("second factor is not enabled"),
+        };
+
+        assert_eq!(auth_data(&uid).email, email);
+        assert!(auth_data(&uid).password.is_none());
+        assert!(is_linked(&uid));
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn linked_login_asks_for_second_factor() {
+        let idp = MockIdp::start();
+        let service = AuthService::new(test_support::db());
+        let user = test_support::create_user(&test_support::db(), UserProfilesRoles::Law, None);
+
+        test_support::db()
+            .apply(|conn| {
+                diesel::update(auth_data::dsl::auth_data.find(user.auth_uid))
+                    .set(auth_data::dsl::totp_enabled.eq(true))
+                    .execute(conn)
+            })
+            .unwrap();
+
+        let identity = external_identity(&idp, &user.email);
+
+        assert!(matches!(
+            service.authorize_external_user(&identity, &TestConfig, &TestAttempts(None)),
+            Ok(AuthorizationResult::MfaRequired { uid, enrollment: false }) if uid == user.auth_uid
+        ));
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn locked_account_is_refused() {
+        let idp = MockIdp::start();
+        let service = AuthService::new(test_support::db());
+        let user =
+            test_support::create_user(&test_support::db(), UserProfilesRoles::Employee, None);
+        let identity = external_identity(&idp, &user.email);
+
+        assert!(matches!(
+            service.authorize_external_user(
+                &identity,
+                &TestConfig,
+                &TestAttempts(Some(|| LoginAttemptsError::Locked))
+            ),
+            Err(DbError::Execution(AuthServiceError::AccountLocked))
+        ));
+        assert!(matches!(
+            service.authorize_external_user(
+                &identity,
+                &TestConfig,
+                &TestAttempts(Some(|| LoginAttemptsError::Backoff))
+            ),
+            Err(DbError::Execution(AuthServiceError::TooManyAttempts))
+        ));
+    }
+
+    fn encoded(config: &argon2::Config, salt: &[u8]) -> String {
+        argon2::hash_encoded(b"password", salt, config).unwrap()
+    }
+
+    #[test]
+    fn current_params_are_kept() {
+        let hash = encoded(&TestConfig.argon2_config(), &[7; SALT_LENGTH]);
+
+        assert!(!AuthService::needs_rehash(&hash, &TestConfig));
+    }
+
+    #[test]
+    fn other_params_need_rehash() {
+        let current = TestConfig.argon2_config();
+
+        for config in [
+            argon2::Config {
+                mem_cost: current.mem_cost * 2,
+                ..current.clone()
+            },
+            argon2::Config {
+                time_cost: current.time_cost + 1,
+                ..current.clone()
+            },
+            argon2::Config {
+                lanes: current.lanes + 1,
+                ..current.clone()
+            },
+            argon2::Config {
+                variant: argon2::Variant::Argon2i,
+                ..current.clone()
+            },
+            argon2::Config {
+                version: argon2::Version::Version10,
+                ..current.clone()
+            },
+        ] {
+            let hash = encoded(&config, &[7; SALT_LENGTH]);
+
+            assert!(AuthService::needs_rehash(&hash, &TestConfig), "{hash}");
+        }
+    }
+
+    #[test]
+    fn legacy_salt_needs_rehash() {
+        let hash = encoded(&TestConfig.argon2_config(), TestConfig.legacy_salt());
+
+        assert!(AuthService::needs_rehash(&hash, &TestConfig));
+    }
+
+    #[test]
+    fn malformed_hash_needs_rehash() {
+        let hash = encoded(&TestConfig.argon2_config(), &[7; SALT_LENGTH]);
+
+        for malformed in [
+            "",
+            "password",
+            "$",
+            "$argon2id$v=19",
+            "argon2id$v=19$m=64,t=1,p=1$c2FsdA$aGFzaA",
+            &format!("{hash}$extra"),
+            &hash.replace('$', "$$"),
+            "$argon2id$v=19$m=64,t=1,p=1$$",
+            "$аргон2id$v=19$m=64,t=1,p=1$c2FsdA$aGFzaA",
+        ] {
+            assert!(
+                AuthService::needs_rehash(malformed, &TestConfig),
+                "{malformed}"
+            );
+        }
+    }
+}
@@ -0,0 +1,1306 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use chrono::{Days, NaiveTime};
+use diesel::{insert_into, prelude::*, update};
+use uuid::Uuid;
+
+use super::{
+    dto::billing::{
+        BalancesQuery, ClientBalance, CreateInvoiceDto, InvoiceDetails, InvoiceExportRow,
+        InvoicesExportQuery, InvoicesQuery, PayInvoiceDto, RecordPaymentDto,
+    },
+    pagination::Pagination,
+    payments::{Charge, PaymentProvider, Receipt},
+};
+use crate::db::{
+    models::{
+        custom_types::{
+            amount::Amount, invoices_statuses::InvoicesStatuses,
+            law_transaction_statuses::LawTransactionsStatues, payments_statuses::PaymentsStatuses,
+        },
+        invoice_items::InvoiceItem,
+        invoices::Invoice,
+        law_transactions::LawTransactions,
+        payments::Payment,
+        service::Service,
+    },
+    orm::schema::{auth_data, invoice_items, invoices, law_transactions, payments, services},
+    Db, DbError, DbProvider,
+};
+
+const MAX_EXPORT_ROWS: i64 = 10_000;
+const BASIS_POINTS: i128 = 10_000;
+const MANUAL_PROVIDER: &str = "manual";
+/// Longer than any charge takes, younger pending payments may still be in flight
+const STALE_PAYMENT_MINUTES: i64 = 15;
+const RECONCILE_BATCH_SIZE: i64 = 100;
+
+#[derive(Debug)]
+pub enum BillingServiceError {
+    NotFound,
+    UserNotFound,
+    NotAllowed,
+    TransactionNotFound,
+    NotBillable,
+    AlreadyInvoiced,
+    ServiceNotFound,
+    CurrencyMismatch,
+    InvalidDiscount,
+    InvalidAmount,
+    AmountTooLarge,
+    Overpayment,
+    Settled,
+    Void,
+    HasPayments,
+    PaymentPending,
+    PaymentDeclined,
+    InvalidPage,
+    Query,
+    Insert,
+    Update,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = invoice_items)]
+struct NewInvoiceItem {
+    invoice_uid: Uuid,
+    position: i32,
+    service_uid: Option<Uuid>,
+    name: String,
+    quantity: i32,
+    #[diesel(column_name = unit_price_minor)]
+    unit_price: Amount,
+    #[diesel(column_name = discount_minor)]
+    discount: Amount,
+    tax_rate_bp: i32,
+    #[diesel(column_name = tax_minor)]
+    tax: Amount,
+    #[diesel(column_name = total_minor)]
+    total: Amount,
+}
+
+struct NewPayment<'a> {
+    amount: Amount,
+    provider: &'a str,
+    reference: Option<&'a str>,
+    note: Option<&'a str>,
+    recorded_by: Option<Uuid>,
+    paid_at: chrono::NaiveDateTime,
+    status: PaymentsStatuses,
+}
+
+/// Invoices for engagements and payments against them. Amounts are exact
+/// minor units, taxes are rounded half up per line.
+pub struct BillingService {
+    db: Arc<Db>,
+    payment_provider: Arc<dyn PaymentProvider>,
+}
+
+impl BillingService {
+    pub fn new(db: Arc<Db>, payment_provider: Arc<dyn PaymentProvider>) -> Self {
+        Self {
+            db,
+            payment_provider,
+        }
+    }
+
+    /// Bills the engagement. Without explicit items the engaged service is
+    /// billed once at the price agreed on.
+    pub fn create(
+        &self,
+        issuer_uid: &Uuid,
+        dto: &CreateInvoiceDto,
+    ) -> Result<InvoiceDetails, DbError<BillingServiceError>> {
+        self.db.transaction(|conn| {
+            let transaction = law_transactions::table
+                .find(dto.transaction_uid)
+                .for_update()
+                .first::<LawTransactions>(conn)
+                .optional()
+                .map_err(|_| BillingServiceError::Query)?
+                .ok_or(BillingServiceError::TransactionNotFound)?;
+
+            if !matches!(
+                transaction.status,
+                LawTransactionsStatues::Processing | LawTransactionsStatues::Completed
+            ) {
+                return Err(BillingServiceError::NotBillable);
+            }
+
+            // The engagement is locked above, so the check holds until commit
+            let invoiced = diesel::select(diesel::dsl::exists(
+                invoices::table
+                    .filter(invoices::dsl::transaction_uid.eq(transaction.uid))
+                    .filter(invoices::dsl::status.ne(InvoicesStatuses::Void)),
+            ))
+            .get_result::<bool>(conn)
+            .map_err(|_| BillingServiceError::Query)?;
+
+            if invoiced {
+                return Err(BillingServiceError::AlreadyInvoiced);
+            }
+
+            let lines = match &dto.items {
+                None => vec![(
+                    transaction
+                        .service_uid
+                        .ok_or(BillingServiceError::NotBillable)?,
+                    1,
+                    Amount::ZERO,
+                    dto.tax_rate_bp.unwrap_or(0),
+                )],
+                Some(items) => items
+                    .iter()
+                    .map(|item| {
+                        (
+                            item.service_uid,
+                            item.quantity.unwrap_or(1),
+                            item.discount.unwrap_or(Amount::ZERO),
+                            item.tax_rate_bp.or(dto.tax_rate_bp).unwrap_or(0),
+                        )
+                    })
+                    .collect(),
+            };
+
+            let mut items = Vec::with_capacity(lines.len());
+            let (mut subtotal, mut discount, mut tax, mut total) =
+                (Amount::ZERO, Amount::ZERO, Amount::ZERO, Amount::ZERO);
+
+            for (position, (service_uid, quantity, item_discount, tax_rate_bp)) in
+                lines.into_iter().enumerate()
+            {
+                let service = Self::find_service(conn, &transaction, &service_uid)?;
+                let (item, item_subtotal) = Self::price_item(
+                    &transaction,
+                    &service,
+                    position as i32 + 1,
+                    quantity,
+                    item_discount,
+                    tax_rate_bp,
+                )?;
+
+                subtotal = Self::add(subtotal, item_subtotal)?;
+                discount = Self::add(discount, item.discount)?;
+                tax = Self::add(tax, item.tax)?;
+                total = Self::add(total, item.total)?;
+                items.push(item);
+            }
+
+            let status = if total == Amount::ZERO {
+                InvoicesStatuses::Paid
+            } else {
+                InvoicesStatuses::Issued
+            };
+
+            let invoice = insert_into(invoices::dsl::invoices)
+                .values((
+                    invoices::dsl::transaction_uid.eq(transaction.uid),
+                    invoices::dsl::client_uid.eq(transaction.client_uid),
+                    invoices::dsl::law_uid.eq(transaction.law_uid),
+                    invoices::dsl::currency.eq(&transaction.currency),
+                    invoices::dsl::subtotal_minor.eq(subtotal),
+                    invoices::dsl::discount_minor.eq(discount),
+                    invoices::dsl::tax_minor.eq(tax),
+                    invoices::dsl::total_minor.eq(total),
+                    invoices::dsl::status.eq(status),
+                    invoices::dsl::issued_by.eq(issuer_uid),
+                ))
+                .get_result::<Invoice>(conn)
+                .map_err(|err| match err {
+                    diesel::result::Error::DatabaseError(
+                        diesel::result::DatabaseErrorKind::UniqueViolation,
+                        _,
+                    ) => BillingServiceError::AlreadyInvoiced,
+                    err => {
+                        log::error!("{}", err);
+                        BillingServiceError::Insert
+                    }
+                })?;
+
+            for item in items.iter_mut() {
+                item.invoice_uid = invoice.uid;
+            }
+
+            let items = insert_into(invoice_items::dsl::invoice_items)
+                .values(&items)
+                .get_results::<InvoiceItem>(conn)
+                .map_err(|err| {
+                    log::error!("{}", err);
+                    BillingServiceError::Insert
+                })?;
+
+            Ok(InvoiceDetails {
+                outstanding: total,
+                invoice,
+                items,
+                payments: Vec::new(),
+            })
+        })
+    }
+
+    /// Employees see every invoice, clients and lawyers those of their
+    /// engagements
+    pub fn get(
+        &self,
+        auth_uid: &Uuid,
+        uid: &Uuid,
+        manage: bool,
+    ) -> Result<InvoiceDetails, DbError<BillingServiceError>> {
+        self.db.apply(|conn| {
+            let details = Self::details(conn, uid)?;
+
+            if !manage {
+                let profile_uid = Some(Self::find_profile_uid(conn, auth_uid)?);
+
+                if details.invoice.client_uid != profile_uid
+                    && details.invoice.law_uid != profile_uid
+                {
+                    return Err(BillingServiceError::NotFound);
+                }
+            }
+
+            Ok(details)
+        })
+    }
+
+    pub fn get_own(
+        &self,
+        auth_uid: &Uuid,
+        filters: &InvoicesQuery,
+    ) -> Result<Vec<Invoice>, DbError<BillingServiceError>> {
+        self.db.apply(|conn| {
+            let profile_uid = Self::find_profile_uid(conn, auth_uid)?;
+            let query = invoices::table
+                .filter(
+                    invoices::dsl::client_uid
+                        .eq(profile_uid)
+                        .or(invoices::dsl::law_uid.eq(profile_uid)),
+                )
+                .into_boxed();
+
+            Self::page(conn, query, filters)
+        })
+    }
+
+    pub fn get_invoices(
+        &self,
+        filters: &InvoicesQuery,
+    ) -> Result<Vec<Invoice>, DbError<BillingServiceError>> {
+        self.db.apply(|conn| {
+            let mut query = invoices::table.into_boxed();
+
+            if let Some(client_uid) = filters.client_uid {
+                query = query.filter(invoices::dsl::client_uid.eq(client_uid));
+            }
+
+            Self::page(conn, query, filters)
+        })
+    }
+
+    /// Payments made outside the system, recorded by an employee
+    pub fn record_payment(
+        &self,
+        recorder_uid: &Uuid,
+        uid: &Uuid,
+        dto: &RecordPaymentDto,
+    ) -> Result<InvoiceDetails, DbError<BillingServiceError>> {
+        self.db.transaction(|conn| {
+            let invoice = Self::lock(conn, uid)?;
+            let payable = Self::payable(conn, &invoice)?;
+
+            Self::check_payable(&invoice, dto.amount, payable)?;
+            Self::insert_payment(
+                conn,
+                &invoice,
+                NewPayment {
+                    amount: dto.amount,
+                    provider: MANUAL_PROVIDER,
+                    reference: dto.reference.as_deref(),
+                    note: dto.note.as_deref(),
+                    recorded_by: Some(*recorder_uid),
+                    paid_at: dto
+                        .paid_at
+                        .unwrap_or_else(|| chrono::Utc::now().naive_utc()),
+                    status: PaymentsStatuses::Succeeded,
+                },
+            )?;
+            Self::add_paid(conn, &invoice, dto.amount)?;
+
+            Self::details(conn, uid)
+        })
+    }
+
+    /// The client pays through the payment provider, the charged amount is
+    /// returned along with the invoice. The payment is stored as pending
+    /// before the provider is called, which holds its amount back from other
+    /// payments without keeping the invoice locked during the call. When the
+    /// outcome of the charge can't be stored the payment stays pending until
+    /// [`BillingService::reconcile_pending`] settles it.
+    pub fn pay(
+        &self,
+        auth_uid: &Uuid,
+        uid: &Uuid,
+        dto: &PayInvoiceDto,
+    ) -> Result<(InvoiceDetails, Amount), DbError<BillingServiceError>> {
+        let (payment_uid, amount, currency) = self.db.transaction(|conn| {
+            let profile_uid = Some(Self::find_profile_uid(conn, auth_uid)?);
+            let invoice = Self::lock(conn, uid)?;
+
+            if invoice.client_uid != profile_uid {
+                return Err(if invoice.law_uid == profile_uid {
+                    BillingServiceError::NotAllowed
+                } else {
+                    BillingServiceError::NotFound
+                });
+            }
+
+            let payable = Self::payable(conn, &invoice)?;
+            let amount = dto.amount.unwrap_or(payable);
+
+            Self::check_payable(&invoice, amount, payable)?;
+
+            let payment_uid = Self::insert_payment(
+                conn,
+                &invoice,
+                NewPayment {
+                    amount,
+                    provider: self.payment_provider.name(),
+                    reference: None,
+                    note: None,
+                    recorded_by: None,
+                    paid_at: chrono::Utc::now().naive_utc(),
+                    status: PaymentsStatuses::Pending,
+                },
+            )?;
+
+            Ok((payment_uid, amount, invoice.currency))
+        })?;
+
+        let receipt = self
+            .payment_provider
+            .charge(
+                &payment_uid,
+                &Charge {
+                    invoice_uid: *uid,
+                    amount,
+                    currency: &currency,
+                },
+            )
+            .map_err(|err| {
+                log::warn!(
+                    "Charge {} for invoice {} failed: {:?}",
+                    payment_uid,
+                    uid,
+                    err
+                )
+            })
+            .ok();
+
+        let details = self
+            .db
+            .transaction(|conn| {
+                Self::settle(conn, uid, &payment_uid, amount, receipt.as_ref())?;
+                Self::details(conn, uid)
+            })
+            .map_err(|_| {
+                log::error!(
+                    "Outcome of charge {} for invoice {} was not stored, it stays pending",
+                    payment_uid,
+                    uid
+                );
+                DbError::Execution(BillingServiceError::PaymentPending)
+            })?;
+
+        if receipt.is_none() {
+            return Err(DbError::Execution(BillingServiceError::PaymentDeclined));
+        }
+
+        Ok((details, amount))
+    }
+
+    /// Asks the provider what became of payments pending for longer than a
+    /// charge takes and stores the outcome. Payments the provider can't
+    /// tell about stay pending for the next run. Returns how many were
+    /// settled.
+    pub fn reconcile_pending(&self) -> Result<usize, DbError<BillingServiceError>> {
+        let stale_before =
+            chrono::Utc::now().naive_utc() - chrono::Duration::minutes(STALE_PAYMENT_MINUTES);
+        let stale = self.db.apply(|conn| {
+            payments::table
+                .inner_join(invoices::table)
+                .filter(payments::dsl::status.eq(PaymentsStatuses::Pending))
+                .filter(payments::dsl::provider.eq(self.payment_provider.name()))
+                .filter(payments::dsl::created_at.lt(stale_before))
+                .order(payments::dsl::created_at.asc())
+                .limit(RECONCILE_BATCH_SIZE)
+                .select((Payment::as_select(), invoices::dsl::currency))
+                .load::<(Payment, String)>(conn)
+                .map_err(|_| BillingServiceError::Query)
+        })?;
+        let mut settled = 0;
+
+        for (payment, currency) in stale {
+            let found = self.payment_provider.find_charge(
+                &payment.uid,
+                &Charge {
+                    invoice_uid: payment.invoice_uid,
+                    amount: payment.amount,
+                    currency: &currency,
+                },
+            );
+            let receipt = match found {
+                Some(found) => found.ok(),
+                None => continue,
+            };
+            let stored = self.db.transaction(|conn| {
+                Self::settle(
+                    conn,
+                    &payment.invoice_uid,
+                    &payment.uid,
+                    payment.amount,
+                    receipt.as_ref(),
+                )
+            });
+
+            match stored {
+                Ok(true) => settled += 1,
+                Ok(false) => (),
+                Err(_) => log::error!("Pending payment {} was not reconciled", payment.uid),
+            }
+        }
+
+        Ok(settled)
+    }
+
+    /// Only invoices nothing was paid against are voided, paid ones are
+    /// corrected by a new invoice
+    pub fn void(&self, uid: &Uuid) -> Result<Invoice, DbError<BillingServiceError>> {
+        self.db.transaction(|conn| {
+            let invoice = Self::lock(conn, uid)?;
+
+            if invoice.status == InvoicesStatuses::Void {
+                return Err(BillingServiceError::Void);
+            }
+
+            if invoice.paid != Amount::ZERO || Self::pending(conn, &invoice.uid)? != Amount::ZERO {
+                return Err(BillingServiceError::HasPayments);
+            }
+
+            update(invoices::table.find(invoice.uid))
+                .set((
+                    invoices::dsl::status.eq(InvoicesStatuses::Void),
+                    invoices::dsl::voided_at.eq(chrono::Utc::now().naive_utc()),
+                ))
+                .get_result(conn)
+                .map_err(|err| {
+                    log::error!("{}", err);
+                    BillingServiceError::Update
+                })
+        })
+    }
+
+    pub fn get_balances(
+        &self,
+        filters: &BalancesQuery,
+    ) -> Result<Vec<ClientBalance>, DbError<BillingServiceError>> {
+        self.db
+            .apply(|conn| Self::balances(conn, filters.client_uid))
+    }
+
+    pub fn get_own_balance(
+        &self,
+        auth_uid: &Uuid,
+    ) -> Result<Vec<ClientBalance>, DbError<BillingServiceError>> {
+        self.db.apply(|conn| {
+            let profile_uid = Self::find_profile_uid(conn, auth_uid)?;
+
+            Self::balances(conn, Some(profile_uid))
+        })
+    }
+
+    pub fn export(
+        &self,
+        filters: &InvoicesExportQuery,
+    ) -> Result<Vec<InvoiceExportRow>, DbError<BillingServiceError>> {
+        self.db.apply(|conn| {
+            let mut query = invoices::table.into_boxed();
+
+            if let Some(from) = filters.from {
+                query = query.filter(invoices::dsl::created_at.ge(from.and_time(NaiveTime::MIN)));
+            }
+
+            if let Some(to) = filters.to.and_then(|to| to.checked_add_days(Days::new(1))) {
+                query = query.filter(invoices::dsl::created_at.lt(to.and_time(NaiveTime::MIN)));
+            }
+
+            if let Some(status) = filters.status {
+                query = query.filter(invoices::dsl::status.eq(status));
+            }
+
+            let invoices = query
+                .order(invoices::dsl::number.asc())
+                .limit(MAX_EXPORT_ROWS)
+                .load::<Invoice>(conn)
+                .map_err(|_| BillingServiceError::Query)?;
+
+            Ok(invoices
+                .into_iter()
+                .map(|invoice| InvoiceExportRow {
+                    outstanding: Self::outstanding(&invoice),
+                    number: invoice.number,
+                    uid: invoice.uid,
+                    transaction_uid: invoice.transaction_uid,
+                    client_uid: invoice.client_uid,
+                    law_uid: invoice.law_uid,
+                    status: invoice.status,
+                    currency: invoice.currency,
+                    subtotal: invoice.subtotal,
+                    discount: invoice.discount,
+                    tax: invoice.tax,
+                    total: invoice.total,
+                    paid: invoice.paid,
+                    created_at: invoice.created_at,
+                    voided_at: invoice.voided_at,
+                })
+                .collect())
+        })
+    }
+
+    /// Services of the engaged lawyer in the currency of the engagement. The
+    /// engaged service itself is billable even after it was archived.
+    fn find_service(
+        conn: &mut PgConnection,
+        transaction: &LawTransactions,
+        service_uid: &Uuid,
+    ) -> Result<Service, BillingServiceError> {
+        let service = services::table
+            .find(service_uid)
+            .first::<Service>(conn)
+            .optional()
+            .map_err(|_| BillingServiceError::Query)?
+            .filter(|service| Some(service.law_uid) == transaction.law_uid)
+            .filter(|service| {
+                service.archived_at.is_none() || Some(service.uid) == transaction.service_uid
+            })
+            .ok_or(BillingServiceError::ServiceNotFound)?;
+
+        if service.currency != transaction.currency {
+            return Err(BillingServiceError::CurrencyMismatch);
+        }
+
+        Ok(service)
+    }
+
+    /// Returns the line and its amount before discount and taxes
+    fn price_item(
+        transaction: &LawTransactions,
+        service: &Service,
+        position: i32,
+        quantity: u32,
+        discount: Amount,
+        tax_rate_bp: u32,
+    ) -> Result<(NewInvoiceItem, Amount), BillingServiceError> {
+        let unit_price = if Some(service.uid) == transaction.service_uid {
+            transaction.cost
+        } else {
+            service.cost
+        };
+        let subtotal = unit_price
+            .checked_mul(quantity as i64)
+            .ok_or(BillingServiceError::AmountTooLarge)?;
+        let taxable = subtotal
+            .checked_sub(discount)
+            .filter(|taxable| *taxable >= Amount::ZERO)
+            .ok_or(BillingServiceError::InvalidDiscount)?;
+        let tax = (taxable.minor() as i128 * tax_rate_bp as i128 + BASIS_POINTS / 2) / BASIS_POINTS;
+        let tax = i64::try_from(tax)
+            .map(Amount::from_minor)
+            .map_err(|_| BillingServiceError::AmountTooLarge)?;
+        let total = Self::add(taxable, tax)?;
+        let item = NewInvoiceItem {
+            invoice_uid: Uuid::nil(),
+            position,
+            service_uid: Some(service.uid),
+            name: service.name.clone(),
+            quantity: quantity as i32,
+            unit_price,
+            discount,
+            tax_rate_bp: tax_rate_bp as i32,
+            tax,
+            total,
+        };
+
+        Ok((item, subtotal))
+    }
+
+    fn add(left: Amount, right: Amount) -> Result<Amount, BillingServiceError> {
+        left.checked_add(right)
+            .ok_or(BillingServiceError::AmountTooLarge)
+    }
+
+    /// What is left to pay once pending charges go through
+    fn payable(conn: &mut PgConnection, invoice: &Invoice) -> Result<Amount, BillingServiceError> {
+        Ok(Self::outstanding(invoice)
+            .checked_sub(Self::pending(conn, &invoice.uid)?)
+            .filter(|payable| *payable >= Amount::ZERO)
+            .unwrap_or(Amount::ZERO))
+    }
+
+    fn pending(conn: &mut PgConnection, invoice_uid: &Uuid) -> Result<Amount, BillingServiceError> {
+        payments::table
+            .filter(payments::dsl::invoice_uid.eq(invoice_uid))
+            .filter(payments::dsl::status.eq(PaymentsStatuses::Pending))
+            .select(payments::dsl::amount_minor)
+            .load::<Amount>(conn)
+            .map_err(|_| BillingServiceError::Query)?
+            .into_iter()
+            .try_fold(Amount::ZERO, Self::add)
+    }
+
+    fn check_payable(
+        invoice: &Invoice,
+        amount: Amount,
+        payable: Amount,
+    ) -> Result<(), BillingServiceError> {
+        if invoice.status == InvoicesStatuses::Void {
+            return Err(BillingServiceError::Void);
+        }
+
+        if invoice.status == InvoicesStatuses::Paid {
+            return Err(BillingServiceError::Settled);
+        }
+
+        if payable == Amount::ZERO {
+            return Err(BillingServiceError::PaymentPending);
+        }
+
+        if amount <= Amount::ZERO {
+            return Err(BillingServiceError::InvalidAmount);
+        }
+
+        if amount > payable {
+            return Err(BillingServiceError::Overpayment);
+        }
+
+        Ok(())
+    }
+
+    fn insert_payment(
+        conn: &mut PgConnection,
+        invoice: &Invoice,
+        payment: NewPayment,
+    ) -> Result<Uuid, BillingServiceError> {
+        insert_into(payments::dsl::payments)
+            .values((
+                payments::dsl::invoice_uid.eq(invoice.uid),
+                payments::dsl::amount_minor.eq(payment.amount),
+                payments::dsl::provider.eq(payment.provider),
+                payments::dsl::reference.eq(payment.reference),
+                payments::dsl::note.eq(payment.note),
+                payments::dsl::recorded_by.eq(payment.recorded_by),
+                payments::dsl::paid_at.eq(payment.paid_at),
+                payments::dsl::status.eq(payment.status),
+            ))
+            .returning(payments::dsl::uid)
+            .get_result(conn)
+            .map_err(|err| {
+                log::error!("{}", err);
+                BillingServiceError::Insert
+            })
+    }
+
+    /// Stores the outcome of a pending charge, `false` when it was stored
+    /// already
+    fn settle(
+        conn: &mut PgConnection,
+        invoice_uid: &Uuid,
+        payment_uid: &Uuid,
+        amount: Amount,
+        receipt: Option<&Receipt>,
+    ) -> Result<bool, BillingServiceError> {
+        let invoice = Self::lock(conn, invoice_uid)?;
+        let status = match receipt {
+            Some(_) => PaymentsStatuses::Succeeded,
+            None => PaymentsStatuses::Failed,
+        };
+        let updated = update(
+            payments::table
+                .find(payment_uid)
+                .filter(payments::dsl::status.eq(PaymentsStatuses::Pending)),
+        )
+        .set((
+            payments::dsl::status.eq(status),
+            payments::dsl::reference.eq(receipt.map(|receipt| &receipt.reference)),
+            payments::dsl::paid_at.eq(chrono::Utc::now().naive_utc()),
+        ))
+        .execute(conn)
+        .map_err(|err| {
+            log::error!("{}", err);
+            BillingServiceError::Update
+        })?;
+
+        if updated == 0 {
+            return Ok(false);
+        }
+
+        if receipt.is_some() {
+            Self::add_paid(conn, &invoice, amount)?;
+        }
+
+        Ok(true)
+    }
+
+    fn add_paid(
+        conn: &mut PgConnection,
+        invoice: &Invoice,
+        amount: Amount,
+    ) -> Result<(), BillingServiceError> {
+        let paid = Self::add(invoice.paid, amount)?;
+        let status = if paid == invoice.total {
+            InvoicesStatuses::Paid
+        } else {
+            InvoicesStatuses::PartiallyPaid
+        };
+
+        update(invoices::table.find(invoice.uid))
+            .set((
+                invoices::dsl::paid_minor.eq(paid),
+                invoices::dsl::status.eq(status),
+            ))
+            .execute(conn)
+            .map_err(|err| {
+                log::error!("{}", err);
+                BillingServiceError::Update
+            })?;
+
+        Ok(())
+    }
+
+    /// Sums open invoices per client and currency
+    fn balances(
+        conn: &mut PgConnection,
+        client_uid: Option<Uuid>,
+    ) -> Result<Vec<ClientBalance>, BillingServiceError> {
+        let mut query = invoices::table
+            .filter(invoices::dsl::client_uid.is_not_null())
+            .filter(
+                invoices::dsl::status
+                    .eq_any([InvoicesStatuses::Issued, InvoicesStatuses::PartiallyPaid]),
+            )
+            .select((
+                invoices::dsl::client_uid,
+                invoices::dsl::currency,
+                invoices::dsl::total_minor,
+                invoices::dsl::paid_minor,
+            ))
+            .into_boxed();
+
+        if let Some(client_uid) = client_uid {
+            query = query.filter(invoices::dsl::client_uid.eq(client_uid));
+        }
+
+        let rows = query
+            .load::<(Option<Uuid>, String, Amount, Amount)>(conn)
+            .map_err(|_| BillingServiceError::Query)?;
+        let mut balances = BTreeMap::<(Uuid, String), ClientBalance>::new();
+
+        for (client_uid, currency, total, paid) in rows {
+            let Some(client_uid) = client_uid else {
+                continue;
+            };
+            let balance = balances
+                .entry((client_uid, currency.clone()))
+                .or_insert_with(|| ClientBalance {
+                    client_uid,
+                    currency,
+                    invoiced: Amount::ZERO,
+                    paid: Amount::ZERO,
+                    outstanding: Amount::ZERO,
+                    open_invoices: 0,
+                });
+
+            balance.invoiced = Self::add(balance.invoiced, total)?;
+            balance.paid = Self::add(balance.paid, paid)?;
+            balance.outstanding = balance
+                .invoiced
+                .checked_sub(balance.paid)
+                .ok_or(BillingServiceError::AmountTooLarge)?;
+            balance.open_invoices += 1;
+        }
+
+        Ok(balances.into_values().collect())
+    }
+
+    fn page(
+        conn: &mut PgConnection,
+        mut query: invoices::BoxedQuery<'_, diesel::pg::Pg>,
+        filters: &InvoicesQuery,
+    ) -> Result<Vec<Invoice>, BillingServiceError> {
+        let pagination = Pagination::new(filters.page, filters.per_page)
+            .ok_or(BillingServiceError::InvalidPage)?;
+
+        if let Some(status) = filters.status {
+            query = query.filter(invoices::dsl::status.eq(status));
+        }
+
+        query
+            .order(invoices::dsl::number.desc())
+            .offset(pagination.offset)
+            .limit(pagination.per_page)
+            .load(conn)
+            .map_err(|_| BillingServiceError::Query)
+    }
+
+    fn details(conn: &mut PgConnection, uid: &Uuid) -> Result<InvoiceDetails, BillingServiceError> {
+        let invoice = invoices::table
+            .find(uid)
+            .first::<Invoice>(conn)
+            .optional()
+            .map_err(|_| BillingServiceError::Query)?
+            .ok_or(BillingServiceError::NotFound)?;
+        let items = InvoiceItem::belonging_to(&invoice)
+            .order(invoice_items::dsl::position.asc())
+            .load(conn)
+            .map_err(|_| BillingServiceError::Query)?;
+        let payments = Payment::belonging_to(&invoice)
+            .order(payments::dsl::paid_at.asc())
+            .load(conn)
+            .map_err(|_| BillingServiceError::Query)?;
+
+        Ok(InvoiceDetails {
+            outstanding: Self::outstanding(&invoice),
+            invoice,
+            items,
+            payments,
+        })
+    }
+
+    fn lock(conn: &mut PgConnection, uid: &Uuid) -> Result<Invoice, BillingServiceError> {
+        invoices::table
+            .find(uid)
+            .for_update()
+            .first::<Invoice>(conn)
+            .optional()
+            .map_err(|_| BillingServiceError::Query)?
+            .ok_or(BillingServiceError::NotFound)
+    }
+
+    fn outstanding(invoice: &Invoice) -> Amount {
+        invoice
+            .total
+            .checked_sub(invoice.paid)
+            .unwrap_or(Amount::ZERO)
+    }
+
+    fn find_profile_uid(
+        conn: &mut PgConnection,
+        auth_uid: &Uuid,
+    ) -> Result<Uuid, BillingServiceError> {
+        auth_data::table
+            .find(auth_uid)
+            .select(auth_data::dsl::profile_uid)
+            .first::<Uuid>(conn)
+            .optional()
+            .map_err(|_| BillingServiceError::Query)?
+            .ok_or(BillingServiceError::UserNotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::{
+        services::{
+            dto::law_transaction::CreateLawTransactionDto,
+            payments::{PaymentProviderError, Receipt},
+            transactions::TransactionService,
+        },
+        test_support,
+    };
+
+    const ENGAGED: Uuid = Uuid::from_u128(1);
+
+    fn transaction(cost: i64) -> LawTransactions {
+        let now = chrono::Utc::now().naive_utc();
+
+        LawTransactions {
+            uid: Uuid::new_v4(),
+            court_case_uid: None,
+            client_uid: None,
+            status: LawTransactionsStatues::Processing,
+            created_at: now,
+            law_uid: None,
+            service_uid: Some(ENGAGED),
+            cost: Amount::from_minor(cost),
+            currency: "RUB".to_owned(),
+            reason: None,
+            accepted_at: None,
+            declined_at: None,
+            completed_at: None,
+            cancelled_at: None,
+        }
+    }
+
+    fn service(uid: Uuid, cost: i64) -> Service {
+        Service {
+            uid,
+            law_uid: Uuid::new_v4(),
+            name: "Consultation".to_owned(),
+            cost: Amount::from_minor(cost),
+            currency: "RUB".to_owned(),
+            archived_at: None,
+            created_at: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    /// Tax and total of a single line
+    fn price(unit: i64, quantity: u32, discount: i64, tax_rate_bp: u32) -> (i64, i64) {
+        let (item, _) = BillingService::price_item(
+            &transaction(unit),
+            &service(ENGAGED, 0),
+            1,
+            quantity,
+            Amount::from_minor(discount),
+            tax_rate_bp,
+        )
+        .unwrap();
+
+        (item.tax.minor(), item.total.minor())
+    }
+
+    #[test]
+    fn tax_is_rounded_half_up() {
+        // 10% of 0.15 is 0.015
+        assert_eq!(price(15, 1, 0, 1000), (2, 17));
+        assert_eq!(price(14, 1, 0, 1000), (1, 15));
+        // 20% of 3 * 0.33 is 0.198
+        assert_eq!(price(33, 3, 0, 2000), (20, 119));
+        assert_eq!(price(10_000, 1, 0, 0), (0, 10_000));
+    }
+
+    #[test]
+    fn tax_is_taken_after_discount() {
+        // 20% of 2 * 10.00 - 5.00
+        assert_eq!(price(1000, 2, 500, 2000), (300, 1800));
+        assert_eq!(price(1000, 1, 1000, 2000), (0, 0));
+    }
+
+    #[test]
+    fn line_pricing_errors() {
+        let discount_over_price = BillingService::price_item(
+            &transaction(1000),
+            &service(ENGAGED, 0),
+            1,
+            1,
+            Amount::from_minor(1001),
+            0,
+        );
+        assert!(matches!(
+            discount_over_price,
+            Err(BillingServiceError::InvalidDiscount)
+        ));
+
+        let overflow = BillingService::price_item(
+            &transaction(i64::MAX / 2 + 1),
+            &service(ENGAGED, 0),
+            1,
+            2,
+            Amount::ZERO,
+            0,
+        );
+        assert!(matches!(overflow, Err(BillingServiceError::AmountTooLarge)));
+    }
+
+    #[test]
+    fn other_services_are_billed_at_their_price() {
+        let (item, subtotal) = BillingService::price_item(
+            &transaction(1000),
+            &service(Uuid::new_v4(), 2500),
+            2,
+            2,
+            Amount::ZERO,
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(item.unit_price, Amount::from_minor(2500));
+        assert_eq!(subtotal, Amount::from_minor(5000));
+    }
+
+    /// Declines on demand and remembers the idempotency keys it was given
+    #[derive(Default)]
+    struct TestProvider {
+        decline: Mutex<bool>,
+        unreachable: Mutex<bool>,
+        charges: Mutex<Vec<Uuid>>,
+    }
+
+    impl PaymentProvider for TestProvider {
+        fn name(&self) -> &'static str {
+            "test"
+        }
+
+        fn charge(&self, payment_uid: &Uuid, _: &Charge) -> Result<Receipt, PaymentProviderError> {
+            self.charges.lock().unwrap().push(*payment_uid);
+
+            if *self.decline.lock().unwrap() {
+                return Err(PaymentProviderError::Declined);
+            }
+
+            Ok(Receipt {
+                reference: format!("test-{}", payment_uid),
+            })
+        }
+
+        fn find_charge(
+            &self,
+            payment_uid: &Uuid,
+            _: &Charge,
+        ) -> Option<Result<Receipt, PaymentProviderError>> {
+            if *self.unreachable.lock().unwrap() {
+                return None;
+            }
+
+            if !self.charges.lock().unwrap().contains(payment_uid) {
+                return Some(Err(PaymentProviderError::Declined));
+            }
+
+            Some(Ok(Receipt {
+                reference: format!("test-{}", payment_uid),
+            }))
+        }
+    }
+
+    /// Client and lawyer of an accepted engagement billed at 1000.00
+    fn engagement(db: &Arc<Db>) -> (test_support::TestUser, test_support::TestUser, Uuid) {
+        let (law, service_uid) =
+            test_support::create_law_service(db, Amount::from_minor(100_000), "RUB");
+        let client = test_support::create_client(db);
+        let transactions = TransactionService::new(db.clone());
+        let transaction = transactions
+            .create(
+                &client.auth_uid,
+                &CreateLawTransactionDto {
+                    service_uid,
+                    court_case_uid: None,
+                },
+            )
+            .unwrap();
+
+        transactions
+            .transition(
+                &law.auth_uid,
+                &transaction.uid,
+                LawTransactionsStatues::Processing,
+                None,
+            )
+            .unwrap();
+
+        (client, law, transaction.uid)
+    }
+
+    fn invoice_dto(transaction_uid: Uuid) -> CreateInvoiceDto {
+        CreateInvoiceDto {
+            transaction_uid,
+            items: None,
+            tax_rate_bp: None,
+        }
+    }
+
+    fn pay(amount: Option<i64>) -> PayInvoiceDto {
+        PayInvoiceDto {
+            amount: amount.map(Amount::from_minor),
+        }
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn partial_payments_settle_the_invoice() {
+        let db = test_support::db();
+        let provider = Arc::new(TestProvider::default());
+        let service = BillingService::new(db.clone(), provider.clone());
+        let (client, law, transaction_uid) = engagement(&db);
+        let invoice = service
+            .create(&law.auth_uid, &invoice_dto(transaction_uid))
+            .unwrap()
+            .invoice;
+
+        assert!(matches!(
+            service.create(&law.auth_uid, &invoice_dto(transaction_uid)),
+            Err(DbError::Execution(BillingServiceError::AlreadyInvoiced))
+        ));
+
+        let (details, amount) = service
+            .pay(&client.auth_uid, &invoice.uid, &pay(Some(40_000)))
+            .unwrap();
+        assert_eq!(amount, Amount::from_minor(40_000));
+        assert_eq!(details.invoice.status, InvoicesStatuses::PartiallyPaid);
+        assert_eq!(details.outstanding, Amount::from_minor(60_000));
+        assert_eq!(details.payments[0].uid, provider.charges.lock().unwrap()[0]);
+        assert_eq!(details.payments[0].status, PaymentsStatuses::Succeeded);
+
+        assert!(matches!(
+            service.pay(&client.auth_uid, &invoice.uid, &pay(Some(60_001))),
+            Err(DbError::Execution(BillingServiceError::Overpayment))
+        ));
+
+        let (details, amount) = service
+            .pay(&client.auth_uid, &invoice.uid, &pay(None))
+            .unwrap();
+        assert_eq!(amount, Amount::from_minor(60_000));
+        assert_eq!(details.invoice.status, InvoicesStatuses::Paid);
+        assert_eq!(details.invoice.paid, details.invoice.total);
+
+        service.void(&invoice.uid).unwrap_err();
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn declined_charge_is_kept_as_failed() {
+        let db = test_support::db();
+        let provider = Arc::new(TestProvider::default());
+        let service = BillingService::new(db.clone(), provider.clone());
+        let (client, law, transaction_uid) = engagement(&db);
+        let invoice = service
+            .create(&law.auth_uid, &invoice_dto(transaction_uid))
+            .unwrap()
+            .invoice;
+
+        *provider.decline.lock().unwrap() = true;
+        assert!(matches!(
+            service.pay(&client.auth_uid, &invoice.uid, &pay(None)),
+            Err(DbError::Execution(BillingServiceError::PaymentDeclined))
+        ));
+
+        let details = service.get(&client.auth_uid, &invoice.uid, false).unwrap();
+        assert_eq!(details.invoice.status, InvoicesStatuses::Issued);
+        assert_eq!(details.invoice.paid, Amount::ZERO);
+        assert_eq!(details.payments.len(), 1);
+        assert_eq!(details.payments[0].status, PaymentsStatuses::Failed);
+
+        // A voided invoice makes room for a new one
+        service.void(&invoice.uid).unwrap();
+        service
+            .create(&law.auth_uid, &invoice_dto(transaction_uid))
+            .unwrap();
+    }
+
+    fn insert_pending(db: &Db, invoice_uid: &Uuid, amount: i64) -> Uuid {
+        db.transaction(|conn| {
+            let invoice = BillingService::lock(conn, invoice_uid)?;
+
+            BillingService::insert_payment(
+                conn,
+                &invoice,
+                NewPayment {
+                    amount: Amount::from_minor(amount),
+                    provider: "test",
+                    reference: None,
+                    note: None,
+                    recorded_by: None,
+                    paid_at: chrono::Utc::now().naive_utc(),
+                    status: PaymentsStatuses::Pending,
+                },
+            )
+        })
+        .unwrap()
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn pending_charge_holds_its_amount() {
+        let db = test_support::db();
+        let service = BillingService::new(db.clone(), Arc::new(TestProvider::default()));
+        let (client, law, transaction_uid) = engagement(&db);
+        let invoice = service
+            .create(&law.auth_uid, &invoice_dto(transaction_uid))
+            .unwrap()
+            .invoice;
+
+        insert_pending(&db, &invoice.uid, 100_000);
+
+        assert!(matches!(
+            service.pay(&client.auth_uid, &invoice.uid, &pay(None)),
+            Err(DbError::Execution(BillingServiceError::PaymentPending))
+        ));
+        assert!(matches!(
+            service.void(&invoice.uid),
+            Err(DbError::Execution(BillingServiceError::HasPayments))
+        ));
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn stale_pending_payments_are_reconciled() {
+        let db = test_support::db();
+        let provider = Arc::new(TestProvider::default());
+        let service = BillingService::new(db.clone(), provider.clone());
+        let (_, law, transaction_uid) = engagement(&db);
+        let invoice = service
+            .create(&law.auth_uid, &invoice_dto(transaction_uid))
+            .unwrap()
+            .invoice;
+        let charged = insert_pending(&db, &invoice.uid, 60_000);
+        let lost = insert_pending(&db, &invoice.uid, 40_000);
+        let details = || {
+            db.apply(|conn| BillingService::details(conn, &invoice.uid))
+                .unwrap()
+        };
+        let statuses = || {
+            let details = details();
+            let status = |uid| {
+                details
+                    .payments
+                    .iter()
+                    .find(|payment| payment.uid == uid)
+                    .map(|payment| payment.status)
+                    .unwrap()
+            };
+
+            (status(charged), status(lost))
+        };
+
+        provider.charges.lock().unwrap().push(charged);
+
+        // Charges that may still be in flight are left alone
+        service.reconcile_pending().unwrap();
+        assert_eq!(
+            statuses(),
+            (PaymentsStatuses::Pending, PaymentsStatuses::Pending)
+        );
+
+        db.apply(|conn| {
+            update(payments::table.filter(payments::dsl::uid.eq_any([charged, lost])))
+                .set(payments::dsl::created_at.eq(chrono::Utc::now().naive_utc()
+                    - chrono::Duration::minutes(STALE_PAYMENT_MINUTES + 1)))
+                .execute(conn)
+        })
+        .unwrap();
+
+        *provider.unreachable.lock().unwrap() = true;
+        service.reconcile_pending().unwrap();
+        assert_eq!(
+            statuses(),
+            (PaymentsStatuses::Pending, PaymentsStatuses::Pending)
+        );
+
+        *provider.unreachable.lock().unwrap() = false;
+        assert!(service.reconcile_pending().unwrap() >= 2);
+        assert_eq!(
+            statuses(),
+            (PaymentsStatuses::Succeeded, PaymentsStatuses::Failed)
+        );
+
+        let settled = details();
+        assert_eq!(settled.invoice.paid, Amount::from_minor(60_000));
+        assert_eq!(settled.invoice.status, InvoicesStatuses::PartiallyPaid);
+        assert!(settled.payments.iter().any(|payment| payment.uid == charged
+            && payment.reference == Some(format!("test-{}", charged))));
+
+        // Settled payments aren't counted twice
+        service.reconcile_pending().unwrap();
+        assert_eq!(details().invoice.paid, Amount::from_minor(60_000));
+    }
+}
@@ -0,0 +1,190 @@
+use std::sync::Arc;
+
+use diesel::{insert_into, prelude::*, update};
+use uuid::Uuid;
+
+use super::dto::catalog::{CreateServiceDto, UpdateServiceDto, DEFAULT_CURRENCY};
+use crate::db::{
+    models::service::Service,
+    orm::schema::{auth_data, services, user_profiles},
+    Db, DbError, DbProvider,
+};
+
+#[derive(Debug)]
+pub enum CatalogServiceError {
+    NotLaw,
+    LawNotFound,
+    NotFound,
+    Archived,
+    Query,
+    Insert,
+    Update,
+}
+
+/// Services lawyers offer to clients. Services are archived instead of
+/// deleted, so deals made for them keep pointing at what was agreed on.
+pub struct CatalogService {
+    db: Arc<Db>,
+}
+
+impl CatalogService {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+
+    /// Active services of a lawyer by the `user_profiles` uid
+    pub fn get_law_services(
+        &self,
+        law_uid: &Uuid,
+    ) -> Result<Vec<Service>, DbError<CatalogServiceError>> {
+        self.db.apply(|conn| {
+            let law_exists = diesel::select(diesel::dsl::exists(
+                user_profiles::table
+                    .filter(user_profiles::dsl::uid.eq(law_uid))
+                    .filter(user_profiles::dsl::law_profile.is_not_null())
+                    .filter(user_profiles::dsl::deleted_at.is_null())
+                    .filter(user_profiles::dsl::blocked_at.is_null()),
+            ))
+            .get_result::<bool>(conn)
+            .map_err(|_| CatalogServiceError::Query)?;
+
+            if !law_exists {
+                return Err(CatalogServiceError::LawNotFound);
+            }
+
+            services::table
+                .filter(services::dsl::law_uid.eq(law_uid))
+                .filter(services::dsl::archived_at.is_null())
+                .order((services::dsl::name.asc(), services::dsl::uid.asc()))
+                .load(conn)
+                .map_err(|_| CatalogServiceError::Query)
+        })
+    }
+
+    pub fn get_own(
+        &self,
+        auth_uid: &Uuid,
+        archived: bool,
+    ) -> Result<Vec<Service>, DbError<CatalogServiceError>> {
+        self.db.apply(|conn| {
+            let law_uid = Self::find_law_uid(conn, auth_uid)?;
+            let mut query = services::table
+                .filter(services::dsl::law_uid.eq(law_uid))
+                .into_boxed();
+
+            if !archived {
+                query = query.filter(services::dsl::archived_at.is_null());
+            }
+
+            query
+                .order((services::dsl::name.asc(), services::dsl::uid.asc()))
+                .load(conn)
+                .map_err(|_| CatalogServiceError::Query)
+        })
+    }
+
+    pub fn create(
+        &self,
+        auth_uid: &Uuid,
+        dto: &CreateServiceDto,
+    ) -> Result<Service, DbError<CatalogServiceError>> {
+        self.db.apply(|conn| {
+            let law_uid = Self::find_law_uid(conn, auth_uid)?;
+            let currency = dto.currency.as_deref().unwrap_or(DEFAULT_CURRENCY);
+
+            insert_into(services::dsl::services)
+                .values((
+                    services::dsl::law_uid.eq(law_uid),
+                    services::dsl::name.eq(&dto.name),
+                    services::dsl::cost_minor.eq(dto.cost),
+                    services::dsl::currency.eq(currency),
+                ))
+                .get_result(conn)
+                .map_err(|err| {
+                    log::error!("{}", err);
+                    CatalogServiceError::Insert
+                })
+        })
+    }
+
+    pub fn update(
+        &self,
+        auth_uid: &Uuid,
+        uid: &Uuid,
+        dto: &UpdateServiceDto,
+    ) -> Result<Service, DbError<CatalogServiceError>> {
+        self.db.transaction(|conn| {
+            let law_uid = Self::find_law_uid(conn, auth_uid)?;
+            let service = Self::lock_own(conn, &law_uid, uid)?;
+
+            if service.archived_at.is_some() {
+                return Err(CatalogServiceError::Archived);
+            }
+
+            if dto.is_empty() {
+                return Ok(service);
+            }
+
+            update(services::table.find(service.uid))
+                .set(dto)
+                .get_result(conn)
+                .map_err(|err| {
+                    log::error!("{}", err);
+                    CatalogServiceError::Update
+                })
+        })
+    }
+
+    pub fn archive(
+        &self,
+        auth_uid: &Uuid,
+        uid: &Uuid,
+    ) -> Result<Service, DbError<CatalogServiceError>> {
+        self.db.transaction(|conn| {
+            let law_uid = Self::find_law_uid(conn, auth_uid)?;
+            let service = Self::lock_own(conn, &law_uid, uid)?;
+
+            if service.archived_at.is_some() {
+                return Err(CatalogServiceError::Archived);
+            }
+
+            update(services::table.find(service.uid))
+                .set(services::dsl::archived_at.eq(chrono::Utc::now().naive_utc()))
+                .get_result(conn)
+                .map_err(|err| {
+                    log::error!("{}", err);
+                    CatalogServiceError::Update
+                })
+        })
+    }
+
+    /// Only users holding a law profile have a catalog
+    fn find_law_uid(conn: &mut PgConnection, auth_uid: &Uuid) -> Result<Uuid, CatalogServiceError> {
+        user_profiles::table
+            .inner_join(auth_data::table)
+            .filter(auth_data::dsl::uid.eq(auth_uid))
+            .filter(user_profiles::dsl::law_profile.is_not_null())
+            .filter(user_profiles::dsl::deleted_at.is_null())
+            .select(user_profiles::dsl::uid)
+            .first::<Uuid>(conn)
+            .optional()
+            .map_err(|_| CatalogServiceError::Query)?
+            .ok_or(CatalogServiceError::NotLaw)
+    }
+
+    /// Services of other lawyers look the same as missing ones
+    fn lock_own(
+        conn: &mut PgConnection,
+        law_uid: &Uuid,
+        uid: &Uuid,
+    ) -> Result<Service, CatalogServiceError> {
+        services::table
+            .find(uid)
+            .filter(services::dsl::law_uid.eq(law_uid))
+            .for_update()
+            .first::<Service>(conn)
+            .optional()
+            .map_err(|_| CatalogServiceError::Query)?
+            .ok_or(CatalogServiceError::NotFound)
+    }
+}
@@ -0,0 +1,37 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::db::models::{api_keys::ApiKey, service_accounts::ServiceAccount};
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct ServiceAccountDto {
+    #[validate(length(min = 3, max = 255))]
+    pub name: String,
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct IssueApiKeyDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+
+    #[validate(length(min = 1))]
+    pub scopes: Vec<String>,
+
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+#[derive(Serialize)]
+pub struct ServiceAccountWithKeys {
+    #[serde(flatten)]
+    pub account: ServiceAccount,
+    pub keys: Vec<ApiKey>,
+}
+
+#[derive(Serialize)]
+pub struct IssuedApiKey {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    /// Shown only once
+    pub key: String,
+}
@@ -0,0 +1,15 @@
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Deserialize, Debug)]
+pub struct AuditEventsQuery {
+    pub actor_uid: Option<Uuid>,
+    pub action: Option<String>,
+    pub target_uid: Option<Uuid>,
+    pub outcome: Option<String>,
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>,
+    pub page: Option<u64>,
+    pub per_page: Option<i64>,
+}
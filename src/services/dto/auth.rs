@@ -1,6 +1,8 @@
 use serde::Deserialize;
 use validator::{validate_email, validate_length, Validate, ValidationError};
 
+use super::passport::birth_date;
+
 #[derive(Deserialize, Validate, Debug, Clone)]
 pub struct RegistrationDto {
     #[validate(email)]
@@ -15,7 +17,11 @@ pub struct RegistrationDto {
     pub first_name: String,
     pub second_name: String,
     pub patronymic: Option<String>,
+    #[validate(custom = "birth_date")]
     pub birth_date: chrono::NaiveDate,
+
+    #[validate(length(max = 255))]
+    pub device: Option<String>,
 }
 
 fn email_or_username(value: &str) -> Result<(), ValidationError> {
@@ -37,4 +43,34 @@ pub struct AuthorizationDto {
 
     #[validate(length(min = 8, max = 32))]
     pub password: String,
+
+    #[validate(length(max = 255))]
+    pub device: Option<String>,
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct ChangePasswordDto {
+    #[validate(length(min = 8, max = 32))]
+    pub current_password: String,
+
+    #[validate(length(min = 8, max = 32))]
+    pub new_password: String,
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct ChangeUsernameDto {
+    #[validate(length(min = 8, max = 32))]
+    pub current_password: String,
+
+    #[validate(length(min = 3, max = 255))]
+    pub username: String,
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct ChangeEmailDto {
+    #[validate(length(min = 8, max = 32))]
+    pub current_password: String,
+
+    #[validate(email)]
+    pub email: String,
 }
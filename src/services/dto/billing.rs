@@ -0,0 +1,145 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::db::models::{
+    custom_types::{amount::Amount, invoices_statuses::InvoicesStatuses},
+    invoice_items::InvoiceItem,
+    invoices::Invoice,
+    payments::Payment,
+};
+
+const MAX_INVOICE_ITEMS: usize = 50;
+
+fn invoice_items(items: &[InvoiceItemDto]) -> Result<(), ValidationError> {
+    if !items.is_empty()
+        && items.len() <= MAX_INVOICE_ITEMS
+        && items.iter().all(|item| item.validate().is_ok())
+    {
+        return Ok(());
+    }
+
+    Err(ValidationError::new("invoice_items"))
+}
+
+#[derive(Serialize, Deserialize, Validate, Debug)]
+pub struct InvoiceItemDto {
+    /// Service of the engaged lawyer
+    pub service_uid: Uuid,
+
+    #[validate(range(min = 1, max = 1000))]
+    pub quantity: Option<u32>,
+
+    /// Off the whole line, before taxes
+    pub discount: Option<Amount>,
+
+    /// Basis points, overrides the invoice rate
+    #[validate(range(max = 10000))]
+    pub tax_rate_bp: Option<u32>,
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct CreateInvoiceDto {
+    pub transaction_uid: Uuid,
+
+    /// Defaults to the engaged service at the agreed price
+    #[validate(custom = "invoice_items")]
+    pub items: Option<Vec<InvoiceItemDto>>,
+
+    /// Basis points applied to items without their own rate
+    #[validate(range(max = 10000))]
+    pub tax_rate_bp: Option<u32>,
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct RecordPaymentDto {
+    pub amount: Amount,
+
+    /// Bank transfer id, receipt number and the like
+    #[validate(length(min = 1, max = 255))]
+    pub reference: Option<String>,
+
+    #[validate(length(min = 1, max = 255))]
+    pub note: Option<String>,
+
+    /// Defaults to now
+    pub paid_at: Option<NaiveDateTime>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PayInvoiceDto {
+    /// Defaults to the outstanding amount less pending charges
+    pub amount: Option<Amount>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct InvoicesQuery {
+    pub status: Option<InvoicesStatuses>,
+    pub client_uid: Option<Uuid>,
+    pub page: Option<u64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BalancesQuery {
+    pub client_uid: Option<Uuid>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+pub enum ExportFormat {
+    #[default]
+    #[serde(rename = "json")]
+    Json,
+
+    #[serde(rename = "csv")]
+    Csv,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct InvoicesExportQuery {
+    /// Inclusive, by the issue date
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub status: Option<InvoicesStatuses>,
+    pub format: Option<ExportFormat>,
+}
+
+#[derive(Serialize)]
+pub struct InvoiceDetails {
+    #[serde(flatten)]
+    pub invoice: Invoice,
+    pub outstanding: Amount,
+    pub items: Vec<InvoiceItem>,
+    pub payments: Vec<Payment>,
+}
+
+/// Open invoices of a client in one currency
+#[derive(Serialize)]
+pub struct ClientBalance {
+    pub client_uid: Uuid,
+    pub currency: String,
+    pub invoiced: Amount,
+    pub paid: Amount,
+    pub outstanding: Amount,
+    pub open_invoices: i64,
+}
+
+#[derive(Serialize)]
+pub struct InvoiceExportRow {
+    pub number: i64,
+    pub uid: Uuid,
+    pub transaction_uid: Uuid,
+    pub client_uid: Option<Uuid>,
+    pub law_uid: Option<Uuid>,
+    pub status: InvoicesStatuses,
+    pub currency: String,
+    pub subtotal: Amount,
+    pub discount: Amount,
+    pub tax: Amount,
+    pub total: Amount,
+    pub paid: Amount,
+    pub outstanding: Amount,
+    pub created_at: NaiveDateTime,
+    pub voided_at: Option<NaiveDateTime>,
+}
@@ -0,0 +1,72 @@
+use diesel::AsChangeset;
+use serde::Deserialize;
+use validator::{Validate, ValidationError};
+
+use crate::db::models::custom_types::amount::Amount;
+
+pub const DEFAULT_CURRENCY: &str = "RUB";
+
+/// ISO 4217 codes of currencies with two minor digits, which is all
+/// [`Amount`] can hold. Yen, dinars and the like need their own scale first.
+pub const SUPPORTED_CURRENCIES: [&str; 8] =
+    ["BYN", "CHF", "CNY", "EUR", "GBP", "KZT", "RUB", "USD"];
+
+pub fn currency_code(value: &str) -> Result<(), ValidationError> {
+    if SUPPORTED_CURRENCIES.contains(&value) {
+        return Ok(());
+    }
+
+    Err(ValidationError::new("currency_code"))
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct CreateServiceDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+
+    pub cost: Amount,
+
+    /// Defaults to `DEFAULT_CURRENCY`
+    #[validate(custom = "currency_code")]
+    pub currency: Option<String>,
+}
+
+#[derive(Deserialize, Validate, AsChangeset, Debug)]
+#[diesel(table_name = crate::db::orm::schema::services)]
+pub struct UpdateServiceDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: Option<String>,
+
+    #[diesel(column_name = cost_minor)]
+    pub cost: Option<Amount>,
+
+    #[validate(custom = "currency_code")]
+    pub currency: Option<String>,
+}
+
+impl UpdateServiceDto {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.cost.is_none() && self.currency.is_none()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OwnServicesQuery {
+    /// Archived services are hidden unless asked for
+    pub archived: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_two_digit_currencies_are_accepted() {
+        assert!(currency_code(DEFAULT_CURRENCY).is_ok());
+        assert!(currency_code("USD").is_ok());
+        assert!(currency_code("JPY").is_err());
+        assert!(currency_code("KWD").is_err());
+        assert!(currency_code("usd").is_err());
+        assert!(currency_code("").is_err());
+    }
+}
@@ -0,0 +1,45 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+use validator::{Validate, ValidationError};
+
+use crate::db::models::custom_types::{
+    itn::Itn, law_applications_statuses::LawApplicationsStatuses,
+};
+
+pub fn itn(value: &Itn) -> Result<(), ValidationError> {
+    if value.is_valid() {
+        return Ok(());
+    }
+
+    Err(ValidationError::new("itn"))
+}
+
+fn not_in_future(value: &NaiveDate) -> Result<(), ValidationError> {
+    if *value <= chrono::Utc::now().date_naive() {
+        return Ok(());
+    }
+
+    Err(ValidationError::new("not_in_future"))
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct LawApplicationDto {
+    #[validate(custom = "itn")]
+    pub itn: Itn,
+
+    #[validate(custom = "not_in_future")]
+    pub start_activity_date: NaiveDate,
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct ReviewLawApplicationDto {
+    #[validate(length(min = 1, max = 255))]
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LawApplicationsQuery {
+    pub status: Option<LawApplicationsStatuses>,
+    pub page: Option<u64>,
+    pub per_page: Option<i64>,
+}
@@ -0,0 +1,37 @@
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::db::models::custom_types::law_transaction_statuses::LawTransactionsStatues;
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct CreateLawTransactionDto {
+    /// Active service of the engaged lawyer
+    pub service_uid: Uuid,
+
+    pub court_case_uid: Option<Uuid>,
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct LawTransactionReasonDto {
+    #[validate(length(min = 1, max = 255))]
+    pub reason: Option<String>,
+}
+
+/// Side of the deal the user is on
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LawTransactionParty {
+    #[serde(rename = "client")]
+    Client,
+
+    #[serde(rename = "law")]
+    Law,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LawTransactionsQuery {
+    pub status: Option<LawTransactionsStatues>,
+    pub party: Option<LawTransactionParty>,
+    pub page: Option<u64>,
+    pub per_page: Option<i64>,
+}
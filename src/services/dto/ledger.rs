@@ -0,0 +1,18 @@
+use serde::Serialize;
+
+#[derive(Serialize, Debug)]
+pub struct LedgerBreak {
+    pub seq: i64,
+    pub reason: &'static str,
+}
+
+#[derive(Serialize, Debug)]
+pub struct LedgerVerification {
+    pub valid: bool,
+    pub entries: i64,
+    pub checkpoints: i64,
+    pub last_checkpoint_seq: Option<i64>,
+    /// Everything after the first broken link is untrusted, so the walk
+    /// stops there
+    pub first_broken: Option<LedgerBreak>,
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MfaPendingData {
+    pub uid: Uuid,
+    pub device: Option<String>,
+    pub enrollment: bool,
+}
+
+#[derive(Deserialize, Validate, Debug, Clone)]
+pub struct MfaCodeDto {
+    #[validate(length(min = 6, max = 32))]
+    pub code: String,
+}
+
+#[derive(Deserialize, Validate, Debug, Clone)]
+pub struct MfaPendingDto {
+    #[validate(length(min = 1, max = 64))]
+    pub mfa_token: String,
+}
+
+#[derive(Deserialize, Validate, Debug, Clone)]
+pub struct MfaPendingCodeDto {
+    #[validate(length(min = 1, max = 64))]
+    pub mfa_token: String,
+
+    #[validate(length(min = 6, max = 32))]
+    pub code: String,
+}
+
+#[derive(Serialize)]
+pub struct TotpSetupResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Serialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
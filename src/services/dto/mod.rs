@@ -1,2 +1,14 @@
+pub mod api_keys;
+pub mod audit;
 pub mod auth;
+pub mod billing;
+pub mod catalog;
+pub mod law_application;
+pub mod law_transaction;
+pub mod ledger;
+pub mod mfa;
+pub mod oidc;
+pub mod passport;
+pub mod session;
 pub mod user;
+pub mod verification;
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OidcPendingData {
+    pub verifier: String,
+    pub nonce: String,
+    pub device: Option<String>,
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct OidcAuthorizeDto {
+    #[validate(length(max = 255))]
+    pub device: Option<String>,
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct OidcCallbackDto {
+    #[validate(length(min = 1, max = 2048))]
+    pub code: String,
+
+    #[validate(length(equal = 64))]
+    pub state: String,
+}
+
+#[derive(Serialize)]
+pub struct OidcAuthorizationResponse {
+    pub authorization_url: String,
+}
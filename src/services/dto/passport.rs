@@ -0,0 +1,136 @@
+use chrono::{Months, NaiveDate, NaiveDateTime};
+use diesel::{AsChangeset, Insertable};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+/// Passports are issued from this age
+const MIN_AGE: u32 = 14;
+const MAX_AGE: u32 = 120;
+
+pub fn passport_series(value: &str) -> Result<(), ValidationError> {
+    if value.len() == 4 && value.bytes().all(|c| c.is_ascii_digit()) {
+        return Ok(());
+    }
+
+    Err(ValidationError::new("passport_series"))
+}
+
+pub fn passport_number(value: &str) -> Result<(), ValidationError> {
+    if value.len() == 6 && value.bytes().all(|c| c.is_ascii_digit()) {
+        return Ok(());
+    }
+
+    Err(ValidationError::new("passport_number"))
+}
+
+pub fn birth_date(value: &NaiveDate) -> Result<(), ValidationError> {
+    let today = chrono::Utc::now().date_naive();
+    let latest = today.checked_sub_months(Months::new(MIN_AGE * 12));
+    let earliest = today.checked_sub_months(Months::new(MAX_AGE * 12));
+
+    match (earliest, latest) {
+        (Some(earliest), Some(latest)) if *value > earliest && *value <= latest => Ok(()),
+        _ => Err(ValidationError::new("birth_date")),
+    }
+}
+
+/// Decrypted passport fields
+#[derive(Debug, Clone)]
+pub struct PassportSecrets {
+    pub number: Option<String>,
+    pub series: Option<String>,
+    pub registration_place: Option<String>,
+    pub birthday_date: NaiveDate,
+}
+
+/// Outcome of one re-encryption batch. `last_uid` is where the next batch
+/// starts, `None` once every row was visited.
+pub struct ReencryptionBatch {
+    pub updated: usize,
+    pub failed: Vec<Uuid>,
+    pub last_uid: Option<Uuid>,
+}
+
+#[derive(Insertable, AsChangeset)]
+#[diesel(table_name = crate::db::orm::schema::passports)]
+#[diesel(treat_none_as_null = true)]
+pub struct SealedPassport {
+    pub number: Option<String>,
+    pub series: Option<String>,
+    pub registration_place: Option<String>,
+    pub birthday_date: String,
+    pub number_index: Option<String>,
+    pub data_key: Option<String>,
+    pub key_id: Option<String>,
+}
+
+#[derive(Deserialize, Validate, Debug, Clone)]
+pub struct UpdatePassportDto {
+    #[validate(custom = "passport_series")]
+    pub series: Option<String>,
+
+    #[validate(custom = "passport_number")]
+    pub number: Option<String>,
+
+    #[validate(length(min = 1, max = 255))]
+    pub registration_place: Option<String>,
+
+    #[validate(custom = "birth_date")]
+    pub birthday_date: Option<NaiveDate>,
+}
+
+#[derive(Serialize)]
+pub struct PassportDetails {
+    pub uid: Uuid,
+    pub first_name: String,
+    pub second_name: String,
+    pub patronymic: Option<String>,
+    pub series: Option<String>,
+    pub number: Option<String>,
+    pub registration_place: Option<String>,
+    pub birthday_date: NaiveDate,
+    pub verified_at: Option<NaiveDateTime>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn years_ago(years: u32) -> NaiveDate {
+        chrono::Utc::now()
+            .date_naive()
+            .checked_sub_months(Months::new(years * 12))
+            .unwrap()
+    }
+
+    #[test]
+    fn series_has_four_digits() {
+        assert!(passport_series("1234").is_ok());
+        assert!(passport_series("123").is_err());
+        assert!(passport_series("12345").is_err());
+        assert!(passport_series("12a4").is_err());
+        assert!(passport_series("١٢٣٤").is_err());
+        assert!(passport_series("１２３４").is_err());
+        assert!(passport_series("").is_err());
+    }
+
+    #[test]
+    fn number_has_six_digits() {
+        assert!(passport_number("123456").is_ok());
+        assert!(passport_number("12345").is_err());
+        assert!(passport_number("1234567").is_err());
+        assert!(passport_number(" 12345").is_err());
+        assert!(passport_number("١٢٣٤٥٦").is_err());
+        assert!(passport_number("１２３４５６").is_err());
+    }
+
+    #[test]
+    fn birth_date_is_within_passport_ages() {
+        assert!(birth_date(&years_ago(MIN_AGE)).is_ok());
+        assert!(birth_date(&years_ago(MIN_AGE).succ_opt().unwrap()).is_err());
+        assert!(birth_date(&years_ago(MAX_AGE).succ_opt().unwrap()).is_ok());
+        assert!(birth_date(&years_ago(MAX_AGE)).is_err());
+        assert!(birth_date(&chrono::Utc::now().date_naive()).is_err());
+    }
+}
@@ -1,15 +1,29 @@
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::NaiveDateTime;
 use diesel::Insertable;
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
+use validator::Validate;
+
+use super::{catalog::currency_code, passport::SealedPassport};
+use crate::db::models::{
+    custom_types::{
+        amount::Amount, court_cases_kinds::CourtCasesKinds,
+        court_sides_case_statuses::CourtSidesCaseStatuses, itn::Itn,
+        user_profiles_roles::UserProfilesRoles,
+    },
+    law_profiles::LawProfile,
+    service::Service,
+};
 
 #[derive(Insertable)]
 #[diesel(table_name = crate::db::orm::schema::passports)]
 pub struct PassportOrmData<'a> {
+    pub uid: Uuid,
     pub first_name: &'a str,
     pub second_name: &'a str,
     pub patronymic: Option<&'a str>,
-    pub birthday_date: NaiveDate,
+    #[diesel(embed)]
+    pub sealed: SealedPassport,
 }
 
 #[derive(Serialize)]
@@ -20,11 +34,197 @@ pub struct LawProfileWithUser {
     pub first_name: String,
     pub second_name: String,
     pub patronymic: Option<String>,
-    pub itn: String,
+    pub itn: Itn,
     pub start_activity_date: NaiveDateTime,
 }
 
+#[derive(Serialize, Default)]
+pub struct CaseStats {
+    pub total: i64,
+    pub wins: i64,
+    pub losses: i64,
+    pub unknown: i64,
+}
+
+impl CaseStats {
+    pub fn add(&mut self, status: CourtSidesCaseStatuses) {
+        self.total += 1;
+
+        match status {
+            CourtSidesCaseStatuses::Winning => self.wins += 1,
+            CourtSidesCaseStatuses::Loss => self.losses += 1,
+            CourtSidesCaseStatuses::Unknown => self.unknown += 1,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct CaseKindStats {
+    pub kind: CourtCasesKinds,
+    #[serde(flatten)]
+    pub stats: CaseStats,
+}
+
+#[derive(Serialize)]
+pub struct LawStats {
+    #[serde(flatten)]
+    pub overall: CaseStats,
+    /// Share of wins among decided cases, `None` until one is decided
+    pub win_rate: Option<f64>,
+    pub by_kind: Vec<CaseKindStats>,
+}
+
+#[derive(Serialize)]
+pub struct LawDetails {
+    #[serde(flatten)]
+    pub profile: LawProfileWithUser,
+    pub services: Vec<Service>,
+    pub stats: LawStats,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, Default)]
+pub enum LawsSort {
+    #[default]
+    #[serde(rename = "name")]
+    Name,
+
+    /// Longest practice first
+    #[serde(rename = "experience")]
+    Experience,
+
+    #[serde(rename = "newest")]
+    Newest,
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct LawsQuery {
+    pub page: Option<u64>,
+    pub per_page: Option<i64>,
+
+    /// Part of the first name, second name or patronymic
+    #[validate(length(min = 1, max = 255))]
+    pub name: Option<String>,
+
+    /// Full years since `start_activity_date`
+    pub min_experience: Option<u32>,
+    pub max_experience: Option<u32>,
+
+    /// Part of the name of a service the lawyer offers
+    #[validate(length(min = 1, max = 255))]
+    pub service: Option<String>,
+
+    /// Cost bounds of a matching active service, given as decimal strings
+    pub min_cost: Option<Amount>,
+    pub max_cost: Option<Amount>,
+
+    /// Currency the cost bounds are in
+    #[validate(custom = "currency_code")]
+    pub currency: Option<String>,
+
+    /// Kind of court cases the lawyer took part in
+    pub case_kind: Option<CourtCasesKinds>,
+
+    pub sort: Option<LawsSort>,
+}
+
+#[derive(Serialize)]
+pub struct LawsPage {
+    pub items: Vec<LawProfileWithUser>,
+    pub total: i64,
+    pub page: u64,
+    pub per_page: i64,
+    pub next_page: Option<u64>,
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct DeleteLawsRequestResponse {
     pub uids: Vec<Uuid>,
 }
+
+#[derive(Serialize)]
+pub struct CurrentUserPassport {
+    pub first_name: String,
+    pub second_name: String,
+    pub patronymic: Option<String>,
+    pub verified: bool,
+}
+
+/// Everything the client needs about the caller, instead of decoding the JWT
+#[derive(Serialize)]
+pub struct CurrentUser {
+    pub uid: Uuid,
+    pub username: String,
+    pub email: String,
+    pub email_verified: bool,
+    pub mfa_enabled: bool,
+    pub role: UserProfilesRoles,
+    pub avatar_uid: Option<Uuid>,
+    pub passport: Option<CurrentUserPassport>,
+    pub law_profile: Option<LawProfile>,
+    pub created_at: NaiveDateTime,
+}
+
+/// Username, email and password have their own endpoints since they require
+/// the current password
+#[derive(Deserialize, Validate, Debug)]
+pub struct UpdateCurrentUserDto {
+    #[validate(length(min = 1, max = 255))]
+    pub first_name: Option<String>,
+
+    #[validate(length(min = 1, max = 255))]
+    pub second_name: Option<String>,
+
+    #[validate(length(min = 1, max = 255))]
+    pub patronymic: Option<String>,
+
+    pub avatar_uid: Option<Uuid>,
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct UsersQuery {
+    pub role: Option<UserProfilesRoles>,
+
+    /// Part of the username, email or names
+    #[validate(length(min = 1, max = 255))]
+    pub search: Option<String>,
+
+    pub blocked: Option<bool>,
+
+    /// Deleted profiles are only listed on request
+    pub deleted: Option<bool>,
+
+    pub page: Option<u64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct UserSummary {
+    pub uid: Uuid,
+    pub auth_uid: Option<Uuid>,
+    pub username: Option<String>,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub mfa_enabled: bool,
+    pub role: UserProfilesRoles,
+    pub first_name: Option<String>,
+    pub second_name: Option<String>,
+    pub patronymic: Option<String>,
+    pub passport_verified: bool,
+    pub law_profile: Option<Uuid>,
+    pub blocked_at: Option<NaiveDateTime>,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+pub struct UserDetails {
+    #[serde(flatten)]
+    pub summary: UserSummary,
+    pub avatar_uid: Option<Uuid>,
+    pub law: Option<LawProfile>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ChangeRoleDto {
+    pub role: UserProfilesRoles,
+}
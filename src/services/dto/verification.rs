@@ -0,0 +1,23 @@
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct ForgotPasswordDto {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct ResetPasswordDto {
+    #[validate(length(equal = 64))]
+    pub token: String,
+
+    #[validate(length(min = 8, max = 32))]
+    pub password: String,
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct VerifyEmailDto {
+    #[validate(length(equal = 64))]
+    pub token: String,
+}
@@ -0,0 +1,339 @@
+use std::sync::Arc;
+
+use chrono::NaiveTime;
+use diesel::{insert_into, prelude::*, update};
+use uuid::Uuid;
+
+use super::{
+    dto::law_application::{LawApplicationDto, LawApplicationsQuery},
+    pagination::Pagination,
+};
+use crate::db::{
+    models::{
+        custom_types::{
+            law_applications_statuses::LawApplicationsStatuses,
+            user_profiles_roles::UserProfilesRoles,
+        },
+        law_applications::LawApplication,
+        user_profiles::UserProfile,
+    },
+    orm::schema::{auth_data, law_applications, law_profiles, user_profiles},
+    Db, DbError, DbProvider,
+};
+
+#[derive(Debug)]
+pub enum LawApplicationServiceError {
+    NotFound,
+    AlreadyLaw,
+    NotClient,
+    ItnTaken,
+    Pending,
+    Reviewed,
+    SelfReview,
+    InvalidPage,
+    Query,
+    Insert,
+    Update,
+}
+
+/// Clients apply to become lawyers, employees review the applications. An
+/// approval creates the law profile and switches the role in one transaction.
+/// Staff keep their role, only `user` accounts may apply.
+pub struct LawApplicationService {
+    db: Arc<Db>,
+}
+
+impl LawApplicationService {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+
+    pub fn apply(
+        &self,
+        auth_uid: &Uuid,
+        dto: &LawApplicationDto,
+    ) -> Result<LawApplication, DbError<LawApplicationServiceError>> {
+        self.db.transaction(|conn| {
+            let profile_uid = auth_data::table
+                .find(auth_uid)
+                .select(auth_data::dsl::profile_uid)
+                .first::<Uuid>(conn)
+                .map_err(|_| LawApplicationServiceError::NotFound)?;
+            let profile = Self::lock_profile(conn, &profile_uid)?;
+
+            if profile.law_profile.is_some() || profile.role == UserProfilesRoles::Law {
+                return Err(LawApplicationServiceError::AlreadyLaw);
+            }
+
+            if profile.role != UserProfilesRoles::User {
+                return Err(LawApplicationServiceError::NotClient);
+            }
+
+            let itn_taken = diesel::select(diesel::dsl::exists(
+                law_profiles::table.filter(law_profiles::dsl::itn.eq(&dto.itn)),
+            ))
+            .get_result::<bool>(conn)
+            .map_err(|_| LawApplicationServiceError::Query)?;
+
+            if itn_taken {
+                return Err(LawApplicationServiceError::ItnTaken);
+            }
+
+            insert_into(law_applications::dsl::law_applications)
+                .values((
+                    law_applications::dsl::profile_uid.eq(profile_uid),
+                    law_applications::dsl::itn.eq(&dto.itn),
+                    law_applications::dsl::start_activity_date
+                        .eq(dto.start_activity_date.and_time(NaiveTime::MIN)),
+                ))
+                .get_result(conn)
+                .map_err(|err| match err {
+                    diesel::result::Error::DatabaseError(
+                        diesel::result::DatabaseErrorKind::UniqueViolation,
+                        _,
+                    ) => LawApplicationServiceError::Pending,
+                    err => {
+                        log::error!("{}", err);
+                        LawApplicationServiceError::Insert
+                    }
+                })
+        })
+    }
+
+    pub fn get_own(
+        &self,
+        auth_uid: &Uuid,
+    ) -> Result<Vec<LawApplication>, DbError<LawApplicationServiceError>> {
+        self.db.apply(|conn| {
+            law_applications::table
+                .inner_join(user_profiles::table.inner_join(auth_data::table))
+                .filter(auth_data::dsl::uid.eq(auth_uid))
+                .select(LawApplication::as_select())
+                .order(law_applications::dsl::created_at.desc())
+                .load(conn)
+                .map_err(|_| LawApplicationServiceError::Query)
+        })
+    }
+
+    pub fn get_applications(
+        &self,
+        filters: &LawApplicationsQuery,
+    ) -> Result<Vec<LawApplication>, DbError<LawApplicationServiceError>> {
+        let pagination = Pagination::new(filters.page, filters.per_page)
+            .ok_or(DbError::Execution(LawApplicationServiceError::InvalidPage))?;
+
+        self.db.apply(|conn| {
+            let mut query = law_applications::table.into_boxed();
+
+            if let Some(status) = filters.status {
+                query = query.filter(law_applications::dsl::status.eq(status));
+            }
+
+            query
+                .order(law_applications::dsl::created_at.asc())
+                .offset(pagination.offset)
+                .limit(pagination.per_page)
+                .load(conn)
+                .map_err(|_| LawApplicationServiceError::Query)
+        })
+    }
+
+    /// Returns the application and the `auth_data` uid of the applicant,
+    /// whose tokens still carry the old role
+    pub fn approve(
+        &self,
+        reviewer_uid: &Uuid,
+        application_uid: &Uuid,
+        reason: Option<&str>,
+    ) -> Result<(LawApplication, Option<Uuid>), DbError<LawApplicationServiceError>> {
+        self.db.transaction(|conn| {
+            let (application, applicant_uid) =
+                Self::lock_pending(conn, reviewer_uid, application_uid)?;
+            let profile = Self::lock_profile(conn, &application.profile_uid)?;
+
+            if profile.law_profile.is_some() || profile.role == UserProfilesRoles::Law {
+                return Err(LawApplicationServiceError::AlreadyLaw);
+            }
+
+            // The role may have changed since the application was made
+            if profile.role != UserProfilesRoles::User {
+                return Err(LawApplicationServiceError::NotClient);
+            }
+
+            let law_uid = insert_into(law_profiles::dsl::law_profiles)
+                .values((
+                    law_profiles::dsl::itn.eq(&application.itn),
+                    law_profiles::dsl::start_activity_date.eq(application.start_activity_date),
+                ))
+                .returning(law_profiles::dsl::uid)
+                .get_result::<Uuid>(conn)
+                .map_err(|err| match err {
+                    diesel::result::Error::DatabaseError(
+                        diesel::result::DatabaseErrorKind::UniqueViolation,
+                        _,
+                    ) => LawApplicationServiceError::ItnTaken,
+                    err => {
+                        log::error!("{}", err);
+                        LawApplicationServiceError::Insert
+                    }
+                })?;
+
+            update(user_profiles::table.find(profile.uid))
+                .set((
+                    user_profiles::dsl::law_profile.eq(law_uid),
+                    user_profiles::dsl::role.eq(UserProfilesRoles::Law),
+                ))
+                .execute(conn)
+                .map_err(|err| {
+                    log::error!("{}", err);
+                    LawApplicationServiceError::Update
+                })?;
+
+            let application = Self::close(
+                conn,
+                reviewer_uid,
+                &application.uid,
+                LawApplicationsStatuses::Approved,
+                reason,
+            )?;
+
+            Ok((application, applicant_uid))
+        })
+    }
+
+    pub fn reject(
+        &self,
+        reviewer_uid: &Uuid,
+        application_uid: &Uuid,
+        reason: &str,
+    ) -> Result<LawApplication, DbError<LawApplicationServiceError>> {
+        self.db.transaction(|conn| {
+            let (application, _) = Self::lock_pending(conn, reviewer_uid, application_uid)?;
+
+            Self::close(
+                conn,
+                reviewer_uid,
+                &application.uid,
+                LawApplicationsStatuses::Rejected,
+                Some(reason),
+            )
+        })
+    }
+
+    /// Nobody reviews their own application
+    fn lock_pending(
+        conn: &mut PgConnection,
+        reviewer_uid: &Uuid,
+        application_uid: &Uuid,
+    ) -> Result<(LawApplication, Option<Uuid>), LawApplicationServiceError> {
+        let application = law_applications::table
+            .find(application_uid)
+            .for_update()
+            .first::<LawApplication>(conn)
+            .optional()
+            .map_err(|_| LawApplicationServiceError::Query)?
+            .ok_or(LawApplicationServiceError::NotFound)?;
+
+        if application.status != LawApplicationsStatuses::Pending {
+            return Err(LawApplicationServiceError::Reviewed);
+        }
+
+        let applicant_uid = auth_data::table
+            .filter(auth_data::dsl::profile_uid.eq(application.profile_uid))
+            .select(auth_data::dsl::uid)
+            .first::<Uuid>(conn)
+            .optional()
+            .map_err(|_| LawApplicationServiceError::Query)?;
+
+        if applicant_uid.as_ref() == Some(reviewer_uid) {
+            return Err(LawApplicationServiceError::SelfReview);
+        }
+
+        Ok((application, applicant_uid))
+    }
+
+    fn lock_profile(
+        conn: &mut PgConnection,
+        profile_uid: &Uuid,
+    ) -> Result<UserProfile, LawApplicationServiceError> {
+        user_profiles::table
+            .find(profile_uid)
+            .for_update()
+            .first::<UserProfile>(conn)
+            .optional()
+            .map_err(|_| LawApplicationServiceError::Query)?
+            .filter(|profile| profile.deleted_at.is_none())
+            .ok_or(LawApplicationServiceError::NotFound)
+    }
+
+    fn close(
+        conn: &mut PgConnection,
+        reviewer_uid: &Uuid,
+        application_uid: &Uuid,
+        status: LawApplicationsStatuses,
+        reason: Option<&str>,
+    ) -> Result<LawApplication, LawApplicationServiceError> {
+        update(law_applications::table.find(application_uid))
+            .set((
+                law_applications::dsl::status.eq(status),
+                law_applications::dsl::reason.eq(reason),
+                law_applications::dsl::reviewed_by.eq(reviewer_uid),
+                law_applications::dsl::reviewed_at.eq(chrono::Utc::now().naive_utc()),
+            ))
+            .get_result(conn)
+            .map_err(|err| {
+                log::error!("{}", err);
+                LawApplicationServiceError::Update
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn application() -> LawApplicationDto {
+        serde_json::from_value(serde_json::json!({
+            "itn": "7707083893",
+            "start_activity_date": "2015-03-01",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn only_clients_become_lawyers() {
+        let db = test_support::db();
+        let service = LawApplicationService::new(db.clone());
+        let employee = test_support::create_user(&db, UserProfilesRoles::Employee, None);
+        let user = test_support::create_user(&db, UserProfilesRoles::User, None);
+
+        assert!(matches!(
+            service.apply(&employee.auth_uid, &application()),
+            Err(DbError::Execution(LawApplicationServiceError::NotClient))
+        ));
+
+        let pending = service.apply(&user.auth_uid, &application()).unwrap();
+        db.apply(|conn| {
+            update(user_profiles::table.find(user.profile_uid))
+                .set(user_profiles::dsl::role.eq(UserProfilesRoles::Employee))
+                .execute(conn)
+        })
+        .unwrap();
+
+        assert!(matches!(
+            service.approve(&employee.auth_uid, &pending.uid, None),
+            Err(DbError::Execution(LawApplicationServiceError::NotClient))
+        ));
+        let role = db
+            .apply(|conn| {
+                user_profiles::table
+                    .find(user.profile_uid)
+                    .select(user_profiles::dsl::role)
+                    .first::<UserProfilesRoles>(conn)
+            })
+            .unwrap();
+        assert_eq!(role, UserProfilesRoles::Employee);
+    }
+}
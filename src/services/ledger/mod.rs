@@ -0,0 +1,359 @@
+use std::sync::Arc;
+
+use data_encoding::HEXLOWER;
+use diesel::{insert_into, prelude::*};
+use ring::digest;
+use uuid::Uuid;
+
+use super::{
+    auth::keys::JwtKeys,
+    dto::ledger::{LedgerBreak, LedgerVerification},
+};
+use crate::db::{
+    models::{case_ledger::CaseLedgerEntry, case_ledger_checkpoints::CaseLedgerCheckpoint},
+    orm::schema::{case_ledger, case_ledger_checkpoints},
+    Db, DbError, DbProvider,
+};
+
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+const VERIFY_BATCH_SIZE: i64 = 1000;
+
+pub trait LedgerKeysProvider {
+    fn ledger_keys(&self) -> &JwtKeys;
+}
+
+#[derive(Debug)]
+pub enum LedgerServiceError {
+    Query,
+    Insert,
+    Sign,
+}
+
+/// Read side of the court case ledger. Entries are appended by database
+/// triggers on `court_cases` and `court_sides`, so changes made past the
+/// API are chained as well. Checkpoints sign the head of the chain with the
+/// ledger signing key, a rewritten chain can't reproduce them.
+pub struct LedgerService {
+    db: Arc<Db>,
+}
+
+impl LedgerService {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+
+    pub fn get_case_history(
+        &self,
+        court_case_uid: &Uuid,
+    ) -> Result<Vec<CaseLedgerEntry>, DbError<LedgerServiceError>> {
+        self.db.apply(|conn| {
+            case_ledger::table
+                .filter(case_ledger::dsl::court_case_uid.eq(court_case_uid))
+                .order(case_ledger::dsl::seq.asc())
+                .load(conn)
+                .map_err(|_| LedgerServiceError::Query)
+        })
+    }
+
+    /// Signs the current head unless it is signed already
+    pub fn create_checkpoint(
+        &self,
+        keys_provider: &impl LedgerKeysProvider,
+    ) -> Result<Option<CaseLedgerCheckpoint>, DbError<LedgerServiceError>> {
+        let keys = keys_provider.ledger_keys();
+
+        self.db.apply(|conn| {
+            let head = case_ledger::table
+                .select((case_ledger::dsl::seq, case_ledger::dsl::entry_hash))
+                .order(case_ledger::dsl::seq.desc())
+                .first::<(i64, String)>(conn)
+                .optional()
+                .map_err(|_| LedgerServiceError::Query)?;
+
+            let (seq, entry_hash) = match head {
+                Some(head) => head,
+                None => return Ok(None),
+            };
+
+            let signature = keys
+                .sign(Self::checkpoint_message(seq, &entry_hash).as_bytes())
+                .map_err(|_| LedgerServiceError::Sign)?;
+
+            insert_into(case_ledger_checkpoints::dsl::case_ledger_checkpoints)
+                .values((
+                    case_ledger_checkpoints::dsl::seq.eq(seq),
+                    case_ledger_checkpoints::dsl::entry_hash.eq(&entry_hash),
+                    case_ledger_checkpoints::dsl::key_id.eq(keys.signing_kid()),
+                    case_ledger_checkpoints::dsl::signature.eq(&signature),
+                ))
+                .on_conflict_do_nothing()
+                .get_result(conn)
+                .optional()
+                .map_err(|err| {
+                    log::error!("{}", err);
+                    LedgerServiceError::Insert
+                })
+        })
+    }
+
+    /// Walks the whole chain in order and reports the first broken link:
+    /// a changed entry, a missing one, or a checkpoint that doesn't match
+    /// the chain or its signature
+    pub fn verify(
+        &self,
+        keys_provider: &impl LedgerKeysProvider,
+    ) -> Result<LedgerVerification, DbError<LedgerServiceError>> {
+        let keys = keys_provider.ledger_keys();
+
+        self.db.apply(|conn| Self::verify_chain(conn, keys))
+    }
+
+    fn verify_chain(
+        conn: &mut PgConnection,
+        keys: &JwtKeys,
+    ) -> Result<LedgerVerification, LedgerServiceError> {
+        let checkpoints = case_ledger_checkpoints::table
+            .order(case_ledger_checkpoints::dsl::seq.asc())
+            .load::<CaseLedgerCheckpoint>(conn)
+            .map_err(|_| LedgerServiceError::Query)?;
+        let mut pending_checkpoints = checkpoints.iter().peekable();
+        let mut result = LedgerVerification {
+            valid: true,
+            entries: 0,
+            checkpoints: checkpoints.len() as i64,
+            last_checkpoint_seq: checkpoints.last().map(|checkpoint| checkpoint.seq),
+            first_broken: None,
+        };
+        let mut last_seq = 0;
+        let mut last_hash = GENESIS_HASH.to_owned();
+
+        'walk: loop {
+            let entries = case_ledger::table
+                .filter(case_ledger::dsl::seq.gt(last_seq))
+                .order(case_ledger::dsl::seq.asc())
+                .limit(VERIFY_BATCH_SIZE)
+                .load::<CaseLedgerEntry>(conn)
+                .map_err(|_| LedgerServiceError::Query)?;
+
+            if entries.is_empty() {
+                break;
+            }
+
+            for entry in entries {
+                let broken = if entry.seq != last_seq + 1 {
+                    Some("entry_missing")
+                } else if entry.prev_hash != last_hash {
+                    Some("chain_broken")
+                } else if entry.entry_hash != Self::entry_hash(&entry) {
+                    Some("hash_mismatch")
+                } else {
+                    None
+                };
+
+                if let Some(reason) = broken {
+                    result.first_broken = Some(LedgerBreak {
+                        seq: last_seq + 1,
+                        reason,
+                    });
+                    break 'walk;
+                }
+
+                while let Some(checkpoint) =
+                    pending_checkpoints.next_if(|checkpoint| checkpoint.seq <= entry.seq)
+                {
+                    if let Some(reason) = Self::check_checkpoint(keys, checkpoint, &entry) {
+                        result.first_broken = Some(LedgerBreak {
+                            seq: checkpoint.seq,
+                            reason,
+                        });
+                        break 'walk;
+                    }
+                }
+
+                result.entries += 1;
+                last_seq = entry.seq;
+                last_hash = entry.entry_hash;
+            }
+        }
+
+        // Signed entries cut off from the end of the chain
+        if result.first_broken.is_none() {
+            result.first_broken = pending_checkpoints.next().map(|checkpoint| LedgerBreak {
+                seq: checkpoint.seq,
+                reason: "entry_missing",
+            });
+        }
+
+        result.valid = result.first_broken.is_none();
+
+        Ok(result)
+    }
+
+    fn check_checkpoint(
+        keys: &JwtKeys,
+        checkpoint: &CaseLedgerCheckpoint,
+        entry: &CaseLedgerEntry,
+    ) -> Option<&'static str> {
+        let message = Self::checkpoint_message(checkpoint.seq, &checkpoint.entry_hash);
+
+        if !keys.verify(
+            checkpoint.key_id.as_deref(),
+            &checkpoint.signature,
+            message.as_bytes(),
+        ) {
+            return Some("checkpoint_signature_invalid");
+        }
+
+        if checkpoint.seq != entry.seq || checkpoint.entry_hash != entry.entry_hash {
+            return Some("checkpoint_mismatch");
+        }
+
+        None
+    }
+
+    /// Same input as `case_ledger_append` in the database. Every column is
+    /// NOT NULL, so `concat_ws` there never skips a field.
+    fn entry_hash(entry: &CaseLedgerEntry) -> String {
+        let input = [
+            entry.seq.to_string(),
+            entry.prev_hash.clone(),
+            entry.entity.clone(),
+            entry.entity_uid.to_string(),
+            entry.court_case_uid.to_string(),
+            entry.operation.clone(),
+            entry.payload.clone(),
+            entry.created_at.format("%Y-%m-%dT%H:%M:%S%.6f").to_string(),
+        ]
+        .join("|");
+
+        HEXLOWER.encode(digest::digest(&digest::SHA256, input.as_bytes()).as_ref())
+    }
+
+    fn checkpoint_message(seq: i64, entry_hash: &str) -> String {
+        format!("case_ledger:{}:{}", seq, entry_hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use diesel::{sql_query, update};
+
+    use super::*;
+    use crate::{
+        db::{
+            models::custom_types::{
+                court_cases_decisions::CourtCasesDecisions, court_cases_kinds::CourtCasesKinds,
+            },
+            orm::schema::court_cases,
+        },
+        test_support::{self, TestConfig},
+    };
+
+    /// Takes a ledger entry for each change
+    fn create_court_case(conn: &mut PgConnection) -> QueryResult<Uuid> {
+        let uid = insert_into(court_cases::table)
+            .values((
+                court_cases::dsl::number.eq(Uuid::new_v4().simple().to_string()),
+                court_cases::dsl::judge_fullname.eq("Judge"),
+                court_cases::dsl::decision.eq(CourtCasesDecisions::Processing),
+                court_cases::dsl::kind.eq(CourtCasesKinds::Civil),
+            ))
+            .returning(court_cases::dsl::uid)
+            .get_result::<Uuid>(conn)?;
+
+        update(court_cases::table.find(uid))
+            .set(court_cases::dsl::decision.eq(CourtCasesDecisions::Complete))
+            .execute(conn)?;
+
+        Ok(uid)
+    }
+
+    /// Computed by `case_ledger_append` in postgres
+    #[test]
+    fn entry_hash_matches_the_database() {
+        let uid = Uuid::parse_str("6f1c2a9e-3b4d-4e5f-8a7b-9c0d1e2f3a4b").unwrap();
+        let entry = CaseLedgerEntry {
+            seq: 1,
+            entity: "court_case".to_owned(),
+            entity_uid: uid,
+            court_case_uid: uid,
+            operation: "insert".to_owned(),
+            payload: r#"{"number" : "А-1|2", "judge_fullname" : "Иванов"}"#.to_owned(),
+            prev_hash: GENESIS_HASH.to_owned(),
+            entry_hash: String::new(),
+            created_at: NaiveDate::from_ymd_opt(2026, 10, 18)
+                .unwrap()
+                .and_hms_micro_opt(9, 5, 3, 789)
+                .unwrap(),
+        };
+
+        assert_eq!(
+            LedgerService::entry_hash(&entry),
+            "a0c4d8328578d41c3a82adca8efa000cff493049f72c4d1d9ac9c1e873fafdbf"
+        );
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn appended_entries_verify() {
+        let db = test_support::db();
+        let service = LedgerService::new(db.clone());
+        let court_case_uid = db.apply(create_court_case).unwrap();
+
+        let history = service.get_case_history(&court_case_uid).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].prev_hash, history[0].entry_hash);
+        assert!(history
+            .iter()
+            .all(|entry| entry.entry_hash == LedgerService::entry_hash(entry)));
+
+        service.create_checkpoint(&TestConfig).unwrap();
+
+        let verification = service.verify(&TestConfig).unwrap();
+        assert!(verification.valid, "{:?}", verification.first_broken);
+        assert!(verification.entries >= history[1].seq);
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn changed_entry_is_reported() {
+        let db = test_support::db();
+
+        // Rolled back, so the shared chain stays intact for other tests
+        let verification = db
+            .apply(|conn| {
+                Ok::<_, ()>(conn.test_transaction(|conn| {
+                    let court_case_uid = create_court_case(conn)?;
+                    let seq = case_ledger::table
+                        .filter(case_ledger::dsl::court_case_uid.eq(court_case_uid))
+                        .select(case_ledger::dsl::seq)
+                        .order(case_ledger::dsl::seq.asc())
+                        .first::<i64>(conn)?;
+
+                    sql_query("ALTER TABLE case_ledger DISABLE TRIGGER case_ledger_append_only")
+                        .execute(conn)?;
+                    update(case_ledger::table.find(seq))
+                        .set(case_ledger::dsl::payload.eq(r#"{"number" : "forged"}"#))
+                        .execute(conn)?;
+
+                    let verification = LedgerService::verify_chain(conn, TestConfig.ledger_keys())
+                        .map_err(|_| diesel::result::Error::RollbackTransaction)?;
+
+                    Ok::<_, diesel::result::Error>((seq, verification))
+                }))
+            })
+            .unwrap();
+        let (seq, verification) = verification;
+
+        assert!(!verification.valid);
+        assert_eq!(verification.entries, seq - 1);
+        assert!(matches!(
+            verification.first_broken,
+            Some(LedgerBreak {
+                seq: broken,
+                reason: "hash_mismatch",
+            }) if broken == seq
+        ));
+    }
+}
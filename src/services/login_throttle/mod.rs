@@ -0,0 +1,152 @@
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use super::auth::{LoginAttemptsError, LoginAttemptsGuard};
+use crate::cache::Cache;
+
+const FAILURES_WINDOW_MINUTES: i64 = 60;
+const LOCK_MINUTES: i64 = 30;
+const MAX_BACKOFF_SECONDS: u32 = 300;
+
+const ACCOUNT_BACKOFF_THRESHOLD: i64 = 3;
+const ACCOUNT_LOCK_THRESHOLD: i64 = 10;
+const IP_BACKOFF_THRESHOLD: i64 = 10;
+const IP_LOCK_THRESHOLD: i64 = 100;
+
+/// Counts failed logins per account and per IP. Every failure past the
+/// backoff threshold doubles the delay before the next attempt is accepted,
+/// reaching the lock threshold blocks the subject for `LOCK_MINUTES`.
+pub struct LoginThrottleService {
+    redis: Arc<Cache>,
+}
+
+impl LoginThrottleService {
+    pub fn new(redis: Arc<Cache>) -> Self {
+        Self { redis }
+    }
+
+    pub fn check_ip(&self, ip: &str) -> Result<(), LoginAttemptsError> {
+        self.check_subject(&Self::ip_subject(ip))
+    }
+
+    pub fn ip_failed(&self, ip: &str) {
+        self.register_failure(
+            &Self::ip_subject(ip),
+            IP_BACKOFF_THRESHOLD,
+            IP_LOCK_THRESHOLD,
+        );
+    }
+
+    pub fn unlock_account(&self, uid: &Uuid) -> Result<(), LoginAttemptsError> {
+        let subject = Self::account_subject(uid);
+
+        for key in [
+            Self::failures_key(&subject),
+            Self::backoff_key(&subject),
+            Self::lock_key(&subject),
+        ] {
+            self.redis
+                .remove(&key)
+                .map_err(|_| LoginAttemptsError::Storage)?;
+        }
+
+        Ok(())
+    }
+
+    fn check_subject(&self, subject: &str) -> Result<(), LoginAttemptsError> {
+        let locked = self
+            .redis
+            .get_pair(&Self::lock_key(subject))
+            .map_err(|_| LoginAttemptsError::Storage)?;
+
+        if locked.is_some() {
+            return Err(LoginAttemptsError::Locked);
+        }
+
+        let backoff = self
+            .redis
+            .get_pair(&Self::backoff_key(subject))
+            .map_err(|_| LoginAttemptsError::Storage)?;
+
+        if backoff.is_some() {
+            return Err(LoginAttemptsError::Backoff);
+        }
+
+        Ok(())
+    }
+
+    fn register_failure(&self, subject: &str, backoff_threshold: i64, lock_threshold: i64) {
+        let now = chrono::Utc::now();
+        let failures = self.redis.increment(
+            &Self::failures_key(subject),
+            (now + chrono::Duration::minutes(FAILURES_WINDOW_MINUTES)).timestamp() as usize,
+        );
+
+        let failures = match failures {
+            Ok(failures) => failures,
+            Err(_) => return,
+        };
+
+        if failures >= lock_threshold {
+            log::warn!("Security event: login locked for {} after {} failures", subject, failures);
+
+            let _ = self.redis.add_pair(
+                &Self::lock_key(subject),
+                "1",
+                (now + chrono::Duration::minutes(LOCK_MINUTES)).timestamp() as usize,
+            );
+        } else if failures >= backoff_threshold {
+            let delay = 2_u32
+                .saturating_pow((failures - backoff_threshold) as u32)
+                .min(MAX_BACKOFF_SECONDS);
+
+            let _ = self.redis.add_pair(
+                &Self::backoff_key(subject),
+                "1",
+                (now + chrono::Duration::seconds(delay.into())).timestamp() as usize,
+            );
+        }
+    }
+
+    fn account_subject(uid: &Uuid) -> String {
+        format!("account:{}", uid)
+    }
+
+    fn ip_subject(ip: &str) -> String {
+        format!("ip:{}", ip)
+    }
+
+    fn failures_key(subject: &str) -> String {
+        format!("login_failures:{}", subject)
+    }
+
+    fn backoff_key(subject: &str) -> String {
+        format!("login_backoff:{}", subject)
+    }
+
+    fn lock_key(subject: &str) -> String {
+        format!("login_lock:{}", subject)
+    }
+}
+
+impl LoginAttemptsGuard for LoginThrottleService {
+    fn check(&self, uid: &Uuid) -> Result<(), LoginAttemptsError> {
+        self.check_subject(&Self::account_subject(uid))
+    }
+
+    fn failed(&self, uid: &Uuid) {
+        self.register_failure(
+            &Self::account_subject(uid),
+            ACCOUNT_BACKOFF_THRESHOLD,
+            ACCOUNT_LOCK_THRESHOLD,
+        );
+    }
+
+    fn succeeded(&self, uid: &Uuid) {
+        let subject = Self::account_subject(uid);
+
+        let _ = self.redis.remove(&Self::failures_key(&subject));
+        let _ = self.redis.remove(&Self::backoff_key(&subject));
+    }
+}
@@ -0,0 +1,42 @@
+use std::{fs, path::PathBuf};
+
+use uuid::Uuid;
+
+use super::{Mail, Mailer, MailerError};
+
+/// Stand-in for local development and tests: every email is written to its
+/// own file in `dir` instead of being sent.
+pub struct FileMailer {
+    dir: PathBuf,
+}
+
+impl FileMailer {
+    pub fn new(dir: &str) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl Mailer for FileMailer {
+    fn send(&self, mail: &Mail) -> Result<(), MailerError> {
+        let path = self.dir.join(format!(
+            "{}-{}.eml",
+            chrono::Utc::now().format("%Y%m%d%H%M%S"),
+            Uuid::new_v4()
+        ));
+        let content = format!(
+            "To: {}\nSubject: {}\n\n{}\n",
+            mail.to, mail.subject, mail.body
+        );
+
+        fs::create_dir_all(&self.dir)
+            .and_then(|_| fs::write(&path, content))
+            .map_err(|err| {
+                log::error!("{}: {}", path.display(), err);
+                MailerError::Send
+            })?;
+
+        log::info!("Email \"{}\" to {} written to {}", mail.subject, mail.to, path.display());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+mod file;
+mod smtp;
+
+pub use file::FileMailer;
+pub use smtp::SmtpMailer;
+
+#[derive(Debug)]
+pub enum MailerError {
+    InvalidAddress,
+    Build,
+    Send,
+}
+
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Sends plain text emails. Implementations block, so they are called from
+/// `web::block` like the database.
+pub trait Mailer: Send + Sync {
+    fn send(&self, mail: &Mail) -> Result<(), MailerError>;
+}
@@ -0,0 +1,149 @@
+use ring::{aead::LessSafeKey, rand::SystemRandom};
+use uuid::Uuid;
+
+use crate::services::aead;
+
+#[derive(Debug)]
+pub enum TotpKeysError {
+    InvalidKey,
+    UnknownKey,
+    Encrypt,
+    Decrypt,
+}
+
+/// Encryption of TOTP secrets at rest. Secrets are sealed with one of the
+/// master keys, bound to their account, and stored next to the id of the
+/// key, so keys can be rotated the same way passport keys are.
+pub struct TotpKeys {
+    current_kid: String,
+    master_keys: Vec<(String, LessSafeKey)>,
+    rng: SystemRandom,
+}
+
+impl TotpKeys {
+    /// `master_keys` are `(kid, key)` pairs of 32 byte keys. Retired keys
+    /// stay there until every secret is sealed with the current one.
+    pub fn new(
+        current_kid: &str,
+        master_keys: &[(String, Vec<u8>)],
+    ) -> Result<Self, TotpKeysError> {
+        let master_keys = master_keys
+            .iter()
+            .map(|(kid, key)| {
+                aead::key(key)
+                    .map(|key| (kid.clone(), key))
+                    .map_err(|_| TotpKeysError::InvalidKey)
+            })
+            .collect::<Result<Vec<(String, LessSafeKey)>, TotpKeysError>>()?;
+
+        if !master_keys.iter().any(|(kid, _)| kid == current_kid) {
+            return Err(TotpKeysError::UnknownKey);
+        }
+
+        Ok(Self {
+            current_kid: current_kid.to_owned(),
+            master_keys,
+            rng: SystemRandom::new(),
+        })
+    }
+
+    pub fn current_kid(&self) -> &str {
+        &self.current_kid
+    }
+
+    /// Seals the secret under the current master key
+    pub fn seal(&self, uid: &Uuid, secret: &str) -> Result<String, TotpKeysError> {
+        aead::encrypt(
+            &self.rng,
+            self.master_key(&self.current_kid)?,
+            &Self::aad(uid),
+            secret.as_bytes(),
+        )
+        .map_err(|_| TotpKeysError::Encrypt)
+    }
+
+    /// Secrets without a key id were stored before encryption and are read
+    /// as they are
+    pub fn open(
+        &self,
+        uid: &Uuid,
+        secret: &str,
+        kid: Option<&str>,
+    ) -> Result<String, TotpKeysError> {
+        let kid = match kid {
+            Some(kid) => kid,
+            None => return Ok(secret.to_owned()),
+        };
+
+        aead::decrypt(self.master_key(kid)?, &Self::aad(uid), secret)
+            .ok()
+            .and_then(|plaintext| String::from_utf8(plaintext).ok())
+            .ok_or(TotpKeysError::Decrypt)
+    }
+
+    fn master_key(&self, kid: &str) -> Result<&LessSafeKey, TotpKeysError> {
+        self.master_keys
+            .iter()
+            .find(|(master_kid, _)| master_kid == kid)
+            .map(|(_, key)| key)
+            .ok_or(TotpKeysError::UnknownKey)
+    }
+
+    fn aad(uid: &Uuid) -> String {
+        format!("auth_data:{}:totp_secret", uid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(current_kid: &str) -> TotpKeys {
+        TotpKeys::new(
+            current_kid,
+            &[
+                ("old".to_owned(), vec![1; 32]),
+                ("new".to_owned(), vec![2; 32]),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn sealed_secret_opens_for_its_account_only() {
+        let keys = keys("new");
+        let uid = Uuid::new_v4();
+        let sealed = keys.seal(&uid, "JBSWY3DPEHPK3PXP").unwrap();
+
+        assert_ne!(sealed, "JBSWY3DPEHPK3PXP");
+        assert_eq!(
+            keys.open(&uid, &sealed, Some("new")).unwrap(),
+            "JBSWY3DPEHPK3PXP"
+        );
+        assert!(keys.open(&Uuid::new_v4(), &sealed, Some("new")).is_err());
+        assert!(keys.open(&uid, &sealed, Some("old")).is_err());
+        assert!(keys.open(&uid, &sealed, Some("retired")).is_err());
+    }
+
+    #[test]
+    fn secret_without_key_id_is_plaintext() {
+        let uid = Uuid::new_v4();
+
+        assert_eq!(
+            keys("new").open(&uid, "JBSWY3DPEHPK3PXP", None).unwrap(),
+            "JBSWY3DPEHPK3PXP"
+        );
+    }
+
+    #[test]
+    fn current_key_must_be_known() {
+        assert!(matches!(
+            TotpKeys::new("missing", &[("old".to_owned(), vec![1; 32])]),
+            Err(TotpKeysError::UnknownKey)
+        ));
+        assert!(matches!(
+            TotpKeys::new("short", &[("short".to_owned(), vec![1; 16])]),
+            Err(TotpKeysError::InvalidKey)
+        ));
+    }
+}
@@ -0,0 +1,372 @@
+pub mod keys;
+pub mod totp;
+
+use std::sync::Arc;
+
+use data_encoding::{BASE32_NOPAD, HEXLOWER};
+use diesel::{delete, insert_into, prelude::*, update};
+use rand::RngCore;
+use ring::digest;
+use uuid::Uuid;
+
+use self::keys::TotpKeys;
+use super::dto::mfa::{MfaPendingData, TotpSetupResponse};
+use crate::{
+    cache::Cache,
+    db::{
+        models::{auth_data::AuthData, custom_types::user_profiles_roles::UserProfilesRoles},
+        orm::schema::{auth_data, mfa_recovery_codes},
+        Db, DbError, DbProvider,
+    },
+};
+
+const RECOVERY_CODES_COUNT: usize = 10;
+const PENDING_LIFETIME_MINUTES: i64 = 5;
+
+#[derive(Debug)]
+pub enum MfaServiceError {
+    UserNotFound,
+    AlreadyEnabled,
+    NotEnabled,
+    SetupNotStarted,
+    InvalidCode,
+    Crypto,
+    Cache,
+    Update,
+}
+
+pub trait MfaPolicyProvider {
+    fn mfa_issuer(&self) -> &str;
+    fn mfa_required_for(&self, role: UserProfilesRoles) -> bool;
+}
+
+pub trait TotpKeysProvider {
+    fn totp_keys(&self) -> &TotpKeys;
+}
+
+/// TOTP second factor with single-use recovery codes. Logins that need the
+/// second factor are parked as pending under an opaque short-lived token.
+pub struct MfaService {
+    db: Arc<Db>,
+    redis: Arc<Cache>,
+}
+
+impl MfaService {
+    pub fn new(db: Arc<Db>, redis: Arc<Cache>) -> Self {
+        Self { db, redis }
+    }
+
+    pub fn start_setup(
+        &self,
+        uid: &Uuid,
+        issuer: &str,
+        keys_provider: &impl TotpKeysProvider,
+    ) -> Result<TotpSetupResponse, DbError<MfaServiceError>> {
+        let keys = keys_provider.totp_keys();
+
+        self.db.apply(|conn| {
+            let data = Self::find_auth_data(conn, uid)?;
+
+            if data.totp_enabled {
+                return Err(MfaServiceError::AlreadyEnabled);
+            }
+
+            let secret = totp::generate_secret();
+
+            Self::store_secret(conn, keys, uid, &secret)?;
+
+            Ok(TotpSetupResponse {
+                otpauth_uri: totp::otpauth_uri(issuer, &data.email, &secret),
+                secret,
+            })
+        })
+    }
+
+    pub fn confirm_setup(
+        &self,
+        uid: &Uuid,
+        code: &str,
+        keys_provider: &impl TotpKeysProvider,
+    ) -> Result<Vec<String>, DbError<MfaServiceError>> {
+        let keys = keys_provider.totp_keys();
+
+        self.db.transaction(|conn| {
+            let data = Self::find_auth_data(conn, uid)?;
+
+            if data.totp_enabled {
+                return Err(MfaServiceError::AlreadyEnabled);
+            }
+
+            if data.totp_secret.is_none() {
+                return Err(MfaServiceError::SetupNotStarted);
+            }
+
+            let secret = Self::open_secret(keys, &data)?;
+
+            self.check_totp(uid, &secret, code)?;
+
+            update(auth_data::dsl::auth_data.find(uid))
+                .set(auth_data::dsl::totp_enabled.eq(true))
+                .execute(conn)
+                .map_err(|_| MfaServiceError::Update)?;
+
+            Self::replace_recovery_codes(conn, uid)
+        })
+    }
+
+    pub fn disable(
+        &self,
+        uid: &Uuid,
+        code: &str,
+        keys_provider: &impl TotpKeysProvider,
+    ) -> Result<(), DbError<MfaServiceError>> {
+        let keys = keys_provider.totp_keys();
+
+        self.db.transaction(|conn| {
+            self.check_code(conn, keys, uid, code)?;
+
+            update(auth_data::dsl::auth_data.find(uid))
+                .set((
+                    auth_data::dsl::totp_secret.eq(None::<String>),
+                    auth_data::dsl::totp_key_id.eq(None::<String>),
+                    auth_data::dsl::totp_enabled.eq(false),
+                ))
+                .execute(conn)
+                .map_err(|_| MfaServiceError::Update)?;
+            delete(mfa_recovery_codes::dsl::mfa_recovery_codes)
+                .filter(mfa_recovery_codes::dsl::auth_uid.eq(uid))
+                .execute(conn)
+                .map_err(|_| MfaServiceError::Update)?;
+
+            Ok(())
+        })
+    }
+
+    pub fn regenerate_recovery_codes(
+        &self,
+        uid: &Uuid,
+        code: &str,
+        keys_provider: &impl TotpKeysProvider,
+    ) -> Result<Vec<String>, DbError<MfaServiceError>> {
+        let keys = keys_provider.totp_keys();
+
+        self.db.transaction(|conn| {
+            self.check_code(conn, keys, uid, code)?;
+
+            Self::replace_recovery_codes(conn, uid)
+        })
+    }
+
+    /// Accepts either a TOTP code or one of the unused recovery codes
+    pub fn verify(
+        &self,
+        uid: &Uuid,
+        code: &str,
+        keys_provider: &impl TotpKeysProvider,
+    ) -> Result<(), DbError<MfaServiceError>> {
+        let keys = keys_provider.totp_keys();
+
+        self.db
+            .transaction(|conn| self.check_code(conn, keys, uid, code))
+    }
+
+    pub fn create_pending(&self, pending: &MfaPendingData) -> Result<String, MfaServiceError> {
+        let mut token = [0_u8; 32];
+
+        rand::thread_rng().fill_bytes(&mut token);
+
+        let token = HEXLOWER.encode(&token);
+        let value = serde_json::to_string(pending).map_err(|_| MfaServiceError::Cache)?;
+        let ttl = (chrono::Utc::now() + chrono::Duration::minutes(PENDING_LIFETIME_MINUTES))
+            .timestamp() as usize;
+
+        self.redis
+            .add_pair(&Self::pending_key(&token), &value, ttl)
+            .map_err(|_| MfaServiceError::Cache)?;
+
+        Ok(token)
+    }
+
+    pub fn find_pending(&self, token: &str) -> Result<Option<MfaPendingData>, MfaServiceError> {
+        let value = self
+            .redis
+            .get_pair(&Self::pending_key(token))
+            .map_err(|_| MfaServiceError::Cache)?;
+
+        match value {
+            Some(value) => serde_json::from_str(&value)
+                .map(Some)
+                .map_err(|_| MfaServiceError::Cache),
+            None => Ok(None),
+        }
+    }
+
+    pub fn remove_pending(&self, token: &str) -> Result<(), MfaServiceError> {
+        self.redis
+            .remove(&Self::pending_key(token))
+            .map_err(|_| MfaServiceError::Cache)
+    }
+
+    fn check_code(
+        &self,
+        conn: &mut PgConnection,
+        keys: &TotpKeys,
+        uid: &Uuid,
+        code: &str,
+    ) -> Result<(), MfaServiceError> {
+        let data = Self::find_auth_data(conn, uid)?;
+
+        if !data.totp_enabled || data.totp_secret.is_none() {
+            return Err(MfaServiceError::NotEnabled);
+        }
+
+        let secret = Self::open_secret(keys, &data)?;
+
+        match self.check_totp(uid, &secret, code) {
+            Err(MfaServiceError::InvalidCode) => Self::use_recovery_code(conn, uid, code),
+            Ok(()) if data.totp_key_id.as_deref() != Some(keys.current_kid()) => {
+                Self::store_secret(conn, keys, uid, &secret)
+            }
+            result => result,
+        }
+    }
+
+    fn open_secret(keys: &TotpKeys, data: &AuthData) -> Result<String, MfaServiceError> {
+        let secret = data
+            .totp_secret
+            .as_deref()
+            .ok_or(MfaServiceError::NotEnabled)?;
+
+        keys.open(&data.uid, secret, data.totp_key_id.as_deref())
+            .map_err(|err| {
+                log::error!("TOTP secret of {}: {:?}", data.uid, err);
+                MfaServiceError::Crypto
+            })
+    }
+
+    /// Plaintext secrets and those under a retired key are sealed again
+    /// with the current key once they are used
+    fn store_secret(
+        conn: &mut PgConnection,
+        keys: &TotpKeys,
+        uid: &Uuid,
+        secret: &str,
+    ) -> Result<(), MfaServiceError> {
+        let sealed = keys
+            .seal(uid, secret)
+            .map_err(|_| MfaServiceError::Crypto)?;
+
+        update(auth_data::dsl::auth_data.find(uid))
+            .set((
+                auth_data::dsl::totp_secret.eq(sealed),
+                auth_data::dsl::totp_key_id.eq(keys.current_kid()),
+            ))
+            .execute(conn)
+            .map_err(|_| MfaServiceError::Update)?;
+
+        Ok(())
+    }
+
+    /// A code is accepted once, later steps only, so an intercepted code
+    /// can't be replayed within its validity window.
+    fn check_totp(&self, uid: &Uuid, secret: &str, code: &str) -> Result<(), MfaServiceError> {
+        let key = format!("totp_last_step:{}", uid);
+        let last_step = self
+            .redis
+            .get_pair(&key)
+            .map_err(|_| MfaServiceError::Cache)?
+            .and_then(|value| value.parse::<u64>().ok());
+        let step = totp::verify(secret, code, totp::current_step())
+            .filter(|step| last_step.is_none_or(|last_step| *step > last_step))
+            .ok_or(MfaServiceError::InvalidCode)?;
+        let ttl = (chrono::Utc::now() + chrono::Duration::minutes(PENDING_LIFETIME_MINUTES))
+            .timestamp() as usize;
+
+        self.redis
+            .add_pair(&key, &step.to_string(), ttl)
+            .map_err(|_| MfaServiceError::Cache)?;
+
+        Ok(())
+    }
+
+    fn use_recovery_code(
+        conn: &mut PgConnection,
+        uid: &Uuid,
+        code: &str,
+    ) -> Result<(), MfaServiceError> {
+        let used = update(mfa_recovery_codes::dsl::mfa_recovery_codes)
+            .filter(mfa_recovery_codes::dsl::auth_uid.eq(uid))
+            .filter(mfa_recovery_codes::dsl::code_hash.eq(Self::hash_recovery_code(code)))
+            .filter(mfa_recovery_codes::dsl::used_at.is_null())
+            .set(mfa_recovery_codes::dsl::used_at.eq(chrono::Utc::now().naive_utc()))
+            .execute(conn)
+            .map_err(|_| MfaServiceError::Update)?;
+
+        if used == 0 {
+            return Err(MfaServiceError::InvalidCode);
+        }
+
+        Ok(())
+    }
+
+    fn replace_recovery_codes(
+        conn: &mut PgConnection,
+        uid: &Uuid,
+    ) -> Result<Vec<String>, MfaServiceError> {
+        let codes = (0..RECOVERY_CODES_COUNT)
+            .map(|_| Self::generate_recovery_code())
+            .collect::<Vec<String>>();
+
+        delete(mfa_recovery_codes::dsl::mfa_recovery_codes)
+            .filter(mfa_recovery_codes::dsl::auth_uid.eq(uid))
+            .execute(conn)
+            .map_err(|_| MfaServiceError::Update)?;
+        insert_into(mfa_recovery_codes::dsl::mfa_recovery_codes)
+            .values(
+                codes
+                    .iter()
+                    .map(|code| {
+                        (
+                            mfa_recovery_codes::dsl::auth_uid.eq(uid),
+                            mfa_recovery_codes::dsl::code_hash.eq(Self::hash_recovery_code(code)),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .execute(conn)
+            .map_err(|_| MfaServiceError::Update)?;
+
+        Ok(codes)
+    }
+
+    fn generate_recovery_code() -> String {
+        let mut bytes = [0_u8; 7];
+
+        rand::thread_rng().fill_bytes(&mut bytes);
+
+        let code = BASE32_NOPAD.encode(&bytes).to_lowercase();
+
+        format!("{}-{}", &code[..5], &code[5..10])
+    }
+
+    fn hash_recovery_code(code: &str) -> String {
+        let normalized = code
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+
+        HEXLOWER.encode(digest::digest(&digest::SHA256, normalized.as_bytes()).as_ref())
+    }
+
+    fn find_auth_data(conn: &mut PgConnection, uid: &Uuid) -> Result<AuthData, MfaServiceError> {
+        auth_data::dsl::auth_data
+            .find(uid)
+            .first(conn)
+            .map_err(|_| MfaServiceError::UserNotFound)
+    }
+
+    fn pending_key(token: &str) -> String {
+        format!("mfa_pending:{}", token)
+    }
+}
@@ -1,3 +1,21 @@
+pub mod aead;
+pub mod api_keys;
+pub mod audit;
 pub mod auth;
+pub mod billing;
+pub mod catalog;
 pub mod dto;
+pub mod law_applications;
+pub mod ledger;
+pub mod login_throttle;
+pub mod mailer;
+pub mod mfa;
+pub mod oidc;
+pub mod pagination;
+pub mod passports;
+pub mod payments;
+pub mod permissions;
+pub mod session;
+pub mod transactions;
 pub mod user;
+pub mod verification;
@@ -0,0 +1,445 @@
+use std::{collections::HashMap, sync::Arc, sync::RwLock, time::Duration};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use data_encoding::HEXLOWER;
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use rand::RngCore;
+use ring::digest;
+use serde::Deserialize;
+use url::Url;
+
+use super::dto::oidc::OidcPendingData;
+use crate::{
+    cache::Cache, db::models::custom_types::user_profiles_roles::UserProfilesRoles,
+};
+
+const PENDING_LIFETIME_MINUTES: i64 = 10;
+const REQUEST_TIMEOUT_SECONDS: u64 = 10;
+
+#[derive(Debug)]
+pub enum OidcServiceError {
+    Disabled,
+    Discovery,
+    InvalidState,
+    TokenExchange,
+    InvalidIdToken,
+    MissingEmail,
+    Cache,
+}
+
+#[derive(Clone)]
+pub struct OidcSettings {
+    pub issuer_url: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub redirect_url: String,
+    pub scopes: String,
+    /// ID token claim holding the role, a string or an array of strings
+    pub role_claim: String,
+    /// Role given to users whose token carries no usable role claim
+    pub default_role: UserProfilesRoles,
+}
+
+impl OidcSettings {
+    /// Only staff accounts are managed by the identity provider
+    pub fn is_allowed_role(role: UserProfilesRoles) -> bool {
+        matches!(role, UserProfilesRoles::Employee | UserProfilesRoles::Law)
+    }
+}
+
+/// A user the identity provider vouched for
+pub struct OidcIdentity {
+    pub issuer: String,
+    pub subject: String,
+    pub email: String,
+    pub email_verified: bool,
+    pub username: Option<String>,
+    pub role: UserProfilesRoles,
+}
+
+#[derive(Deserialize, Clone)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Deserialize)]
+struct IdTokenClaims {
+    iss: String,
+    sub: String,
+    nonce: Option<String>,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+    preferred_username: Option<String>,
+    #[serde(flatten)]
+    extra: HashMap<String, serde_json::Value>,
+}
+
+/// OpenID Connect authorization code flow with PKCE. The provider metadata
+/// and its signing keys are fetched lazily and kept in memory, the keys are
+/// fetched again when a token is signed with an unknown one.
+pub struct OidcService {
+    redis: Arc<Cache>,
+    settings: Option<OidcSettings>,
+    agent: ureq::Agent,
+    metadata: RwLock<Option<ProviderMetadata>>,
+    jwks: RwLock<JwkSet>,
+}
+
+impl OidcService {
+    pub fn new(redis: Arc<Cache>, settings: Option<OidcSettings>) -> Self {
+        Self {
+            redis,
+            settings,
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECONDS))
+                .build(),
+            metadata: RwLock::new(None),
+            jwks: RwLock::new(JwkSet { keys: vec![] }),
+        }
+    }
+
+    /// Starts a login, the user is sent to the returned url and comes back to
+    /// the redirect url with `code` and `state`.
+    pub fn authorization_url(&self, device: Option<String>) -> Result<String, OidcServiceError> {
+        let settings = self.settings()?;
+        let metadata = self.metadata()?;
+        let state = Self::random_token();
+        let pending = OidcPendingData {
+            verifier: Self::random_token(),
+            nonce: Self::random_token(),
+            device,
+        };
+        let challenge = URL_SAFE_NO_PAD
+            .encode(digest::digest(&digest::SHA256, pending.verifier.as_bytes()).as_ref());
+        let ttl = (chrono::Utc::now() + chrono::Duration::minutes(PENDING_LIFETIME_MINUTES))
+            .timestamp() as usize;
+        let value = serde_json::to_string(&pending).map_err(|_| OidcServiceError::Cache)?;
+
+        self.redis
+            .add_pair(&Self::pending_key(&state), &value, ttl)
+            .map_err(|_| OidcServiceError::Cache)?;
+
+        Url::parse_with_params(
+            &metadata.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", settings.client_id.as_str()),
+                ("redirect_uri", settings.redirect_url.as_str()),
+                ("scope", settings.scopes.as_str()),
+                ("state", state.as_str()),
+                ("nonce", pending.nonce.as_str()),
+                ("code_challenge", challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map(String::from)
+        .map_err(|_| OidcServiceError::Discovery)
+    }
+
+    /// Finishes a login: the state is single-use, the code is exchanged with
+    /// the PKCE verifier and the ID token is verified against the provider keys.
+    pub fn exchange(
+        &self,
+        code: &str,
+        state: &str,
+    ) -> Result<(OidcIdentity, Option<String>), OidcServiceError> {
+        let settings = self.settings()?;
+        let metadata = self.metadata()?;
+        let pending: OidcPendingData = self
+            .redis
+            .take_pair(&Self::pending_key(state))
+            .map_err(|_| OidcServiceError::Cache)?
+            .and_then(|value| serde_json::from_str(&value).ok())
+            .ok_or(OidcServiceError::InvalidState)?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", settings.redirect_url.as_str()),
+            ("client_id", settings.client_id.as_str()),
+            ("code_verifier", pending.verifier.as_str()),
+        ];
+
+        if let Some(client_secret) = &settings.client_secret {
+            form.push(("client_secret", client_secret.as_str()));
+        }
+
+        let response: TokenResponse = self
+            .agent
+            .post(&metadata.token_endpoint)
+            .send_form(&form)
+            .map_err(|err| {
+                log::error!("OIDC token exchange: {}", err);
+                OidcServiceError::TokenExchange
+            })?
+            .into_json()
+            .map_err(|err| {
+                log::error!("OIDC token response: {}", err);
+                OidcServiceError::TokenExchange
+            })?;
+
+        let claims = self.verify_id_token(&response.id_token, settings, &metadata)?;
+
+        if claims.nonce.as_deref() != Some(pending.nonce.as_str()) {
+            log::warn!("Security event: OIDC ID token nonce mismatch for {}", claims.sub);
+
+            return Err(OidcServiceError::InvalidIdToken);
+        }
+
+        let role = claims
+            .extra
+            .get(&settings.role_claim)
+            .and_then(Self::find_role)
+            .unwrap_or(settings.default_role);
+
+        Ok((
+            OidcIdentity {
+                issuer: claims.iss,
+                subject: claims.sub,
+                email: claims.email.ok_or(OidcServiceError::MissingEmail)?,
+                email_verified: claims.email_verified,
+                username: claims.preferred_username,
+                role,
+            },
+            pending.device,
+        ))
+    }
+
+    fn verify_id_token(
+        &self,
+        id_token: &str,
+        settings: &OidcSettings,
+        metadata: &ProviderMetadata,
+    ) -> Result<IdTokenClaims, OidcServiceError> {
+        let invalid_token = |err: jsonwebtoken::errors::Error| {
+            log::error!("OIDC ID token: {}", err);
+            OidcServiceError::InvalidIdToken
+        };
+        let header = decode_header(id_token).map_err(invalid_token)?;
+
+        // An HMAC "signature" would only prove knowledge of the client secret
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(OidcServiceError::InvalidIdToken);
+        }
+
+        let key = self.find_key(header.kid.as_deref(), &metadata.jwks_uri)?;
+        let mut validation = Validation::new(header.alg);
+
+        validation.set_audience(&[&settings.client_id]);
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map(|token| token.claims)
+            .map_err(invalid_token)
+    }
+
+    fn find_key(&self, kid: Option<&str>, jwks_uri: &str) -> Result<DecodingKey, OidcServiceError> {
+        let kid = kid.ok_or(OidcServiceError::InvalidIdToken)?;
+
+        if let Some(key) = self.cached_key(kid) {
+            return Ok(key);
+        }
+
+        let jwks: JwkSet = self
+            .agent
+            .get(jwks_uri)
+            .call()
+            .map_err(|err| {
+                log::error!("OIDC keys: {}", err);
+                OidcServiceError::Discovery
+            })?
+            .into_json()
+            .map_err(|err| {
+                log::error!("OIDC keys: {}", err);
+                OidcServiceError::Discovery
+            })?;
+
+        if let Ok(mut cached) = self.jwks.write() {
+            *cached = jwks;
+        }
+
+        self.cached_key(kid).ok_or(OidcServiceError::InvalidIdToken)
+    }
+
+    fn cached_key(&self, kid: &str) -> Option<DecodingKey> {
+        self.jwks
+            .read()
+            .ok()?
+            .find(kid)
+            .and_then(|jwk| DecodingKey::from_jwk(jwk).ok())
+    }
+
+    fn metadata(&self) -> Result<ProviderMetadata, OidcServiceError> {
+        if let Some(metadata) = self.metadata.read().ok().and_then(|e| e.clone()) {
+            return Ok(metadata);
+        }
+
+        let settings = self.settings()?;
+        let discovery_url = format!(
+            "{}/.well-known/openid-configuration",
+            settings.issuer_url.trim_end_matches('/')
+        );
+        let metadata: ProviderMetadata = self
+            .agent
+            .get(&discovery_url)
+            .call()
+            .map_err(|err| {
+                log::error!("OIDC discovery: {}", err);
+                OidcServiceError::Discovery
+            })?
+            .into_json()
+            .map_err(|err| {
+                log::error!("OIDC discovery: {}", err);
+                OidcServiceError::Discovery
+            })?;
+
+        if metadata.issuer.trim_end_matches('/') != settings.issuer_url.trim_end_matches('/') {
+            log::error!("OIDC discovery: issuer {} doesn't match", metadata.issuer);
+
+            return Err(OidcServiceError::Discovery);
+        }
+
+        if let Ok(mut cached) = self.metadata.write() {
+            *cached = Some(metadata.clone());
+        }
+
+        Ok(metadata)
+    }
+
+    fn settings(&self) -> Result<&OidcSettings, OidcServiceError> {
+        self.settings.as_ref().ok_or(OidcServiceError::Disabled)
+    }
+
+    fn find_role(value: &serde_json::Value) -> Option<UserProfilesRoles> {
+        let parse = |value: &serde_json::Value| {
+            value
+                .as_str()
+                .and_then(|role| role.parse().ok())
+                .filter(|role| OidcSettings::is_allowed_role(*role))
+        };
+
+        match value {
+            serde_json::Value::Array(values) => values.iter().find_map(parse),
+            value => parse(value),
+        }
+    }
+
+    fn random_token() -> String {
+        let mut token = [0_u8; 32];
+
+        rand::thread_rng().fill_bytes(&mut token);
+
+        HEXLOWER.encode(&token)
+    }
+
+    fn pending_key(state: &str) -> String {
+        format!("oidc_state:{}", state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonwebtoken::{encode, EncodingKey, Header};
+
+    use super::*;
+    use crate::test_support::{self, MockIdp};
+
+    fn service(idp: &MockIdp) -> OidcService {
+        OidcService::new(test_support::cache(), Some(idp.settings()))
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn exchange_returns_identity_vouched_by_provider() {
+        let idp = MockIdp::start();
+        let service = service(&idp);
+        let url = service
+            .authorization_url(Some("laptop".to_owned()))
+            .unwrap();
+        let claims = idp.claims(&url, "staff@example.com");
+
+        idp.respond_with(idp.sign(&claims));
+
+        let state = test_support::authorization_state(&url);
+        let (identity, device) = service.exchange("code", &state).unwrap();
+
+        assert_eq!(identity.issuer, idp.issuer);
+        assert_eq!(identity.subject, claims["sub"].as_str().unwrap());
+        assert_eq!(identity.email, "staff@example.com");
+        assert!(identity.email_verified);
+        assert_eq!(identity.role, UserProfilesRoles::Employee);
+        assert_eq!(device.as_deref(), Some("laptop"));
+        assert!(matches!(
+            service.exchange("code", &state),
+            Err(OidcServiceError::InvalidState)
+        ));
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn admin_role_claim_is_ignored() {
+        let idp = MockIdp::start();
+        let service = service(&idp);
+        let url = service.authorization_url(None).unwrap();
+        let mut claims = idp.claims(&url, "staff@example.com");
+
+        claims["roles"] = serde_json::json!(["admin", "law"]);
+        idp.respond_with(idp.sign(&claims));
+
+        let (identity, _) = service
+            .exchange("code", &test_support::authorization_state(&url))
+            .unwrap();
+
+        assert_eq!(identity.role, UserProfilesRoles::Law);
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn token_for_another_login_is_rejected() {
+        let idp = MockIdp::start();
+        let service = service(&idp);
+        let url = service.authorization_url(None).unwrap();
+        let other_url = service.authorization_url(None).unwrap();
+
+        idp.respond_with(idp.sign(&idp.claims(&other_url, "staff@example.com")));
+
+        assert!(matches!(
+            service.exchange("code", &test_support::authorization_state(&url)),
+            Err(OidcServiceError::InvalidIdToken)
+        ));
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn hmac_signed_token_is_rejected() {
+        let idp = MockIdp::start();
+        let service = service(&idp);
+        let url = service.authorization_url(None).unwrap();
+        let id_token = encode(
+            &Header::default(),
+            &idp.claims(&url, "staff@example.com"),
+            &EncodingKey::from_secret(b"client secret"),
+        )
+        .unwrap();
+
+        idp.respond_with(id_token);
+
+        assert!(matches!(
+            service.exchange("code", &test_support::authorization_state(&url)),
+            Err(OidcServiceError::InvalidIdToken)
+        ));
+    }
+}
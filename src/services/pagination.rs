@@ -0,0 +1,84 @@
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+pub const MAX_PAGE_SIZE: i64 = 200;
+
+/// Rows of a listing selected by the 1-based `page` and `per_page` query parameters
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pagination {
+    pub page: u64,
+    pub per_page: i64,
+    pub offset: i64,
+}
+
+impl Pagination {
+    /// `None` when the page starts past the last row postgres can address
+    pub fn new(page: Option<u64>, per_page: Option<i64>) -> Option<Self> {
+        Self::with_sizes(page, per_page, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE)
+    }
+
+    pub fn with_sizes(
+        page: Option<u64>,
+        per_page: Option<i64>,
+        default_size: i64,
+        max_size: i64,
+    ) -> Option<Self> {
+        let page = page.unwrap_or(1).max(1);
+        let per_page = per_page.unwrap_or(default_size).clamp(1, max_size);
+        let offset = i64::try_from(page - 1).ok()?.checked_mul(per_page)?;
+
+        Some(Self {
+            page,
+            per_page,
+            offset,
+        })
+    }
+
+    pub fn next_page(&self, total: i64) -> Option<u64> {
+        if self.offset.saturating_add(self.per_page) < total {
+            Some(self.page + 1)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_and_bounds() {
+        assert_eq!(
+            Pagination::new(None, None),
+            Some(Pagination {
+                page: 1,
+                per_page: DEFAULT_PAGE_SIZE,
+                offset: 0,
+            })
+        );
+
+        let pagination = Pagination::new(Some(0), Some(0)).unwrap();
+        assert_eq!((pagination.page, pagination.per_page), (1, 1));
+
+        let pagination = Pagination::new(Some(3), Some(1000)).unwrap();
+        assert_eq!(pagination.per_page, MAX_PAGE_SIZE);
+        assert_eq!(pagination.offset, 2 * MAX_PAGE_SIZE);
+    }
+
+    #[test]
+    fn overflowing_pages_are_rejected() {
+        assert!(Pagination::new(Some(u64::MAX), None).is_none());
+        assert!(Pagination::new(Some(i64::MAX as u64), Some(2)).is_none());
+        assert!(Pagination::new(Some(i64::MAX as u64), Some(1)).is_some());
+    }
+
+    #[test]
+    fn next_page() {
+        let pagination = Pagination::with_sizes(Some(2), Some(10), 15, 100).unwrap();
+
+        assert_eq!(pagination.next_page(21), Some(3));
+        assert_eq!(pagination.next_page(20), None);
+
+        let last = Pagination::new(Some(i64::MAX as u64), Some(1)).unwrap();
+        assert_eq!(last.next_page(i64::MAX), None);
+    }
+}
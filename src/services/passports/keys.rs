@@ -0,0 +1,204 @@
+use chrono::NaiveDate;
+use data_encoding::HEXLOWER;
+use ring::{
+    aead::LessSafeKey,
+    hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+use uuid::Uuid;
+
+use crate::{
+    db::models::passports::Passport,
+    services::{
+        aead,
+        dto::passport::{PassportSecrets, SealedPassport},
+    },
+};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug)]
+pub enum PassportKeysError {
+    InvalidKey,
+    UnknownKey,
+    Encrypt,
+    Decrypt,
+}
+
+/// Envelope encryption of passport fields. Every row has its own data key,
+/// stored wrapped by one of the master keys, and every ciphertext is bound
+/// to its row and column. Numbers are also indexed with a keyed hash, so
+/// they can be found without decrypting the table.
+pub struct PassportKeys {
+    current_kid: String,
+    master_keys: Vec<(String, LessSafeKey)>,
+    index_key: hmac::Key,
+    rng: SystemRandom,
+}
+
+impl PassportKeys {
+    /// `master_keys` are `(kid, key)` pairs of 32 byte keys. Retired keys
+    /// stay there until every row is encrypted with the current one.
+    pub fn new(
+        current_kid: &str,
+        master_keys: &[(String, Vec<u8>)],
+        index_key: &[u8],
+    ) -> Result<Self, PassportKeysError> {
+        let master_keys = master_keys
+            .iter()
+            .map(|(kid, key)| Ok((kid.clone(), Self::aead_key(key)?)))
+            .collect::<Result<Vec<(String, LessSafeKey)>, PassportKeysError>>()?;
+
+        if !master_keys.iter().any(|(kid, _)| kid == current_kid) {
+            return Err(PassportKeysError::UnknownKey);
+        }
+
+        Ok(Self {
+            current_kid: current_kid.to_owned(),
+            master_keys,
+            index_key: hmac::Key::new(hmac::HMAC_SHA256, index_key),
+            rng: SystemRandom::new(),
+        })
+    }
+
+    pub fn current_kid(&self) -> &str {
+        &self.current_kid
+    }
+
+    pub fn blind_index(&self, number: &str) -> String {
+        let normalized = number
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>();
+
+        HEXLOWER.encode(hmac::sign(&self.index_key, normalized.as_bytes()).as_ref())
+    }
+
+    /// Encrypts the fields with a fresh data key under the current master key
+    pub fn seal(
+        &self,
+        uid: &Uuid,
+        secrets: &PassportSecrets,
+    ) -> Result<SealedPassport, PassportKeysError> {
+        let mut data_key = [0u8; 32];
+
+        self.rng
+            .fill(&mut data_key)
+            .map_err(|_| PassportKeysError::Encrypt)?;
+
+        let (_, master_key) = self
+            .master_keys
+            .iter()
+            .find(|(kid, _)| kid == &self.current_kid)
+            .ok_or(PassportKeysError::UnknownKey)?;
+        let wrapped_key = self.encrypt(master_key, uid, "data_key", &data_key)?;
+        let data_key = Self::aead_key(&data_key)?;
+        let seal_field = |field: &str, value: &Option<String>| {
+            value
+                .as_deref()
+                .map(|value| self.encrypt(&data_key, uid, field, value.as_bytes()))
+                .transpose()
+        };
+
+        Ok(SealedPassport {
+            number: seal_field("number", &secrets.number)?,
+            series: seal_field("series", &secrets.series)?,
+            registration_place: seal_field("registration_place", &secrets.registration_place)?,
+            birthday_date: self.encrypt(
+                &data_key,
+                uid,
+                "birthday_date",
+                secrets
+                    .birthday_date
+                    .format(DATE_FORMAT)
+                    .to_string()
+                    .as_bytes(),
+            )?,
+            number_index: secrets
+                .number
+                .as_deref()
+                .map(|number| self.blind_index(number)),
+            data_key: Some(wrapped_key),
+            key_id: Some(self.current_kid.clone()),
+        })
+    }
+
+    /// Rows without a data key were stored before encryption and are read
+    /// as they are
+    pub fn open(&self, passport: &Passport) -> Result<PassportSecrets, PassportKeysError> {
+        let (wrapped_key, kid) = match (&passport.data_key, &passport.key_id) {
+            (Some(wrapped_key), Some(kid)) => (wrapped_key, kid),
+            _ => {
+                return Ok(PassportSecrets {
+                    number: passport.number.as_deref().map(|e| e.trim().to_owned()),
+                    series: passport.series.as_deref().map(|e| e.trim().to_owned()),
+                    registration_place: passport.registration_place.clone(),
+                    birthday_date: NaiveDate::parse_from_str(&passport.birthday_date, DATE_FORMAT)
+                        .map_err(|_| PassportKeysError::Decrypt)?,
+                })
+            }
+        };
+
+        let (_, master_key) = self
+            .master_keys
+            .iter()
+            .find(|(master_kid, _)| master_kid == kid)
+            .ok_or(PassportKeysError::UnknownKey)?;
+        let data_key = Self::aead_key(&Self::decrypt(
+            master_key,
+            &passport.uid,
+            "data_key",
+            wrapped_key,
+        )?)?;
+        let open_field = |field: &str, value: &Option<String>| {
+            value
+                .as_deref()
+                .map(|value| {
+                    Self::decrypt(&data_key, &passport.uid, field, value).and_then(|plaintext| {
+                        String::from_utf8(plaintext).map_err(|_| PassportKeysError::Decrypt)
+                    })
+                })
+                .transpose()
+        };
+
+        let birthday_date = open_field("birthday_date", &Some(passport.birthday_date.clone()))?
+            .and_then(|value| NaiveDate::parse_from_str(&value, DATE_FORMAT).ok())
+            .ok_or(PassportKeysError::Decrypt)?;
+
+        Ok(PassportSecrets {
+            number: open_field("number", &passport.number)?,
+            series: open_field("series", &passport.series)?,
+            registration_place: open_field("registration_place", &passport.registration_place)?,
+            birthday_date,
+        })
+    }
+
+    fn encrypt(
+        &self,
+        key: &LessSafeKey,
+        uid: &Uuid,
+        field: &str,
+        plaintext: &[u8],
+    ) -> Result<String, PassportKeysError> {
+        aead::encrypt(&self.rng, key, &Self::aad(uid, field), plaintext)
+            .map_err(|_| PassportKeysError::Encrypt)
+    }
+
+    fn decrypt(
+        key: &LessSafeKey,
+        uid: &Uuid,
+        field: &str,
+        ciphertext: &str,
+    ) -> Result<Vec<u8>, PassportKeysError> {
+        aead::decrypt(key, &Self::aad(uid, field), ciphertext)
+            .map_err(|_| PassportKeysError::Decrypt)
+    }
+
+    fn aead_key(key: &[u8]) -> Result<LessSafeKey, PassportKeysError> {
+        aead::key(key).map_err(|_| PassportKeysError::InvalidKey)
+    }
+
+    fn aad(uid: &Uuid, field: &str) -> String {
+        format!("passports:{}:{}", uid, field)
+    }
+}
@@ -0,0 +1,446 @@
+pub mod keys;
+
+use std::sync::Arc;
+
+use diesel::{insert_into, prelude::*, update};
+use uuid::Uuid;
+
+use self::keys::PassportKeys;
+use super::dto::passport::{
+    PassportDetails, PassportSecrets, ReencryptionBatch, UpdatePassportDto,
+};
+use crate::db::{
+    models::{passport_history::PassportHistoryEntry, passports::Passport},
+    orm::schema::{auth_data, passport_history, passports, user_profiles},
+    Db, DbError, DbProvider,
+};
+
+pub trait PassportKeysProvider {
+    fn passport_keys(&self) -> &PassportKeys;
+}
+
+#[derive(Debug)]
+pub enum PassportServiceError {
+    NotFound,
+    AlreadyVerified,
+    Incomplete,
+    SelfVerification,
+    Query,
+    Crypto,
+    Update,
+}
+
+pub struct PassportService {
+    db: Arc<Db>,
+}
+
+impl PassportService {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+
+    pub fn get_own(
+        &self,
+        auth_uid: &Uuid,
+        keys_provider: &impl PassportKeysProvider,
+    ) -> Result<PassportDetails, DbError<PassportServiceError>> {
+        self.db.apply(|conn| {
+            let passport_uid = Self::find_own_uid(conn, auth_uid)?;
+
+            Self::details(conn, &passport_uid, keys_provider.passport_keys())
+        })
+    }
+
+    pub fn get_by_profile(
+        &self,
+        profile_uid: &Uuid,
+        keys_provider: &impl PassportKeysProvider,
+    ) -> Result<PassportDetails, DbError<PassportServiceError>> {
+        self.db.apply(|conn| {
+            let passport_uid = Self::find_profile_passport_uid(conn, profile_uid)?;
+
+            Self::details(conn, &passport_uid, keys_provider.passport_keys())
+        })
+    }
+
+    /// Fills in the owner's passport. A verified passport is not changed
+    /// anymore.
+    pub fn update_own(
+        &self,
+        auth_uid: &Uuid,
+        dto: &UpdatePassportDto,
+        keys_provider: &impl PassportKeysProvider,
+    ) -> Result<PassportDetails, DbError<PassportServiceError>> {
+        let keys = keys_provider.passport_keys();
+
+        self.db.transaction(|conn| {
+            let passport_uid = Self::find_own_uid(conn, auth_uid)?;
+            let passport = Self::lock(conn, &passport_uid)?;
+
+            if passport.verified_at.is_some() {
+                return Err(PassportServiceError::AlreadyVerified);
+            }
+
+            let current = keys.open(&passport).map_err(|err| {
+                log::error!("Passport {}: {:?}", passport.uid, err);
+                PassportServiceError::Crypto
+            })?;
+            let mut fields = Vec::new();
+            let mut changed = current.clone();
+
+            if dto.series.is_some() && dto.series != current.series {
+                changed.series = dto.series.clone();
+                fields.push("series");
+            }
+
+            if dto.number.is_some() && dto.number != current.number {
+                changed.number = dto.number.clone();
+                fields.push("number");
+            }
+
+            if dto.registration_place.is_some()
+                && dto.registration_place != current.registration_place
+            {
+                changed.registration_place = dto.registration_place.clone();
+                fields.push("registration_place");
+            }
+
+            if let Some(birthday_date) = dto.birthday_date {
+                if birthday_date != current.birthday_date {
+                    changed.birthday_date = birthday_date;
+                    fields.push("birthday_date");
+                }
+            }
+
+            if !fields.is_empty() {
+                let sealed = keys
+                    .seal(&passport.uid, &changed)
+                    .map_err(|_| PassportServiceError::Crypto)?;
+
+                update(passports::table.find(passport.uid))
+                    .set(&sealed)
+                    .execute(conn)
+                    .map_err(|_| PassportServiceError::Update)?;
+
+                Self::add_history(conn, &passport.uid, auth_uid, "update", &fields)?;
+            }
+
+            Ok(Self::to_details(passport, changed))
+        })
+    }
+
+    /// Marks a complete passport as checked against the document by an
+    /// employee, who can't verify their own one
+    pub fn verify(
+        &self,
+        profile_uid: &Uuid,
+        employee_uid: &Uuid,
+        keys_provider: &impl PassportKeysProvider,
+    ) -> Result<PassportDetails, DbError<PassportServiceError>> {
+        let keys = keys_provider.passport_keys();
+
+        self.db.transaction(|conn| {
+            let passport_uid = Self::find_profile_passport_uid(conn, profile_uid)?;
+
+            match Self::find_own_uid(conn, employee_uid) {
+                Ok(own_uid) if own_uid == passport_uid => {
+                    return Err(PassportServiceError::SelfVerification)
+                }
+                Err(PassportServiceError::NotFound) | Ok(_) => (),
+                Err(err) => return Err(err),
+            }
+
+            let passport = Self::lock(conn, &passport_uid)?;
+
+            if passport.verified_at.is_some() {
+                return Err(PassportServiceError::AlreadyVerified);
+            }
+
+            let secrets = keys.open(&passport).map_err(|err| {
+                log::error!("Passport {}: {:?}", passport.uid, err);
+                PassportServiceError::Crypto
+            })?;
+
+            if secrets.series.is_none()
+                || secrets.number.is_none()
+                || secrets.registration_place.is_none()
+            {
+                return Err(PassportServiceError::Incomplete);
+            }
+
+            let passport = update(passports::table.find(passport.uid))
+                .set((
+                    passports::dsl::verified_at.eq(diesel::dsl::now),
+                    passports::dsl::verified_by.eq(employee_uid),
+                ))
+                .get_result::<Passport>(conn)
+                .map_err(|_| PassportServiceError::Update)?;
+
+            Self::add_history(conn, &passport.uid, employee_uid, "verify", &[])?;
+
+            Ok(Self::to_details(passport, secrets))
+        })
+    }
+
+    pub fn get_history(
+        &self,
+        profile_uid: &Uuid,
+    ) -> Result<Vec<PassportHistoryEntry>, DbError<PassportServiceError>> {
+        self.db.apply(|conn| {
+            let passport_uid = Self::find_profile_passport_uid(conn, profile_uid)?;
+
+            passport_history::table
+                .filter(passport_history::dsl::passport_uid.eq(passport_uid))
+                .order(passport_history::dsl::created_at.asc())
+                .load(conn)
+                .map_err(|_| PassportServiceError::Query)
+        })
+    }
+
+    /// Profiles with the passport number, matched by its blind index
+    pub fn find_profiles_by_number(
+        &self,
+        number: &str,
+        keys_provider: &impl PassportKeysProvider,
+    ) -> Result<Vec<Uuid>, DbError<PassportServiceError>> {
+        let number_index = keys_provider.passport_keys().blind_index(number);
+
+        self.db.apply(|conn| {
+            user_profiles::table
+                .inner_join(passports::table)
+                .filter(passports::dsl::number_index.eq(&number_index))
+                .select(user_profiles::dsl::uid)
+                .load(conn)
+                .map_err(|_| PassportServiceError::Query)
+        })
+    }
+
+    /// Encrypts up to `limit` rows after `after` that are in plaintext or
+    /// under a retired master key with a new data key. A row that can't be
+    /// re-encrypted is reported and skipped, the others are still updated.
+    /// Call it with the returned `last_uid` until it is `None`.
+    pub fn reencrypt_batch(
+        &self,
+        keys_provider: &impl PassportKeysProvider,
+        after: Option<Uuid>,
+        limit: i64,
+    ) -> Result<ReencryptionBatch, DbError<PassportServiceError>> {
+        let keys = keys_provider.passport_keys();
+
+        self.db.transaction(|conn| {
+            let rows = passports::table
+                .filter(
+                    passports::dsl::key_id
+                        .is_null()
+                        .or(passports::dsl::key_id.ne(keys.current_kid())),
+                )
+                .filter(passports::dsl::uid.gt(after.unwrap_or_else(Uuid::nil)))
+                .order(passports::dsl::uid.asc())
+                .limit(limit)
+                .for_update()
+                .skip_locked()
+                .load::<Passport>(conn)
+                .map_err(|_| PassportServiceError::Query)?;
+            let mut batch = ReencryptionBatch {
+                updated: 0,
+                failed: vec![],
+                last_uid: rows.last().map(|passport| passport.uid),
+            };
+
+            for passport in &rows {
+                let sealed = match keys
+                    .open(passport)
+                    .and_then(|secrets| keys.seal(&passport.uid, &secrets))
+                {
+                    Ok(sealed) => sealed,
+                    Err(err) => {
+                        log::error!("Passport {}: {:?}", passport.uid, err);
+                        batch.failed.push(passport.uid);
+                        continue;
+                    }
+                };
+
+                // A savepoint, a failed row leaves the batch usable
+                let updated = conn.transaction(|conn| {
+                    update(passports::table.find(passport.uid))
+                        .set(&sealed)
+                        .execute(conn)
+                });
+
+                match updated {
+                    Ok(_) => batch.updated += 1,
+                    Err(err) => {
+                        log::error!("Passport {}: {}", passport.uid, err);
+                        batch.failed.push(passport.uid);
+                    }
+                }
+            }
+
+            Ok(batch)
+        })
+    }
+
+    fn find_own_uid(
+        conn: &mut PgConnection,
+        auth_uid: &Uuid,
+    ) -> Result<Uuid, PassportServiceError> {
+        auth_data::table
+            .inner_join(user_profiles::table)
+            .filter(auth_data::dsl::uid.eq(auth_uid))
+            .select(user_profiles::dsl::passport_uid)
+            .first::<Option<Uuid>>(conn)
+            .optional()
+            .map_err(|_| PassportServiceError::Query)?
+            .flatten()
+            .ok_or(PassportServiceError::NotFound)
+    }
+
+    fn find_profile_passport_uid(
+        conn: &mut PgConnection,
+        profile_uid: &Uuid,
+    ) -> Result<Uuid, PassportServiceError> {
+        user_profiles::table
+            .find(profile_uid)
+            .select(user_profiles::dsl::passport_uid)
+            .first::<Option<Uuid>>(conn)
+            .optional()
+            .map_err(|_| PassportServiceError::Query)?
+            .flatten()
+            .ok_or(PassportServiceError::NotFound)
+    }
+
+    fn lock(
+        conn: &mut PgConnection,
+        passport_uid: &Uuid,
+    ) -> Result<Passport, PassportServiceError> {
+        passports::table
+            .find(passport_uid)
+            .for_update()
+            .first(conn)
+            .map_err(|_| PassportServiceError::NotFound)
+    }
+
+    fn details(
+        conn: &mut PgConnection,
+        passport_uid: &Uuid,
+        keys: &PassportKeys,
+    ) -> Result<PassportDetails, PassportServiceError> {
+        let passport = passports::table
+            .find(passport_uid)
+            .first::<Passport>(conn)
+            .map_err(|_| PassportServiceError::NotFound)?;
+        let secrets = keys.open(&passport).map_err(|err| {
+            log::error!("Passport {}: {:?}", passport.uid, err);
+            PassportServiceError::Crypto
+        })?;
+
+        Ok(Self::to_details(passport, secrets))
+    }
+
+    fn to_details(passport: Passport, secrets: PassportSecrets) -> PassportDetails {
+        PassportDetails {
+            uid: passport.uid,
+            first_name: passport.first_name,
+            second_name: passport.second_name,
+            patronymic: passport.patronymic,
+            series: secrets.series,
+            number: secrets.number,
+            registration_place: secrets.registration_place,
+            birthday_date: secrets.birthday_date,
+            verified_at: passport.verified_at,
+        }
+    }
+
+    pub fn add_history(
+        conn: &mut PgConnection,
+        passport_uid: &Uuid,
+        changed_by: &Uuid,
+        action: &str,
+        fields: &[&str],
+    ) -> Result<(), PassportServiceError> {
+        insert_into(passport_history::dsl::passport_history)
+            .values((
+                passport_history::dsl::passport_uid.eq(passport_uid),
+                passport_history::dsl::changed_by.eq(changed_by),
+                passport_history::dsl::action.eq(action),
+                passport_history::dsl::fields.eq(fields.join(",")),
+            ))
+            .execute(conn)
+            .map_err(|err| {
+                log::error!("{}", err);
+                PassportServiceError::Update
+            })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, TestConfig};
+
+    fn insert_passport(key_id: Option<&str>, data_key: Option<&str>) -> Uuid {
+        test_support::db()
+            .apply(|conn| {
+                insert_into(passports::table)
+                    .values((
+                        passports::dsl::first_name.eq("Иван"),
+                        passports::dsl::second_name.eq("Иванов"),
+                        passports::dsl::number.eq("123456"),
+                        passports::dsl::series.eq("4510"),
+                        passports::dsl::birthday_date.eq("1990-01-01"),
+                        passports::dsl::key_id.eq(key_id),
+                        passports::dsl::data_key.eq(data_key),
+                    ))
+                    .returning(passports::dsl::uid)
+                    .get_result::<Uuid>(conn)
+            })
+            .unwrap()
+    }
+
+    fn key_id(uid: &Uuid) -> Option<String> {
+        test_support::db()
+            .apply(|conn| {
+                passports::table
+                    .find(uid)
+                    .select(passports::dsl::key_id)
+                    .first(conn)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn broken_rows_are_skipped_and_reported() {
+        let service = PassportService::new(test_support::db());
+        let plaintext = insert_passport(None, None);
+        let broken = insert_passport(Some("retired"), Some("garbage"));
+        let mut failed = vec![];
+        let mut after = None;
+
+        loop {
+            let batch = service.reencrypt_batch(&TestConfig, after, 1).unwrap();
+
+            failed.extend(batch.failed);
+
+            match batch.last_uid {
+                Some(last_uid) => after = Some(last_uid),
+                None => break,
+            }
+        }
+
+        assert_eq!(key_id(&plaintext).as_deref(), Some("test"));
+        assert_eq!(key_id(&broken).as_deref(), Some("retired"));
+        assert!(failed.contains(&broken));
+        assert!(!failed.contains(&plaintext));
+
+        let sealed: Passport = test_support::db()
+            .apply(|conn| passports::table.find(plaintext).first(conn))
+            .unwrap();
+        let secrets = TestConfig.passport_keys().open(&sealed).unwrap();
+
+        assert_eq!(secrets.number.as_deref(), Some("123456"));
+        assert_eq!(secrets.series.as_deref(), Some("4510"));
+    }
+}
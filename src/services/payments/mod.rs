@@ -0,0 +1,43 @@
+mod local;
+
+pub use local::LocalPaymentProvider;
+
+use uuid::Uuid;
+
+use crate::db::models::custom_types::amount::Amount;
+
+#[derive(Debug)]
+pub enum PaymentProviderError {
+    Declined,
+}
+
+pub struct Charge<'a> {
+    pub invoice_uid: Uuid,
+    pub amount: Amount,
+    pub currency: &'a str,
+}
+
+pub struct Receipt {
+    /// Id of the payment on the provider side
+    pub reference: String,
+}
+
+/// Charges clients through a payment provider. Implementations block, so
+/// they are called from `web::block` like the database.
+pub trait PaymentProvider: Send + Sync {
+    /// Stored with every payment made through the provider
+    fn name(&self) -> &'static str;
+
+    /// `payment_uid` is the idempotency key: a charge retried with the uid
+    /// of the same payment is made once and returns the same receipt
+    fn charge(&self, payment_uid: &Uuid, charge: &Charge) -> Result<Receipt, PaymentProviderError>;
+
+    /// Outcome of the charge made with `payment_uid`, a charge that never
+    /// reached the provider is declined. `None` while the provider can't
+    /// tell, the lookup is repeated later.
+    fn find_charge(
+        &self,
+        payment_uid: &Uuid,
+        charge: &Charge,
+    ) -> Option<Result<Receipt, PaymentProviderError>>;
+}
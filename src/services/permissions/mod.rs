@@ -0,0 +1,90 @@
+use std::str::FromStr;
+
+use crate::db::models::custom_types::user_profiles_roles::UserProfilesRoles;
+
+/// Actions guarded by role. Endpoints declare the permission they need
+/// instead of checking roles themselves, API keys carry them as scopes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    LawsDelete,
+    UsersForceLogout,
+    UsersUnlock,
+    ApiKeysManage,
+    AuditRead,
+    LedgerRead,
+    PassportsRead,
+    PassportsVerify,
+    UsersManage,
+    LawApplicationsReview,
+    ServicesManage,
+    InvoicesManage,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 12] = [
+        Permission::LawsDelete,
+        Permission::UsersForceLogout,
+        Permission::UsersUnlock,
+        Permission::ApiKeysManage,
+        Permission::AuditRead,
+        Permission::LedgerRead,
+        Permission::PassportsRead,
+        Permission::PassportsVerify,
+        Permission::UsersManage,
+        Permission::LawApplicationsReview,
+        Permission::ServicesManage,
+        Permission::InvoicesManage,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Permission::LawsDelete => "laws:delete",
+            Permission::UsersForceLogout => "users:force_logout",
+            Permission::UsersUnlock => "users:unlock",
+            Permission::ApiKeysManage => "api_keys:manage",
+            Permission::AuditRead => "audit:read",
+            Permission::LedgerRead => "ledger:read",
+            Permission::PassportsRead => "passports:read",
+            Permission::PassportsVerify => "passports:verify",
+            Permission::UsersManage => "users:manage",
+            Permission::LawApplicationsReview => "law_applications:review",
+            Permission::ServicesManage => "services:manage",
+            Permission::InvoicesManage => "invoices:manage",
+        }
+    }
+
+    /// Roles the permission is granted to
+    pub fn roles(self) -> &'static [UserProfilesRoles] {
+        use UserProfilesRoles::*;
+
+        match self {
+            Permission::LawsDelete => &[Admin],
+            Permission::UsersForceLogout => &[Admin],
+            Permission::UsersUnlock => &[Admin],
+            Permission::ApiKeysManage => &[Admin],
+            Permission::AuditRead => &[Admin],
+            Permission::LedgerRead => &[Admin, Employee],
+            Permission::PassportsRead => &[Admin, Employee],
+            Permission::PassportsVerify => &[Admin, Employee],
+            Permission::UsersManage => &[Admin],
+            Permission::LawApplicationsReview => &[Admin, Employee],
+            Permission::ServicesManage => &[Law],
+            Permission::InvoicesManage => &[Admin, Employee],
+        }
+    }
+
+    pub fn is_granted_to(self, role: UserProfilesRoles) -> bool {
+        self.roles().contains(&role)
+    }
+}
+
+impl FromStr for Permission {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Permission::ALL
+            .into_iter()
+            .find(|permission| permission.as_str() == value)
+            .ok_or(())
+    }
+}
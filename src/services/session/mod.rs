@@ -0,0 +1,186 @@
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use super::{auth::AuthService, dto::session::SessionData};
+use crate::cache::Cache;
+
+#[derive(Debug)]
+pub enum SessionServiceError {
+    Cache,
+    Serialization,
+    NotFound,
+}
+
+/// Keeps track of user logins. A session shares its uid with the refresh
+/// token family issued on login, so revoking a session revokes the family.
+pub struct SessionService {
+    redis: Arc<Cache>,
+}
+
+impl SessionService {
+    pub fn new(redis: Arc<Cache>) -> Self {
+        Self { redis }
+    }
+
+    pub fn create(
+        &self,
+        user_uid: &Uuid,
+        session: &SessionData,
+        ttl: usize,
+    ) -> Result<(), SessionServiceError> {
+        self.save(session, ttl)?;
+        self.redis
+            .add_to_set(&Self::user_sessions_key(user_uid), &session.uid.to_string(), ttl)
+            .map_err(|_| SessionServiceError::Cache)?;
+
+        Ok(())
+    }
+
+    pub fn touch(
+        &self,
+        user_uid: &Uuid,
+        session_uid: &Uuid,
+        ip: Option<String>,
+        ttl: usize,
+    ) -> Result<(), SessionServiceError> {
+        let mut session = self
+            .find(session_uid)?
+            .ok_or(SessionServiceError::NotFound)?;
+
+        session.last_used_at = chrono::Utc::now().naive_utc();
+
+        if ip.is_some() {
+            session.ip = ip;
+        }
+
+        self.create(user_uid, &session, ttl)
+    }
+
+    pub fn list(&self, user_uid: &Uuid) -> Result<Vec<SessionData>, SessionServiceError> {
+        let key = Self::user_sessions_key(user_uid);
+        let members = self
+            .redis
+            .get_set(&key)
+            .map_err(|_| SessionServiceError::Cache)?;
+        let mut sessions = Vec::with_capacity(members.len());
+
+        for member in members {
+            let session = match Uuid::parse_str(&member) {
+                Ok(session_uid) => self.find(&session_uid)?,
+                Err(_) => None,
+            };
+
+            match session {
+                Some(session) => sessions.push(session),
+                // Session has expired together with its refresh token
+                None => {
+                    let _ = self.redis.remove_from_set(&key, &member);
+                }
+            }
+        }
+
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.last_used_at));
+
+        Ok(sessions)
+    }
+
+    pub fn revoke(&self, user_uid: &Uuid, session_uid: &Uuid) -> Result<(), SessionServiceError> {
+        let belongs_to_user = self
+            .redis
+            .get_set(&Self::user_sessions_key(user_uid))
+            .map_err(|_| SessionServiceError::Cache)?
+            .contains(&session_uid.to_string());
+
+        if !belongs_to_user {
+            return Err(SessionServiceError::NotFound);
+        }
+
+        self.remove(user_uid, session_uid)
+    }
+
+    pub fn revoke_others(
+        &self,
+        user_uid: &Uuid,
+        current_session_uid: &Uuid,
+    ) -> Result<Vec<Uuid>, SessionServiceError> {
+        let members = self
+            .redis
+            .get_set(&Self::user_sessions_key(user_uid))
+            .map_err(|_| SessionServiceError::Cache)?;
+        let mut revoked = Vec::with_capacity(members.len());
+
+        for session_uid in members.iter().filter_map(|e| Uuid::parse_str(e).ok()) {
+            if &session_uid == current_session_uid {
+                continue;
+            }
+
+            self.remove(user_uid, &session_uid)?;
+            revoked.push(session_uid);
+        }
+
+        Ok(revoked)
+    }
+
+    pub fn revoke_all(&self, user_uid: &Uuid) -> Result<Vec<Uuid>, SessionServiceError> {
+        self.revoke_others(user_uid, &Uuid::nil())
+    }
+
+    /// Ends the session without touching the user index, stale index entries
+    /// are dropped on the next listing. Access tokens already issued for the
+    /// session are denied until they expire.
+    pub fn end(&self, session_uid: &Uuid) -> Result<(), SessionServiceError> {
+        let access_tokens_ttl =
+            (chrono::Utc::now() + AuthService::access_token_lifetime()).timestamp() as usize;
+
+        self.redis
+            .revoke_refresh_family(session_uid)
+            .map_err(|_| SessionServiceError::Cache)?;
+        self.redis
+            .deny_session_access_tokens(session_uid, access_tokens_ttl)
+            .map_err(|_| SessionServiceError::Cache)?;
+        self.redis
+            .remove(&Self::session_key(session_uid))
+            .map_err(|_| SessionServiceError::Cache)
+    }
+
+    fn remove(&self, user_uid: &Uuid, session_uid: &Uuid) -> Result<(), SessionServiceError> {
+        self.end(session_uid)?;
+        self.redis
+            .remove_from_set(&Self::user_sessions_key(user_uid), &session_uid.to_string())
+            .map_err(|_| SessionServiceError::Cache)
+    }
+
+    fn find(&self, session_uid: &Uuid) -> Result<Option<SessionData>, SessionServiceError> {
+        let value = self
+            .redis
+            .get_pair(&Self::session_key(session_uid))
+            .map_err(|_| SessionServiceError::Cache)?;
+
+        match value {
+            Some(value) => serde_json::from_str(&value)
+                .map(Some)
+                .map_err(|_| SessionServiceError::Serialization),
+            None => Ok(None),
+        }
+    }
+
+    fn save(&self, session: &SessionData, ttl: usize) -> Result<(), SessionServiceError> {
+        let value =
+            serde_json::to_string(session).map_err(|_| SessionServiceError::Serialization)?;
+
+        self.redis
+            .add_pair(&Self::session_key(&session.uid), &value, ttl)
+            .map_err(|_| SessionServiceError::Cache)?;
+
+        Ok(())
+    }
+
+    fn user_sessions_key(user_uid: &Uuid) -> String {
+        format!("user_sessions:{}", user_uid)
+    }
+
+    fn session_key(session_uid: &Uuid) -> String {
+        format!("session:{}", session_uid)
+    }
+}
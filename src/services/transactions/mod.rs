@@ -0,0 +1,511 @@
+use std::sync::Arc;
+
+use chrono::NaiveDateTime;
+use diesel::{insert_into, prelude::*, update};
+use uuid::Uuid;
+
+use super::{
+    dto::law_transaction::{CreateLawTransactionDto, LawTransactionParty, LawTransactionsQuery},
+    pagination::Pagination,
+};
+use crate::db::{
+    models::{
+        custom_types::{
+            invoices_statuses::InvoicesStatuses, law_transaction_statuses::LawTransactionsStatues,
+        },
+        law_transactions::LawTransactions,
+        service::Service,
+    },
+    orm::schema::{auth_data, court_sides, invoices, law_transactions, services, user_profiles},
+    Db, DbError, DbProvider,
+};
+
+#[derive(Debug)]
+pub enum TransactionServiceError {
+    NotFound,
+    UserNotFound,
+    ServiceNotFound,
+    CourtCaseNotFound,
+    SelfEngagement,
+    EmailNotVerified,
+    NotAllowed,
+    IllegalTransition,
+    Invoiced,
+    InvalidPage,
+    Query,
+    Insert,
+    Update,
+}
+
+/// Only the timestamp of the transition being made is set
+#[derive(AsChangeset)]
+#[diesel(table_name = law_transactions)]
+struct TransitionChangeset<'a> {
+    status: LawTransactionsStatues,
+    reason: Option<&'a str>,
+    accepted_at: Option<NaiveDateTime>,
+    declined_at: Option<NaiveDateTime>,
+    completed_at: Option<NaiveDateTime>,
+    cancelled_at: Option<NaiveDateTime>,
+}
+
+/// Deals between clients and lawyers. A client engages a lawyer for one of
+/// their services, the lawyer accepts or declines and later completes the
+/// work, either side may cancel before it is done.
+pub struct TransactionService {
+    db: Arc<Db>,
+}
+
+impl TransactionService {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+
+    pub fn create(
+        &self,
+        auth_uid: &Uuid,
+        dto: &CreateLawTransactionDto,
+    ) -> Result<LawTransactions, DbError<TransactionServiceError>> {
+        self.db.transaction(|conn| {
+            let (client_uid, email_verified) = auth_data::table
+                .find(auth_uid)
+                .select((auth_data::dsl::profile_uid, auth_data::dsl::email_verified))
+                .first::<(Uuid, bool)>(conn)
+                .optional()
+                .map_err(|_| TransactionServiceError::Query)?
+                .ok_or(TransactionServiceError::UserNotFound)?;
+
+            // Read from the account rather than the token, which keeps the
+            // claim it was issued with until refreshed
+            if !email_verified {
+                return Err(TransactionServiceError::EmailNotVerified);
+            }
+
+            let service = services::table
+                .find(dto.service_uid)
+                .filter(services::dsl::archived_at.is_null())
+                .first::<Service>(conn)
+                .optional()
+                .map_err(|_| TransactionServiceError::Query)?
+                .ok_or(TransactionServiceError::ServiceNotFound)?;
+
+            let law_active = diesel::select(diesel::dsl::exists(
+                user_profiles::table
+                    .filter(user_profiles::dsl::uid.eq(service.law_uid))
+                    .filter(user_profiles::dsl::law_profile.is_not_null())
+                    .filter(user_profiles::dsl::deleted_at.is_null())
+                    .filter(user_profiles::dsl::blocked_at.is_null()),
+            ))
+            .get_result::<bool>(conn)
+            .map_err(|_| TransactionServiceError::Query)?;
+
+            if !law_active {
+                return Err(TransactionServiceError::ServiceNotFound);
+            }
+
+            if service.law_uid == client_uid {
+                return Err(TransactionServiceError::SelfEngagement);
+            }
+
+            // Cases the client is not a side of look the same as missing ones
+            if let Some(court_case_uid) = dto.court_case_uid {
+                let is_side = diesel::select(diesel::dsl::exists(
+                    court_sides::table
+                        .filter(court_sides::dsl::court_case_uid.eq(court_case_uid))
+                        .filter(court_sides::dsl::user_uid.eq(client_uid)),
+                ))
+                .get_result::<bool>(conn)
+                .map_err(|_| TransactionServiceError::Query)?;
+
+                if !is_side {
+                    return Err(TransactionServiceError::CourtCaseNotFound);
+                }
+            }
+
+            insert_into(law_transactions::dsl::law_transactions)
+                .values((
+                    law_transactions::dsl::client_uid.eq(client_uid),
+                    law_transactions::dsl::law_uid.eq(service.law_uid),
+                    law_transactions::dsl::service_uid.eq(service.uid),
+                    law_transactions::dsl::court_case_uid.eq(dto.court_case_uid),
+                    law_transactions::dsl::cost_minor.eq(service.cost),
+                    law_transactions::dsl::currency.eq(&service.currency),
+                    law_transactions::dsl::status.eq(LawTransactionsStatues::Started),
+                ))
+                .get_result(conn)
+                .map_err(|err| {
+                    log::error!("{}", err);
+                    TransactionServiceError::Insert
+                })
+        })
+    }
+
+    pub fn get_own(
+        &self,
+        auth_uid: &Uuid,
+        filters: &LawTransactionsQuery,
+    ) -> Result<Vec<LawTransactions>, DbError<TransactionServiceError>> {
+        let pagination = Pagination::new(filters.page, filters.per_page)
+            .ok_or(DbError::Execution(TransactionServiceError::InvalidPage))?;
+
+        self.db.apply(|conn| {
+            let profile_uid = Self::find_profile_uid(conn, auth_uid)?;
+            let mut query = law_transactions::table.into_boxed();
+
+            query = match filters.party {
+                Some(LawTransactionParty::Client) => {
+                    query.filter(law_transactions::dsl::client_uid.eq(profile_uid))
+                }
+                Some(LawTransactionParty::Law) => {
+                    query.filter(law_transactions::dsl::law_uid.eq(profile_uid))
+                }
+                None => query.filter(
+                    law_transactions::dsl::client_uid
+                        .eq(profile_uid)
+                        .or(law_transactions::dsl::law_uid.eq(profile_uid)),
+                ),
+            };
+
+            if let Some(status) = filters.status {
+                query = query.filter(law_transactions::dsl::status.eq(status));
+            }
+
+            query
+                .order(law_transactions::dsl::created_at.desc())
+                .offset(pagination.offset)
+                .limit(pagination.per_page)
+                .load(conn)
+                .map_err(|_| TransactionServiceError::Query)
+        })
+    }
+
+    pub fn get(
+        &self,
+        auth_uid: &Uuid,
+        uid: &Uuid,
+    ) -> Result<LawTransactions, DbError<TransactionServiceError>> {
+        self.db.apply(|conn| {
+            let profile_uid = Self::find_profile_uid(conn, auth_uid)?;
+            let transaction = law_transactions::table
+                .find(uid)
+                .first::<LawTransactions>(conn)
+                .optional()
+                .map_err(|_| TransactionServiceError::Query)?
+                .ok_or(TransactionServiceError::NotFound)?;
+
+            Self::party(&transaction, &profile_uid).ok_or(TransactionServiceError::NotFound)?;
+
+            Ok(transaction)
+        })
+    }
+
+    /// Moves the deal to `next` if the state machine and the side of the
+    /// user allow it. Invoiced deals are cancelled once the invoice is void.
+    pub fn transition(
+        &self,
+        auth_uid: &Uuid,
+        uid: &Uuid,
+        next: LawTransactionsStatues,
+        reason: Option<&str>,
+    ) -> Result<LawTransactions, DbError<TransactionServiceError>> {
+        self.db.transaction(|conn| {
+            let profile_uid = Self::find_profile_uid(conn, auth_uid)?;
+            let transaction = law_transactions::table
+                .find(uid)
+                .for_update()
+                .first::<LawTransactions>(conn)
+                .optional()
+                .map_err(|_| TransactionServiceError::Query)?
+                .ok_or(TransactionServiceError::NotFound)?;
+            let party =
+                Self::party(&transaction, &profile_uid).ok_or(TransactionServiceError::NotFound)?;
+
+            if !Self::is_allowed(party, next) {
+                return Err(TransactionServiceError::NotAllowed);
+            }
+
+            if !Self::can_transition(transaction.status, next) {
+                return Err(TransactionServiceError::IllegalTransition);
+            }
+
+            // Billing locks the deal as well, so no invoice shows up meanwhile
+            if next == LawTransactionsStatues::Cancelled {
+                let invoiced = diesel::select(diesel::dsl::exists(
+                    invoices::table
+                        .filter(invoices::dsl::transaction_uid.eq(transaction.uid))
+                        .filter(invoices::dsl::status.ne(InvoicesStatuses::Void)),
+                ))
+                .get_result::<bool>(conn)
+                .map_err(|_| TransactionServiceError::Query)?;
+
+                if invoiced {
+                    return Err(TransactionServiceError::Invoiced);
+                }
+            }
+
+            let now = chrono::Utc::now().naive_utc();
+            let at = |status: LawTransactionsStatues| (next == status).then_some(now);
+
+            update(law_transactions::table.find(transaction.uid))
+                .set(&TransitionChangeset {
+                    status: next,
+                    reason,
+                    accepted_at: at(LawTransactionsStatues::Processing),
+                    declined_at: at(LawTransactionsStatues::Declined),
+                    completed_at: at(LawTransactionsStatues::Completed),
+                    cancelled_at: at(LawTransactionsStatues::Cancelled),
+                })
+                .get_result(conn)
+                .map_err(|err| {
+                    log::error!("{}", err);
+                    TransactionServiceError::Update
+                })
+        })
+    }
+
+    /// Started deals are accepted, declined or cancelled, accepted ones are
+    /// completed or cancelled. Completed, declined and cancelled are final.
+    fn can_transition(from: LawTransactionsStatues, to: LawTransactionsStatues) -> bool {
+        use LawTransactionsStatues::*;
+
+        matches!(
+            (from, to),
+            (Started, Processing)
+                | (Started, Declined)
+                | (Started, Cancelled)
+                | (Processing, Completed)
+                | (Processing, Cancelled)
+        )
+    }
+
+    /// The lawyer drives the deal, both sides may walk away
+    fn is_allowed(party: LawTransactionParty, to: LawTransactionsStatues) -> bool {
+        match to {
+            LawTransactionsStatues::Cancelled => true,
+            LawTransactionsStatues::Processing
+            | LawTransactionsStatues::Declined
+            | LawTransactionsStatues::Completed => party == LawTransactionParty::Law,
+            LawTransactionsStatues::Started => false,
+        }
+    }
+
+    /// Deals of other users look the same as missing ones
+    fn party(transaction: &LawTransactions, profile_uid: &Uuid) -> Option<LawTransactionParty> {
+        if transaction.client_uid.as_ref() == Some(profile_uid) {
+            Some(LawTransactionParty::Client)
+        } else if transaction.law_uid.as_ref() == Some(profile_uid) {
+            Some(LawTransactionParty::Law)
+        } else {
+            None
+        }
+    }
+
+    fn find_profile_uid(
+        conn: &mut PgConnection,
+        auth_uid: &Uuid,
+    ) -> Result<Uuid, TransactionServiceError> {
+        auth_data::table
+            .find(auth_uid)
+            .select(auth_data::dsl::profile_uid)
+            .first::<Uuid>(conn)
+            .optional()
+            .map_err(|_| TransactionServiceError::Query)?
+            .ok_or(TransactionServiceError::UserNotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{
+            models::custom_types::{
+                amount::Amount, court_cases_decisions::CourtCasesDecisions,
+                court_cases_kinds::CourtCasesKinds,
+                court_sides_case_statuses::CourtSidesCaseStatuses,
+                court_sides_kinds::CourtSidesKinds, user_profiles_roles::UserProfilesRoles,
+            },
+            orm::schema::court_cases,
+        },
+        services::{
+            billing::BillingService, dto::billing::CreateInvoiceDto, payments::LocalPaymentProvider,
+        },
+        test_support,
+    };
+    use LawTransactionsStatues::*;
+
+    const STATUSES: [LawTransactionsStatues; 5] =
+        [Started, Processing, Declined, Completed, Cancelled];
+
+    #[test]
+    fn transitions() {
+        let allowed = [
+            (Started, Processing),
+            (Started, Declined),
+            (Started, Cancelled),
+            (Processing, Completed),
+            (Processing, Cancelled),
+        ];
+
+        for from in STATUSES {
+            for to in STATUSES {
+                assert_eq!(
+                    TransactionService::can_transition(from, to),
+                    allowed.contains(&(from, to)),
+                    "{:?} -> {:?}",
+                    from,
+                    to
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn lawyer_drives_the_deal() {
+        for to in STATUSES {
+            assert_eq!(
+                TransactionService::is_allowed(LawTransactionParty::Law, to),
+                to != Started,
+                "{:?}",
+                to
+            );
+            assert_eq!(
+                TransactionService::is_allowed(LawTransactionParty::Client, to),
+                to == Cancelled,
+                "{:?}",
+                to
+            );
+        }
+    }
+
+    fn create_court_case(db: &Db, side_uid: &Uuid) -> Uuid {
+        db.transaction(|conn| {
+            let case_uid = insert_into(court_cases::table)
+                .values((
+                    court_cases::dsl::number.eq(Uuid::new_v4().simple().to_string()),
+                    court_cases::dsl::judge_fullname.eq("Judge"),
+                    court_cases::dsl::decision.eq(CourtCasesDecisions::Processing),
+                    court_cases::dsl::kind.eq(CourtCasesKinds::Civil),
+                ))
+                .returning(court_cases::dsl::uid)
+                .get_result::<Uuid>(conn)?;
+
+            insert_into(court_sides::table)
+                .values((
+                    court_sides::dsl::court_case_uid.eq(case_uid),
+                    court_sides::dsl::user_uid.eq(side_uid),
+                    court_sides::dsl::kind.eq(CourtSidesKinds::First),
+                    court_sides::dsl::case_status.eq(CourtSidesCaseStatuses::Unknown),
+                ))
+                .execute(conn)?;
+
+            Ok::<_, diesel::result::Error>(case_uid)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn court_case_must_be_the_clients() {
+        let db = test_support::db();
+        let service = TransactionService::new(db.clone());
+        let (_, service_uid) =
+            test_support::create_law_service(&db, Amount::from_minor(100_000), "RUB");
+        let client = test_support::create_client(&db);
+        let other = test_support::create_user(&db, UserProfilesRoles::User, None);
+        let own_case = create_court_case(&db, &client.profile_uid);
+        let other_case = create_court_case(&db, &other.profile_uid);
+        let engage = |court_case_uid| CreateLawTransactionDto {
+            service_uid,
+            court_case_uid,
+        };
+
+        for court_case_uid in [other_case, Uuid::new_v4()] {
+            assert!(matches!(
+                service.create(&client.auth_uid, &engage(Some(court_case_uid))),
+                Err(DbError::Execution(
+                    TransactionServiceError::CourtCaseNotFound
+                ))
+            ));
+        }
+
+        let transaction = service
+            .create(&client.auth_uid, &engage(Some(own_case)))
+            .unwrap();
+        assert_eq!(transaction.court_case_uid, Some(own_case));
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn client_must_verify_the_email() {
+        let db = test_support::db();
+        let service = TransactionService::new(db.clone());
+        let (_, service_uid) =
+            test_support::create_law_service(&db, Amount::from_minor(100_000), "RUB");
+        let client = test_support::create_user(&db, UserProfilesRoles::User, None);
+        let dto = CreateLawTransactionDto {
+            service_uid,
+            court_case_uid: None,
+        };
+
+        assert!(matches!(
+            service.create(&client.auth_uid, &dto),
+            Err(DbError::Execution(
+                TransactionServiceError::EmailNotVerified
+            ))
+        ));
+
+        test_support::verify_email(&db, &client.auth_uid);
+
+        assert!(service.create(&client.auth_uid, &dto).is_ok());
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn invoiced_deal_is_cancelled_once_the_invoice_is_void() {
+        let db = test_support::db();
+        let service = TransactionService::new(db.clone());
+        let billing = BillingService::new(db.clone(), Arc::new(LocalPaymentProvider));
+        let (law, service_uid) =
+            test_support::create_law_service(&db, Amount::from_minor(100_000), "RUB");
+        let client = test_support::create_client(&db);
+        let transaction = service
+            .create(
+                &client.auth_uid,
+                &CreateLawTransactionDto {
+                    service_uid,
+                    court_case_uid: None,
+                },
+            )
+            .unwrap();
+
+        service
+            .transition(&law.auth_uid, &transaction.uid, Processing, None)
+            .unwrap();
+
+        let invoice = billing
+            .create(
+                &law.auth_uid,
+                &CreateInvoiceDto {
+                    transaction_uid: transaction.uid,
+                    items: None,
+                    tax_rate_bp: None,
+                },
+            )
+            .unwrap();
+
+        for auth_uid in [client.auth_uid, law.auth_uid] {
+            assert!(matches!(
+                service.transition(&auth_uid, &transaction.uid, Cancelled, None),
+                Err(DbError::Execution(TransactionServiceError::Invoiced))
+            ));
+        }
+
+        billing.void(&invoice.invoice.uid).unwrap();
+
+        let cancelled = service
+            .transition(&client.auth_uid, &transaction.uid, Cancelled, None)
+            .unwrap();
+        assert_eq!(cancelled.status, Cancelled);
+    }
+}
@@ -1,14 +1,41 @@
 use std::sync::Arc;
 
-use super::dto::user::{PassportOrmData, LawProfileWithUser};
+use super::{
+    dto::user::{
+        CaseKindStats, CaseStats, CurrentUser, CurrentUserPassport, LawDetails, LawProfileWithUser,
+        LawStats, LawsPage, LawsQuery, LawsSort,
+        PassportOrmData, UpdateCurrentUserDto, UserDetails, UserSummary, UsersQuery,
+    },
+    pagination::Pagination,
+    passports::PassportService,
+};
 use crate::db::{
-    models::{custom_types::user_profiles_roles::UserProfilesRoles, user_profiles::UserProfile, law_profiles::LawProfile, passports::Passport},
-    orm::schema::{user_profiles, law_profiles, passports},
+    models::{
+        custom_types::{
+            court_cases_kinds::CourtCasesKinds, court_sides_case_statuses::CourtSidesCaseStatuses,
+            user_profiles_roles::UserProfilesRoles,
+        },
+        user_profiles::UserProfile, law_profiles::LawProfile, passports::Passport, auth_data::AuthData, service::Service,
+    },
+    orm::schema::{user_profiles, law_profiles, passports, auth_data, files, services, court_sides, court_cases},
     Db, DbError, DbProvider,
 };
-use diesel::{insert_into, prelude::*, delete};
+use chrono::{Months, NaiveDate, NaiveDateTime, NaiveTime};
+use diesel::{
+    helper_types::{InnerJoin, IntoBoxed},
+    insert_into, pg::Pg, prelude::*, delete, update,
+};
 use uuid::Uuid;
 
+const DEFAULT_LAWS_PAGE_SIZE: i64 = 15;
+const MAX_LAWS_PAGE_SIZE: i64 = 100;
+
+type LawsBoxedQuery<'a> = IntoBoxed<
+    'a,
+    InnerJoin<InnerJoin<user_profiles::table, law_profiles::table>, passports::table>,
+    Pg,
+>;
+
 #[derive(Debug)]
 pub enum UserServiceError<T> {
     PassportCreation(T),
@@ -16,6 +43,14 @@ pub enum UserServiceError<T> {
     NotFound,
     GetLaws,
     DeleteLaws,
+    AvatarNotFound,
+    PassportNotFound,
+    PassportVerified,
+    UpdateProfile,
+    GetUsers,
+    InvalidPage,
+    SelfModification,
+    LawRoleChange,
 }
 
 pub struct UserService {
@@ -56,42 +91,381 @@ impl UserService {
         Ok(result)
     }
 
-    pub fn get_laws(&self, page: u64) -> Result<Vec<LawProfileWithUser>, DbError<UserServiceError<()>>> {
-        const LIMIT: i64 = 15;
+    pub fn get_current_user(
+        &self,
+        auth_uid: &Uuid,
+    ) -> Result<CurrentUser, DbError<UserServiceError<()>>> {
+        self.db.apply(|conn| Self::current_user(conn, auth_uid))
+    }
+
+    /// Names are a part of the passport, so they are locked together with
+    /// it once an employee has verified it
+    pub fn update_current_user(
+        &self,
+        auth_uid: &Uuid,
+        dto: &UpdateCurrentUserDto,
+    ) -> Result<CurrentUser, DbError<UserServiceError<()>>> {
+        self.db.transaction(|conn| {
+            let auth = Self::find_auth_data(conn, auth_uid)?;
+            let profile = Self::find_user_by_pk(conn, &auth.profile_uid)?;
+
+            if let Some(avatar_uid) = dto.avatar_uid {
+                let exists = files::table
+                    .find(avatar_uid)
+                    .select(files::dsl::uid)
+                    .first::<Uuid>(conn)
+                    .optional()
+                    .map_err(|_| UserServiceError::UpdateProfile)?
+                    .is_some();
+
+                if !exists {
+                    return Err(UserServiceError::AvatarNotFound);
+                }
+
+                update(user_profiles::table.find(profile.uid))
+                    .set(user_profiles::dsl::avatar_uid.eq(avatar_uid))
+                    .execute(conn)
+                    .map_err(|err| {
+                        log::error!("{}", err);
+                        UserServiceError::UpdateProfile
+                    })?;
+            }
+
+            let mut fields = vec![];
+
+            if dto.first_name.is_some() {
+                fields.push("first_name");
+            }
+
+            if dto.second_name.is_some() {
+                fields.push("second_name");
+            }
+
+            if dto.patronymic.is_some() {
+                fields.push("patronymic");
+            }
+
+            if !fields.is_empty() {
+                let passport_uid = profile
+                    .passport_uid
+                    .ok_or(UserServiceError::PassportNotFound)?;
+                let verified_at = passports::table
+                    .find(passport_uid)
+                    .select(passports::dsl::verified_at)
+                    .for_update()
+                    .first::<Option<NaiveDateTime>>(conn)
+                    .map_err(|_| UserServiceError::PassportNotFound)?;
+
+                if verified_at.is_some() {
+                    return Err(UserServiceError::PassportVerified);
+                }
+
+                update(passports::table.find(passport_uid))
+                    .set((
+                        dto.first_name
+                            .as_ref()
+                            .map(|first_name| passports::dsl::first_name.eq(first_name)),
+                        dto.second_name
+                            .as_ref()
+                            .map(|second_name| passports::dsl::second_name.eq(second_name)),
+                        dto.patronymic
+                            .as_ref()
+                            .map(|patronymic| passports::dsl::patronymic.eq(patronymic)),
+                    ))
+                    .execute(conn)
+                    .map_err(|err| {
+                        log::error!("{}", err);
+                        UserServiceError::UpdateProfile
+                    })?;
+
+                PassportService::add_history(conn, &passport_uid, auth_uid, "update", &fields)
+                    .map_err(|_| UserServiceError::UpdateProfile)?;
+            }
+
+            Self::current_user(conn, auth_uid)
+        })
+    }
+
+    pub fn get_users(
+        &self,
+        filters: &UsersQuery,
+    ) -> Result<Vec<UserSummary>, DbError<UserServiceError<()>>> {
+        let pagination = Pagination::new(filters.page, filters.per_page)
+            .ok_or(DbError::Execution(UserServiceError::InvalidPage))?;
 
         self.db.apply(|conn| {
-            Ok(
-                user_profiles::table
-                    .left_join(law_profiles::table)
-                    .left_join(passports::table)
-                    .filter(user_profiles::dsl::law_profile.is_not_null())
-                    .filter(user_profiles::dsl::passport_uid.is_not_null())
-                    // What?
-                    // Postgres: offset cannot be negative
-                    // Diesel: ...can... be...
-                    .offset((page as i64 - 1) * LIMIT)
-                    .limit(LIMIT)
-                    .load(conn)
-                    .map_err(|_| UserServiceError::GetLaws)?
-                    .into_iter()
-                    .map(|record: (UserProfile, Option<LawProfile>, Option<Passport>)| {
-                        let law = record.1.unwrap();
-                        let passport = record.2.unwrap();
-                        let user = record.0;
-
-                        LawProfileWithUser {
-                            uid: user.uid,
-                            avatar_uid: user.avatar_uid,
-                            law_uid: law.uid,
-                            itn: law.itn,
-                            start_activity_date: law.start_activity_date,
-                            first_name: passport.first_name,
-                            second_name: passport.second_name,
-                            patronymic: passport.patronymic,
-                        }
-                    })
-                    .collect::<Vec<LawProfileWithUser>>()
-            )
+            let mut query = user_profiles::table
+                .left_join(auth_data::table)
+                .left_join(passports::table)
+                .into_boxed();
+
+            if let Some(role) = filters.role {
+                query = query.filter(user_profiles::dsl::role.eq(role));
+            }
+
+            query = match filters.blocked {
+                Some(true) => query.filter(user_profiles::dsl::blocked_at.is_not_null()),
+                Some(false) => query.filter(user_profiles::dsl::blocked_at.is_null()),
+                None => query,
+            };
+
+            query = match filters.deleted {
+                Some(true) => query.filter(user_profiles::dsl::deleted_at.is_not_null()),
+                _ => query.filter(user_profiles::dsl::deleted_at.is_null()),
+            };
+
+            if let Some(search) = &filters.search {
+                let pattern = Self::like_pattern(search);
+
+                query = query.filter(
+                    auth_data::dsl::username
+                        .ilike(pattern.clone())
+                        .or(auth_data::dsl::email.ilike(pattern.clone()))
+                        .or(passports::dsl::first_name.ilike(pattern.clone()))
+                        .or(passports::dsl::second_name.ilike(pattern)),
+                );
+            }
+
+            Ok(query
+                .order(user_profiles::dsl::created_at.desc())
+                .offset(pagination.offset)
+                .limit(pagination.per_page)
+                .load::<(UserProfile, Option<AuthData>, Option<Passport>)>(conn)
+                .map_err(|err| {
+                    log::error!("{}", err);
+                    UserServiceError::GetUsers
+                })?
+                .into_iter()
+                .map(|(user, auth, passport)| Self::to_summary(user, auth, passport))
+                .collect())
+        })
+    }
+
+    /// Deleted profiles are found as well, so admins can look them up
+    pub fn get_user(
+        &self,
+        profile_uid: &Uuid,
+    ) -> Result<UserDetails, DbError<UserServiceError<()>>> {
+        self.db.apply(|conn| {
+            let (user, auth, passport) = user_profiles::table
+                .left_join(auth_data::table)
+                .left_join(passports::table)
+                .filter(user_profiles::dsl::uid.eq(profile_uid))
+                .first::<(UserProfile, Option<AuthData>, Option<Passport>)>(conn)
+                .optional()
+                .map_err(|_| UserServiceError::GetUsers)?
+                .ok_or(UserServiceError::NotFound)?;
+
+            let law = match user.law_profile {
+                Some(law_uid) => law_profiles::table
+                    .find(law_uid)
+                    .first::<LawProfile>(conn)
+                    .optional()
+                    .map_err(|_| UserServiceError::GetUsers)?,
+                None => None,
+            };
+            let avatar_uid = user.avatar_uid;
+
+            Ok(UserDetails {
+                summary: Self::to_summary(user, auth, passport),
+                avatar_uid,
+                law,
+            })
+        })
+    }
+
+    /// Returns the `auth_data` uid of the user, whose tokens carry the old role.
+    /// Nobody is switched into or out of `law` here.
+    pub fn change_role(
+        &self,
+        admin_uid: &Uuid,
+        profile_uid: &Uuid,
+        role: UserProfilesRoles,
+    ) -> Result<Option<Uuid>, DbError<UserServiceError<()>>> {
+        self.db.transaction(|conn| {
+            let auth_uid = Self::lock_managed(conn, admin_uid, profile_uid)?;
+            let current_role = user_profiles::table
+                .find(profile_uid)
+                .select(user_profiles::dsl::role)
+                .first::<UserProfilesRoles>(conn)
+                .map_err(|_| UserServiceError::GetUsers)?;
+
+            // Lawyers come with a law profile, they are made by approving a
+            // law application and never by switching the role
+            if role != current_role
+                && (role == UserProfilesRoles::Law || current_role == UserProfilesRoles::Law)
+            {
+                return Err(UserServiceError::LawRoleChange);
+            }
+
+            update(user_profiles::table.find(profile_uid))
+                .set(user_profiles::dsl::role.eq(role))
+                .execute(conn)
+                .map_err(|err| {
+                    log::error!("{}", err);
+                    UserServiceError::UpdateProfile
+                })?;
+
+            Ok(auth_uid)
+        })
+    }
+
+    /// Returns the `auth_data` uid of the user, whose sessions have to end
+    pub fn set_blocked(
+        &self,
+        admin_uid: &Uuid,
+        profile_uid: &Uuid,
+        blocked: bool,
+    ) -> Result<Option<Uuid>, DbError<UserServiceError<()>>> {
+        self.db.transaction(|conn| {
+            let auth_uid = Self::lock_managed(conn, admin_uid, profile_uid)?;
+            let blocked_at = if blocked {
+                Some(chrono::Utc::now().naive_utc())
+            } else {
+                None
+            };
+
+            update(user_profiles::table.find(profile_uid))
+                .set(user_profiles::dsl::blocked_at.eq(blocked_at))
+                .execute(conn)
+                .map_err(|err| {
+                    log::error!("{}", err);
+                    UserServiceError::UpdateProfile
+                })?;
+
+            Ok(auth_uid)
+        })
+    }
+
+    /// Court cases and transactions still refer to the profile, so it is
+    /// only marked as deleted. Returns the `auth_data` uid of the user.
+    pub fn soft_delete(
+        &self,
+        admin_uid: &Uuid,
+        profile_uid: &Uuid,
+    ) -> Result<Option<Uuid>, DbError<UserServiceError<()>>> {
+        self.db.transaction(|conn| {
+            let auth_uid = Self::lock_managed(conn, admin_uid, profile_uid)?;
+
+            update(user_profiles::table.find(profile_uid))
+                .set(user_profiles::dsl::deleted_at.eq(chrono::Utc::now().naive_utc()))
+                .execute(conn)
+                .map_err(|err| {
+                    log::error!("{}", err);
+                    UserServiceError::UpdateProfile
+                })?;
+
+            Ok(auth_uid)
+        })
+    }
+
+    pub fn get_laws(&self, filters: &LawsQuery) -> Result<LawsPage, DbError<UserServiceError<()>>> {
+        let pagination = Pagination::with_sizes(
+            filters.page,
+            filters.per_page,
+            DEFAULT_LAWS_PAGE_SIZE,
+            MAX_LAWS_PAGE_SIZE,
+        )
+        .ok_or(DbError::Execution(UserServiceError::InvalidPage))?;
+
+        self.db.apply(|conn| {
+            let total = Self::laws_query(filters)
+                .count()
+                .get_result::<i64>(conn)
+                .map_err(|err| {
+                    log::error!("{}", err);
+                    UserServiceError::GetLaws
+                })?;
+
+            let query = match filters.sort.unwrap_or_default() {
+                LawsSort::Name => Self::laws_query(filters)
+                    .order(passports::dsl::second_name.asc())
+                    .then_order_by(passports::dsl::first_name.asc()),
+                LawsSort::Experience => Self::laws_query(filters)
+                    .order(law_profiles::dsl::start_activity_date.asc()),
+                LawsSort::Newest => {
+                    Self::laws_query(filters).order(user_profiles::dsl::created_at.desc())
+                }
+            };
+
+            let items = query
+                // Equal sort keys would otherwise shuffle between pages
+                .then_order_by(user_profiles::dsl::uid.asc())
+                .offset(pagination.offset)
+                .limit(pagination.per_page)
+                .load::<(UserProfile, LawProfile, Passport)>(conn)
+                .map_err(|err| {
+                    log::error!("{}", err);
+                    UserServiceError::GetLaws
+                })?
+                .into_iter()
+                .map(|(user, law, passport)| LawProfileWithUser {
+                    uid: user.uid,
+                    avatar_uid: user.avatar_uid,
+                    law_uid: law.uid,
+                    itn: law.itn,
+                    start_activity_date: law.start_activity_date,
+                    first_name: passport.first_name,
+                    second_name: passport.second_name,
+                    patronymic: passport.patronymic,
+                })
+                .collect::<Vec<LawProfileWithUser>>();
+
+            Ok(LawsPage {
+                items,
+                total,
+                page: pagination.page,
+                per_page: pagination.per_page,
+                next_page: pagination.next_page(total),
+            })
+        })
+    }
+
+    /// Public profile of an active lawyer by the `user_profiles` uid
+    pub fn get_law(&self, uid: &Uuid) -> Result<LawDetails, DbError<UserServiceError<()>>> {
+        self.db.apply(|conn| {
+            let (user, law, passport) = user_profiles::table
+                .inner_join(law_profiles::table)
+                .inner_join(passports::table)
+                .filter(user_profiles::dsl::uid.eq(uid))
+                .filter(user_profiles::dsl::deleted_at.is_null())
+                .filter(user_profiles::dsl::blocked_at.is_null())
+                .first::<(UserProfile, LawProfile, Passport)>(conn)
+                .optional()
+                .map_err(|_| UserServiceError::GetLaws)?
+                .ok_or(UserServiceError::NotFound)?;
+
+            let services = services::table
+                .filter(services::dsl::law_uid.eq(user.uid))
+                .filter(services::dsl::archived_at.is_null())
+                .order(services::dsl::name.asc())
+                .load::<Service>(conn)
+                .map_err(|_| UserServiceError::GetLaws)?;
+
+            let sides = court_sides::table
+                .inner_join(court_cases::table)
+                .filter(court_sides::dsl::user_uid.eq(user.uid))
+                .select((court_cases::dsl::kind, court_sides::dsl::case_status))
+                .load::<(CourtCasesKinds, CourtSidesCaseStatuses)>(conn)
+                .map_err(|err| {
+                    log::error!("{}", err);
+                    UserServiceError::GetLaws
+                })?;
+
+            Ok(LawDetails {
+                profile: LawProfileWithUser {
+                    uid: user.uid,
+                    avatar_uid: user.avatar_uid,
+                    law_uid: law.uid,
+                    itn: law.itn,
+                    start_activity_date: law.start_activity_date,
+                    first_name: passport.first_name,
+                    second_name: passport.second_name,
+                    patronymic: passport.patronymic,
+                },
+                services,
+                stats: Self::law_stats(sides),
+            })
         })
     }
 
@@ -107,6 +481,262 @@ impl UserService {
         })
     }
 
+    /// Admins can't lock themselves out or take away their own role
+    fn lock_managed(
+        conn: &mut PgConnection,
+        admin_uid: &Uuid,
+        profile_uid: &Uuid,
+    ) -> Result<Option<Uuid>, UserServiceError<()>> {
+        let user = user_profiles::table
+            .find(profile_uid)
+            .for_update()
+            .first::<UserProfile>(conn)
+            .optional()
+            .map_err(|_| UserServiceError::GetUsers)?
+            .filter(|user| user.deleted_at.is_none())
+            .ok_or(UserServiceError::NotFound)?;
+        let auth_uid = auth_data::table
+            .filter(auth_data::dsl::profile_uid.eq(user.uid))
+            .select(auth_data::dsl::uid)
+            .first::<Uuid>(conn)
+            .optional()
+            .map_err(|_| UserServiceError::GetUsers)?;
+
+        if auth_uid.as_ref() == Some(admin_uid) {
+            return Err(UserServiceError::SelfModification);
+        }
+
+        Ok(auth_uid)
+    }
+
+    fn to_summary(
+        user: UserProfile,
+        auth: Option<AuthData>,
+        passport: Option<Passport>,
+    ) -> UserSummary {
+        let (auth_uid, username, email, email_verified, mfa_enabled) = match auth {
+            Some(auth) => (
+                Some(auth.uid),
+                Some(auth.username),
+                Some(auth.email),
+                auth.email_verified,
+                auth.totp_enabled,
+            ),
+            None => (None, None, None, false, false),
+        };
+        let (first_name, second_name, patronymic, passport_verified) = match passport {
+            Some(passport) => (
+                Some(passport.first_name),
+                Some(passport.second_name),
+                passport.patronymic,
+                passport.verified_at.is_some(),
+            ),
+            None => (None, None, None, false),
+        };
+
+        UserSummary {
+            uid: user.uid,
+            auth_uid,
+            username,
+            email,
+            email_verified,
+            mfa_enabled,
+            role: user.role,
+            first_name,
+            second_name,
+            patronymic,
+            passport_verified,
+            law_profile: user.law_profile,
+            blocked_at: user.blocked_at,
+            deleted_at: user.deleted_at,
+            created_at: user.created_at,
+        }
+    }
+
+    /// Active lawyers matching the filters, shared by the page and its count
+    fn laws_query(filters: &LawsQuery) -> LawsBoxedQuery<'_> {
+        let mut query = user_profiles::table
+            .inner_join(law_profiles::table)
+            .inner_join(passports::table)
+            .filter(user_profiles::dsl::deleted_at.is_null())
+            .filter(user_profiles::dsl::blocked_at.is_null())
+            .into_boxed();
+
+        if let Some(name) = &filters.name {
+            let pattern = Self::like_pattern(name);
+
+            query = query.filter(
+                passports::dsl::first_name
+                    .ilike(pattern.clone())
+                    .or(passports::dsl::second_name.ilike(pattern.clone()))
+                    .or(passports::dsl::patronymic.ilike(pattern)),
+            );
+        }
+
+        let today = chrono::Utc::now().date_naive();
+
+        if let Some(years) = filters.min_experience {
+            let started_before = today
+                .checked_sub_months(Months::new(years.saturating_mul(12)))
+                .unwrap_or(NaiveDate::MIN)
+                .and_time(NaiveTime::MIN);
+
+            query = query.filter(law_profiles::dsl::start_activity_date.le(started_before));
+        }
+
+        if let Some(years) = filters.max_experience {
+            let started_after = today
+                .checked_sub_months(Months::new(years.saturating_add(1).saturating_mul(12)))
+                .unwrap_or(NaiveDate::MIN)
+                .and_time(NaiveTime::MIN);
+
+            query = query.filter(law_profiles::dsl::start_activity_date.gt(started_after));
+        }
+
+        if filters.service.is_some()
+            || filters.min_cost.is_some()
+            || filters.max_cost.is_some()
+            || filters.currency.is_some()
+        {
+            let mut services_query = services::table
+                .select(services::dsl::law_uid)
+                .filter(services::dsl::archived_at.is_null())
+                .into_boxed();
+
+            if let Some(service) = &filters.service {
+                services_query =
+                    services_query.filter(services::dsl::name.ilike(Self::like_pattern(service)));
+            }
+
+            if let Some(min_cost) = filters.min_cost {
+                services_query = services_query.filter(services::dsl::cost_minor.ge(min_cost));
+            }
+
+            if let Some(max_cost) = filters.max_cost {
+                services_query = services_query.filter(services::dsl::cost_minor.le(max_cost));
+            }
+
+            if let Some(currency) = &filters.currency {
+                services_query = services_query.filter(services::dsl::currency.eq(currency));
+            }
+
+            query = query.filter(user_profiles::dsl::uid.eq_any(services_query));
+        }
+
+        if let Some(case_kind) = filters.case_kind {
+            query = query.filter(
+                user_profiles::dsl::uid.nullable().eq_any(
+                    court_sides::table
+                        .inner_join(court_cases::table)
+                        .filter(court_cases::dsl::kind.eq(case_kind))
+                        .select(court_sides::dsl::user_uid),
+                ),
+            );
+        }
+
+        query
+    }
+
+    fn law_stats(sides: Vec<(CourtCasesKinds, CourtSidesCaseStatuses)>) -> LawStats {
+        let mut overall = CaseStats::default();
+        let mut by_kind: Vec<CaseKindStats> = vec![];
+
+        for (kind, status) in sides {
+            overall.add(status);
+
+            match by_kind.iter_mut().find(|stats| stats.kind == kind) {
+                Some(stats) => stats.stats.add(status),
+                None => {
+                    let mut stats = CaseStats::default();
+
+                    stats.add(status);
+                    by_kind.push(CaseKindStats { kind, stats });
+                }
+            }
+        }
+
+        by_kind.sort_by_key(|kind| std::cmp::Reverse(kind.stats.total));
+
+        let decided = overall.wins + overall.losses;
+        let win_rate = if decided > 0 {
+            Some(overall.wins as f64 / decided as f64)
+        } else {
+            None
+        };
+
+        LawStats {
+            overall,
+            win_rate,
+            by_kind,
+        }
+    }
+
+    /// Matches the value anywhere, wildcards in it are taken literally
+    fn like_pattern(value: &str) -> String {
+        format!(
+            "%{}%",
+            value
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        )
+    }
+
+    fn find_auth_data(
+        conn: &mut PgConnection,
+        auth_uid: &Uuid,
+    ) -> Result<AuthData, UserServiceError<()>> {
+        auth_data::table
+            .find(auth_uid)
+            .first(conn)
+            .map_err(|_| UserServiceError::NotFound)
+    }
+
+    fn current_user(
+        conn: &mut PgConnection,
+        auth_uid: &Uuid,
+    ) -> Result<CurrentUser, UserServiceError<()>> {
+        let auth = Self::find_auth_data(conn, auth_uid)?;
+        let profile = Self::find_user_by_pk(conn, &auth.profile_uid)?;
+
+        let passport = match profile.passport_uid {
+            Some(passport_uid) => passports::table
+                .find(passport_uid)
+                .first::<Passport>(conn)
+                .optional()
+                .map_err(|_| UserServiceError::NotFound)?
+                .map(|passport| CurrentUserPassport {
+                    first_name: passport.first_name,
+                    second_name: passport.second_name,
+                    patronymic: passport.patronymic,
+                    verified: passport.verified_at.is_some(),
+                }),
+            None => None,
+        };
+
+        let law_profile = match profile.law_profile {
+            Some(law_uid) => law_profiles::table
+                .find(law_uid)
+                .first::<LawProfile>(conn)
+                .optional()
+                .map_err(|_| UserServiceError::NotFound)?,
+            None => None,
+        };
+
+        Ok(CurrentUser {
+            uid: profile.uid,
+            username: auth.username,
+            email: auth.email,
+            email_verified: auth.email_verified,
+            mfa_enabled: auth.totp_enabled,
+            role: profile.role,
+            avatar_uid: profile.avatar_uid,
+            passport,
+            law_profile,
+            created_at: profile.created_at,
+        })
+    }
+
     fn create_passport(
         conn: &mut PgConnection,
         data: &PassportOrmData,
@@ -118,7 +748,7 @@ impl UserService {
             .map_err(UserServiceError::PassportCreation)
     }
 
-    fn create_profile(
+    pub fn create_profile(
         conn: &mut PgConnection,
         passport_uid: &Option<Uuid>,
         role: &UserProfilesRoles,
@@ -133,3 +763,116 @@ impl UserService {
             .map_err(UserServiceError::ProfileCreation)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn role(profile_uid: &Uuid) -> UserProfilesRoles {
+        test_support::db()
+            .apply(|conn| {
+                user_profiles::table
+                    .find(profile_uid)
+                    .select(user_profiles::dsl::role)
+                    .first(conn)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn law_stats_without_cases() {
+        let stats = UserService::law_stats(vec![]);
+
+        assert_eq!(stats.overall.total, 0);
+        assert_eq!(stats.win_rate, None);
+        assert!(stats.by_kind.is_empty());
+    }
+
+    #[test]
+    fn law_stats_count_decided_cases_only() {
+        use CourtSidesCaseStatuses::*;
+
+        let stats = UserService::law_stats(vec![
+            (CourtCasesKinds::Arbitration, Unknown),
+            (CourtCasesKinds::Civil, Winning),
+            (CourtCasesKinds::Criminal, Loss),
+            (CourtCasesKinds::Civil, Loss),
+            (CourtCasesKinds::Civil, Unknown),
+            (CourtCasesKinds::Criminal, Winning),
+            (CourtCasesKinds::Civil, Winning),
+        ]);
+
+        assert_eq!(
+            (
+                stats.overall.total,
+                stats.overall.wins,
+                stats.overall.losses,
+                stats.overall.unknown
+            ),
+            (7, 3, 2, 2)
+        );
+        assert_eq!(stats.win_rate, Some(0.6));
+
+        // Most frequent kinds first
+        let by_kind = stats
+            .by_kind
+            .iter()
+            .map(|kind| {
+                (
+                    kind.kind,
+                    kind.stats.total,
+                    kind.stats.wins,
+                    kind.stats.losses,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            by_kind,
+            vec![
+                (CourtCasesKinds::Civil, 4, 2, 1),
+                (CourtCasesKinds::Criminal, 2, 1, 1),
+                (CourtCasesKinds::Arbitration, 1, 0, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn law_stats_without_decisions_have_no_win_rate() {
+        let stats = UserService::law_stats(vec![
+            (CourtCasesKinds::Civil, CourtSidesCaseStatuses::Unknown),
+            (CourtCasesKinds::Civil, CourtSidesCaseStatuses::Unknown),
+        ]);
+
+        assert_eq!(stats.overall.total, 2);
+        assert_eq!(stats.overall.unknown, 2);
+        assert_eq!(stats.win_rate, None);
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn law_role_is_not_switched_directly() {
+        let db = test_support::db();
+        let service = UserService::new(db.clone());
+        let admin_uid = Uuid::new_v4();
+        let user = test_support::create_user(&db, UserProfilesRoles::User, None);
+        let law = test_support::create_user(&db, UserProfilesRoles::Law, None);
+
+        assert!(matches!(
+            service.change_role(&admin_uid, &user.profile_uid, UserProfilesRoles::Law),
+            Err(DbError::Execution(UserServiceError::LawRoleChange))
+        ));
+        assert!(matches!(
+            service.change_role(&admin_uid, &law.profile_uid, UserProfilesRoles::Employee),
+            Err(DbError::Execution(UserServiceError::LawRoleChange))
+        ));
+        assert_eq!(role(&law.profile_uid), UserProfilesRoles::Law);
+        assert_eq!(
+            service
+                .change_role(&admin_uid, &user.profile_uid, UserProfilesRoles::Employee)
+                .unwrap(),
+            Some(user.auth_uid)
+        );
+        assert_eq!(role(&user.profile_uid), UserProfilesRoles::Employee);
+    }
+}
@@ -0,0 +1,478 @@
+use std::sync::Arc;
+
+use data_encoding::HEXLOWER;
+use diesel::{prelude::*, update};
+use rand::RngCore;
+use ring::digest;
+use uuid::Uuid;
+
+use super::mailer::{Mail, Mailer};
+use crate::{
+    cache::Cache,
+    db::{models::auth_data::AuthData, orm::schema::auth_data, Db, DbError, DbProvider},
+};
+
+const EMAIL_VERIFICATION_LIFETIME_HOURS: i64 = 24;
+const PASSWORD_RESET_LIFETIME_MINUTES: i64 = 60;
+
+#[derive(Debug)]
+pub enum VerificationServiceError {
+    UserNotFound,
+    AlreadyVerified,
+    InvalidToken,
+    Cache,
+    Mail,
+    Update,
+}
+
+pub trait AccountLinksProvider {
+    /// Frontend url the links in emails point to
+    fn app_url(&self) -> &str;
+}
+
+#[derive(Clone, Copy)]
+enum TokenPurpose {
+    EmailVerification,
+    PasswordReset,
+}
+
+impl TokenPurpose {
+    fn prefix(self) -> &'static str {
+        match self {
+            TokenPurpose::EmailVerification => "email_verification",
+            TokenPurpose::PasswordReset => "password_reset",
+        }
+    }
+
+    fn lifetime(self) -> chrono::Duration {
+        match self {
+            TokenPurpose::EmailVerification => {
+                chrono::Duration::hours(EMAIL_VERIFICATION_LIFETIME_HOURS)
+            }
+            TokenPurpose::PasswordReset => {
+                chrono::Duration::minutes(PASSWORD_RESET_LIFETIME_MINUTES)
+            }
+        }
+    }
+}
+
+/// Email verification and password reset. Tokens are single-use, only their
+/// hashes are stored and issuing a new one invalidates the previous.
+pub struct VerificationService {
+    db: Arc<Db>,
+    redis: Arc<Cache>,
+    mailer: Arc<dyn Mailer>,
+}
+
+impl VerificationService {
+    pub fn new(db: Arc<Db>, redis: Arc<Cache>, mailer: Arc<dyn Mailer>) -> Self {
+        Self { db, redis, mailer }
+    }
+
+    pub fn send_email_verification(
+        &self,
+        uid: &Uuid,
+        links_provider: &impl AccountLinksProvider,
+    ) -> Result<(), DbError<VerificationServiceError>> {
+        let data = self.db.apply(|conn| {
+            auth_data::dsl::auth_data
+                .find(uid)
+                .first::<AuthData>(conn)
+                .map_err(|_| VerificationServiceError::UserNotFound)
+        })?;
+
+        if data.email_verified {
+            return Err(DbError::Execution(VerificationServiceError::AlreadyVerified));
+        }
+
+        let token = self
+            .issue_token(TokenPurpose::EmailVerification, &data.uid, &data.email)
+            .map_err(DbError::Execution)?;
+
+        self.send(Mail {
+            to: data.email,
+            subject: "Confirm your email".to_owned(),
+            body: format!(
+                "Open the link to confirm your email:\n{}/verify-email?token={}\n\nThe link is valid for {} hours.",
+                links_provider.app_url(),
+                token,
+                EMAIL_VERIFICATION_LIFETIME_HOURS
+            ),
+        })
+        .map_err(DbError::Execution)
+    }
+
+    pub fn verify_email(&self, token: &str) -> Result<Uuid, DbError<VerificationServiceError>> {
+        let (uid, email) = self
+            .take_token(TokenPurpose::EmailVerification, token)
+            .map_err(DbError::Execution)?;
+
+        self.db
+            .apply(|conn| Self::mark_email_verified(conn, &uid, &email))?;
+
+        Ok(uid)
+    }
+
+    /// Unknown emails are not reported to the caller, so the endpoint can't be
+    /// used to find out who is registered.
+    pub fn send_password_reset(
+        &self,
+        email: &str,
+        links_provider: &impl AccountLinksProvider,
+    ) -> Result<(), DbError<VerificationServiceError>> {
+        let data = self.db.apply(|conn| {
+            auth_data::dsl::auth_data
+                .filter(auth_data::dsl::email.eq(email))
+                .first::<AuthData>(conn)
+                .optional()
+                .map_err(|_| VerificationServiceError::UserNotFound)
+        })?;
+
+        let data = match data {
+            Some(data) if data.password.is_some() => data,
+            Some(_) => {
+                log::info!("Password reset requested for an external account");
+
+                return Ok(());
+            }
+            None => {
+                log::info!("Password reset requested for unknown email");
+
+                return Ok(());
+            }
+        };
+
+        let token = self
+            .issue_token(TokenPurpose::PasswordReset, &data.uid, &data.email)
+            .map_err(DbError::Execution)?;
+
+        self.send(Mail {
+            to: data.email,
+            subject: "Reset your password".to_owned(),
+            body: format!(
+                "Open the link to set a new password:\n{}/reset-password?token={}\n\nThe link is valid for {} minutes. If you didn't ask for it, ignore this email.",
+                links_provider.app_url(),
+                token,
+                PASSWORD_RESET_LIFETIME_MINUTES
+            ),
+        })
+        .map_err(DbError::Execution)
+    }
+
+    /// Uses up the reset token and returns the account it was issued for. The
+    /// link came to the mailbox, so the email counts as verified as well.
+    pub fn consume_password_reset(
+        &self,
+        token: &str,
+    ) -> Result<Uuid, DbError<VerificationServiceError>> {
+        let (uid, email) = self
+            .take_token(TokenPurpose::PasswordReset, token)
+            .map_err(DbError::Execution)?;
+
+        self.db
+            .apply(|conn| Self::mark_email_verified(conn, &uid, &email))?;
+
+        Ok(uid)
+    }
+
+    /// Tokens are only good for the email they were sent to, a link that
+    /// reached an address the account has since moved away from proves
+    /// nothing about the current one
+    fn mark_email_verified(
+        conn: &mut PgConnection,
+        uid: &Uuid,
+        email: &str,
+    ) -> Result<(), VerificationServiceError> {
+        let updated = update(auth_data::dsl::auth_data.find(uid))
+            .filter(auth_data::dsl::email.eq(email))
+            .set(auth_data::dsl::email_verified.eq(true))
+            .execute(conn)
+            .map_err(|_| VerificationServiceError::Update)?;
+
+        if updated == 0 {
+            return Err(VerificationServiceError::InvalidToken);
+        }
+
+        Ok(())
+    }
+
+    /// Drops the outstanding verification and reset tokens, used when the
+    /// email changes so links sent to the previous address stop working
+    pub fn revoke_tokens(&self, uid: &Uuid) -> Result<(), VerificationServiceError> {
+        for purpose in [TokenPurpose::EmailVerification, TokenPurpose::PasswordReset] {
+            self.revoke_token(purpose, uid)?;
+        }
+
+        Ok(())
+    }
+
+    fn revoke_token(
+        &self,
+        purpose: TokenPurpose,
+        uid: &Uuid,
+    ) -> Result<(), VerificationServiceError> {
+        let user_key = Self::user_token_key(purpose, uid);
+
+        if let Some(token_hash) = self
+            .redis
+            .get_pair(&user_key)
+            .map_err(|_| VerificationServiceError::Cache)?
+        {
+            self.redis
+                .remove(&Self::token_key(purpose, &token_hash))
+                .map_err(|_| VerificationServiceError::Cache)?;
+        }
+
+        self.redis
+            .remove(&user_key)
+            .map_err(|_| VerificationServiceError::Cache)
+    }
+
+    fn send(&self, mail: Mail) -> Result<(), VerificationServiceError> {
+        self.mailer
+            .send(&mail)
+            .map_err(|_| VerificationServiceError::Mail)
+    }
+
+    fn issue_token(
+        &self,
+        purpose: TokenPurpose,
+        uid: &Uuid,
+        email: &str,
+    ) -> Result<String, VerificationServiceError> {
+        let mut token = [0_u8; 32];
+
+        rand::thread_rng().fill_bytes(&mut token);
+
+        let token = HEXLOWER.encode(&token);
+        let token_hash = Self::hash_token(&token);
+        let user_key = Self::user_token_key(purpose, uid);
+        let ttl = (chrono::Utc::now() + purpose.lifetime()).timestamp() as usize;
+
+        self.revoke_token(purpose, uid)?;
+
+        self.redis
+            .add_pair(
+                &Self::token_key(purpose, &token_hash),
+                &format!("{}:{}", uid, email),
+                ttl,
+            )
+            .map_err(|_| VerificationServiceError::Cache)?;
+        self.redis
+            .add_pair(&user_key, &token_hash, ttl)
+            .map_err(|_| VerificationServiceError::Cache)?;
+
+        Ok(token)
+    }
+
+    /// Returns the account and the email the token was sent to
+    fn take_token(
+        &self,
+        purpose: TokenPurpose,
+        token: &str,
+    ) -> Result<(Uuid, String), VerificationServiceError> {
+        let (uid, email) = self
+            .redis
+            .take_pair(&Self::token_key(purpose, &Self::hash_token(token)))
+            .map_err(|_| VerificationServiceError::Cache)?
+            .and_then(|value| {
+                let (uid, email) = value.split_once(':')?;
+
+                Some((Uuid::parse_str(uid).ok()?, email.to_owned()))
+            })
+            .ok_or(VerificationServiceError::InvalidToken)?;
+
+        let _ = self.redis.remove(&Self::user_token_key(purpose, &uid));
+
+        Ok((uid, email))
+    }
+
+    fn hash_token(token: &str) -> String {
+        HEXLOWER.encode(digest::digest(&digest::SHA256, token.as_bytes()).as_ref())
+    }
+
+    fn token_key(purpose: TokenPurpose, token_hash: &str) -> String {
+        format!("{}:{}", purpose.prefix(), token_hash)
+    }
+
+    fn user_token_key(purpose: TokenPurpose, uid: &Uuid) -> String {
+        format!("{}_user:{}", purpose.prefix(), uid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::*;
+    use crate::{
+        db::models::custom_types::user_profiles_roles::UserProfilesRoles,
+        services::mailer::FileMailer,
+        test_support::{self, TestConfig},
+    };
+
+    fn service(mail_dir: &Path) -> VerificationService {
+        VerificationService::new(
+            test_support::db(),
+            test_support::cache(),
+            Arc::new(FileMailer::new(mail_dir.to_str().unwrap())),
+        )
+    }
+
+    fn mail_dir() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("mail-{}", Uuid::new_v4()))
+    }
+
+    /// The token from the link of the only email written to `dir`
+    fn mailed_token(dir: &Path, to: &str) -> String {
+        let mails = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect::<Vec<String>>();
+
+        assert_eq!(mails.len(), 1);
+        assert!(mails[0].starts_with(&format!("To: {}\n", to)));
+
+        let (_, token) = mails[0].split_once("?token=").unwrap();
+
+        token.lines().next().unwrap().to_owned()
+    }
+
+    fn email_verified(uid: &Uuid) -> bool {
+        test_support::db()
+            .apply(|conn| {
+                auth_data::dsl::auth_data
+                    .find(uid)
+                    .select(auth_data::dsl::email_verified)
+                    .first::<bool>(conn)
+            })
+            .unwrap()
+    }
+
+    fn change_email(uid: &Uuid, email: &str) {
+        test_support::db()
+            .apply(|conn| {
+                update(auth_data::dsl::auth_data.find(uid))
+                    .set(auth_data::dsl::email.eq(email))
+                    .execute(conn)
+            })
+            .unwrap();
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn email_verification_goes_through_mailbox() {
+        let dir = mail_dir();
+        let service = service(&dir);
+        let user = test_support::create_user(&test_support::db(), UserProfilesRoles::User, None);
+
+        service
+            .send_email_verification(&user.auth_uid, &TestConfig)
+            .unwrap();
+
+        let token = mailed_token(&dir, &user.email);
+
+        assert!(!email_verified(&user.auth_uid));
+        assert_eq!(service.verify_email(&token).unwrap(), user.auth_uid);
+        assert!(email_verified(&user.auth_uid));
+        assert!(matches!(
+            service.verify_email(&token),
+            Err(DbError::Execution(VerificationServiceError::InvalidToken))
+        ));
+        assert!(matches!(
+            service.send_email_verification(&user.auth_uid, &TestConfig),
+            Err(DbError::Execution(VerificationServiceError::AlreadyVerified))
+        ));
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn password_reset_goes_through_mailbox() {
+        let dir = mail_dir();
+        let service = service(&dir);
+        let user = test_support::create_user(
+            &test_support::db(),
+            UserProfilesRoles::User,
+            Some("password"),
+        );
+
+        service
+            .send_password_reset(&user.email, &TestConfig)
+            .unwrap();
+
+        let token = mailed_token(&dir, &user.email);
+
+        assert_eq!(
+            service.consume_password_reset(&token).unwrap(),
+            user.auth_uid
+        );
+        assert!(email_verified(&user.auth_uid));
+        assert!(matches!(
+            service.consume_password_reset(&token),
+            Err(DbError::Execution(VerificationServiceError::InvalidToken))
+        ));
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn password_reset_of_unknown_email_sends_nothing() {
+        let dir = mail_dir();
+
+        service(&dir)
+            .send_password_reset("nobody@example.com", &TestConfig)
+            .unwrap();
+
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn tokens_sent_to_previous_email_are_rejected() {
+        let dir = mail_dir();
+        let service = service(&dir);
+        let user = test_support::create_user(
+            &test_support::db(),
+            UserProfilesRoles::User,
+            Some("password"),
+        );
+
+        service
+            .send_password_reset(&user.email, &TestConfig)
+            .unwrap();
+
+        let token = mailed_token(&dir, &user.email);
+
+        change_email(&user.auth_uid, &format!("new-{}", user.email));
+
+        assert!(matches!(
+            service.consume_password_reset(&token),
+            Err(DbError::Execution(VerificationServiceError::InvalidToken))
+        ));
+        assert!(!email_verified(&user.auth_uid));
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+    fn revoked_tokens_are_rejected() {
+        let dir = mail_dir();
+        let service = service(&dir);
+        let user = test_support::create_user(
+            &test_support::db(),
+            UserProfilesRoles::User,
+            Some("password"),
+        );
+
+        service
+            .send_password_reset(&user.email, &TestConfig)
+            .unwrap();
+
+        let token = mailed_token(&dir, &user.email);
+
+        service.revoke_tokens(&user.auth_uid).unwrap();
+
+        assert!(matches!(
+            service.consume_password_reset(&token),
+            Err(DbError::Execution(VerificationServiceError::InvalidToken))
+        ));
+    }
+}
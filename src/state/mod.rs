@@ -3,26 +3,72 @@ use std::sync::Arc;
 use crate::{
     cache::Cache,
     config::Config,
-    services::{auth::AuthService, user::UserService},
+    services::{
+        api_keys::ApiKeyService, audit::AuditService, auth::AuthService,
+        billing::BillingService, catalog::CatalogService, law_applications::LawApplicationService,
+        ledger::LedgerService, login_throttle::LoginThrottleService, mfa::MfaService,
+        oidc::OidcService, passports::PassportService, session::SessionService,
+        transactions::TransactionService, user::UserService, verification::VerificationService,
+    },
 };
 
 pub struct AppState {
     auth_service: AuthService,
     user_service: UserService,
+    session_service: SessionService,
+    login_throttle_service: LoginThrottleService,
+    mfa_service: MfaService,
+    oidc_service: OidcService,
+    verification_service: VerificationService,
+    api_key_service: ApiKeyService,
+    audit_service: AuditService,
+    ledger_service: LedgerService,
+    passport_service: PassportService,
+    law_application_service: LawApplicationService,
+    catalog_service: CatalogService,
+    transaction_service: TransactionService,
+    billing_service: BillingService,
     config: Arc<Config>,
-    redis: Cache,
+    redis: Arc<Cache>,
 }
 
 impl AppState {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         auth_service: AuthService,
         user_service: UserService,
+        session_service: SessionService,
+        login_throttle_service: LoginThrottleService,
+        mfa_service: MfaService,
+        oidc_service: OidcService,
+        verification_service: VerificationService,
+        api_key_service: ApiKeyService,
+        audit_service: AuditService,
+        ledger_service: LedgerService,
+        passport_service: PassportService,
+        law_application_service: LawApplicationService,
+        catalog_service: CatalogService,
+        transaction_service: TransactionService,
+        billing_service: BillingService,
         config: Arc<Config>,
-        redis: Cache,
+        redis: Arc<Cache>,
     ) -> Self {
         Self {
             auth_service,
             user_service,
+            session_service,
+            login_throttle_service,
+            mfa_service,
+            oidc_service,
+            verification_service,
+            api_key_service,
+            audit_service,
+            ledger_service,
+            passport_service,
+            law_application_service,
+            catalog_service,
+            transaction_service,
+            billing_service,
             config,
             redis,
         }
@@ -36,6 +82,58 @@ impl AppState {
         &self.user_service
     }
 
+    pub fn session_service(&self) -> &SessionService {
+        &self.session_service
+    }
+
+    pub fn login_throttle_service(&self) -> &LoginThrottleService {
+        &self.login_throttle_service
+    }
+
+    pub fn mfa_service(&self) -> &MfaService {
+        &self.mfa_service
+    }
+
+    pub fn oidc_service(&self) -> &OidcService {
+        &self.oidc_service
+    }
+
+    pub fn verification_service(&self) -> &VerificationService {
+        &self.verification_service
+    }
+
+    pub fn api_key_service(&self) -> &ApiKeyService {
+        &self.api_key_service
+    }
+
+    pub fn audit_service(&self) -> &AuditService {
+        &self.audit_service
+    }
+
+    pub fn ledger_service(&self) -> &LedgerService {
+        &self.ledger_service
+    }
+
+    pub fn passport_service(&self) -> &PassportService {
+        &self.passport_service
+    }
+
+    pub fn law_application_service(&self) -> &LawApplicationService {
+        &self.law_application_service
+    }
+
+    pub fn catalog_service(&self) -> &CatalogService {
+        &self.catalog_service
+    }
+
+    pub fn transaction_service(&self) -> &TransactionService {
+        &self.transaction_service
+    }
+
+    pub fn billing_service(&self) -> &BillingService {
+        &self.billing_service
+    }
+
     pub fn config(&self) -> &Config {
         &self.config
     }
@@ -0,0 +1,397 @@
+//! Setup shared by the tests that need Postgres and Redis. They are ignored
+//! by default, run them against throwaway instances with
+//! `TEST_DATABASE_URL=... TEST_REDIS_URL=... cargo test -- --ignored`.
+
+use std::{
+    env,
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    sync::{Arc, LazyLock, Mutex, Once},
+    thread,
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{NaiveDate, NaiveTime};
+use diesel::{insert_into, prelude::*, update};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use ring::{
+    rand::SystemRandom,
+    signature::{Ed25519KeyPair, KeyPair},
+};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    cache::Cache,
+    db::{
+        models::custom_types::{amount::Amount, user_profiles_roles::UserProfilesRoles},
+        orm::schema::{auth_data, law_profiles, services, user_profiles},
+        Db, DbProvider,
+    },
+    services::{
+        auth::{
+            keys::JwtKeys, LoginAttemptsError, LoginAttemptsGuard, PasswordHashProvider,
+            SecretsProvider,
+        },
+        ledger::LedgerKeysProvider,
+        mfa::MfaPolicyProvider,
+        oidc::OidcSettings,
+        passports::{keys::PassportKeys, PassportKeysProvider},
+        user::{UserService, UserServiceError},
+        verification::AccountLinksProvider,
+    },
+    MIGRATIONS,
+};
+
+static MIGRATE: Once = Once::new();
+
+pub fn db() -> Arc<Db> {
+    let db = Arc::new(
+        Db::new(&env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set"))
+            .expect("Db instance error"),
+    );
+
+    MIGRATE.call_once(|| db.migrate(MIGRATIONS).expect("Error while migration"));
+
+    db
+}
+
+pub fn cache() -> Arc<Cache> {
+    Arc::new(
+        Cache::new(&env::var("TEST_REDIS_URL").expect("TEST_REDIS_URL must be set"))
+            .expect("Redis instance error"),
+    )
+}
+
+pub struct TestUser {
+    pub auth_uid: Uuid,
+    pub profile_uid: Uuid,
+    pub email: String,
+}
+
+/// Account with a unique email, `password` is hashed with [`TestConfig`]
+pub fn create_user(db: &Db, role: UserProfilesRoles, password: Option<&str>) -> TestUser {
+    let name = Uuid::new_v4().simple().to_string();
+    let email = format!("{}@example.com", name);
+    let password = password.map(|password| {
+        argon2::hash_encoded(
+            password.as_bytes(),
+            name.as_bytes(),
+            &TestConfig.argon2_config(),
+        )
+        .unwrap()
+    });
+
+    db.transaction(|conn| {
+        let profile_uid = UserService::create_profile(conn, &None, &role)?;
+        let auth_uid = insert_into(auth_data::dsl::auth_data)
+            .values((
+                auth_data::dsl::username.eq(&name),
+                auth_data::dsl::password.eq(&password),
+                auth_data::dsl::email.eq(&email),
+                auth_data::dsl::profile_uid.eq(profile_uid),
+            ))
+            .returning(auth_data::dsl::uid)
+            .get_result::<Uuid>(conn)
+            .map_err(UserServiceError::ProfileCreation)?;
+
+        Ok::<_, UserServiceError<diesel::result::Error>>(TestUser {
+            auth_uid,
+            profile_uid,
+            email: email.clone(),
+        })
+    })
+    .unwrap()
+}
+
+pub fn verify_email(db: &Db, auth_uid: &Uuid) {
+    db.apply(|conn| {
+        update(auth_data::table.find(auth_uid))
+            .set(auth_data::dsl::email_verified.eq(true))
+            .execute(conn)
+    })
+    .unwrap();
+}
+
+/// User with a confirmed email, as required to engage a lawyer
+pub fn create_client(db: &Db) -> TestUser {
+    let client = create_user(db, UserProfilesRoles::User, None);
+
+    verify_email(db, &client.auth_uid);
+
+    client
+}
+
+/// Lawyer with a law profile and one active service, returns the service uid
+pub fn create_law_service(db: &Db, cost: Amount, currency: &str) -> (TestUser, Uuid) {
+    let law = create_user(db, UserProfilesRoles::Law, None);
+    let itn = format!("{:012}", Uuid::new_v4().as_u128() % 1_000_000_000_000);
+    let started = NaiveDate::from_ymd_opt(2015, 3, 1)
+        .unwrap()
+        .and_time(NaiveTime::MIN);
+
+    let service_uid = db
+        .transaction(|conn| {
+            let law_uid = insert_into(law_profiles::table)
+                .values((
+                    law_profiles::dsl::itn.eq(&itn),
+                    law_profiles::dsl::start_activity_date.eq(started),
+                ))
+                .returning(law_profiles::dsl::uid)
+                .get_result::<Uuid>(conn)?;
+
+            update(user_profiles::table.find(law.profile_uid))
+                .set(user_profiles::dsl::law_profile.eq(law_uid))
+                .execute(conn)?;
+
+            insert_into(services::table)
+                .values((
+                    services::dsl::law_uid.eq(law.profile_uid),
+                    services::dsl::name.eq("Consultation"),
+                    services::dsl::cost_minor.eq(cost),
+                    services::dsl::currency.eq(currency),
+                ))
+                .returning(services::dsl::uid)
+                .get_result::<Uuid>(conn)
+        })
+        .unwrap();
+
+    (law, service_uid)
+}
+
+/// Cheap hashing and fixed keys, the second factor is required from admins
+pub struct TestConfig;
+
+static ACCESS_KEYS: LazyLock<JwtKeys> =
+    LazyLock::new(|| JwtKeys::from_secret(b"test access secret"));
+static LEDGER_KEYS: LazyLock<JwtKeys> =
+    LazyLock::new(|| JwtKeys::from_secret(b"test ledger secret"));
+static PASSPORT_KEYS: LazyLock<PassportKeys> = LazyLock::new(|| {
+    PassportKeys::new(
+        "test",
+        &[("test".to_owned(), vec![3; 32])],
+        b"test index key",
+    )
+    .unwrap()
+});
+
+impl PasswordHashProvider for TestConfig {
+    fn argon2_config(&self) -> argon2::Config<'_> {
+        argon2::Config {
+            mem_cost: 64,
+            time_cost: 1,
+            lanes: 1,
+            ..argon2::Config::rfc9106_low_mem()
+        }
+    }
+
+    fn legacy_salt(&self) -> &[u8] {
+        b"testsalt"
+    }
+}
+
+impl AccountLinksProvider for TestConfig {
+    fn app_url(&self) -> &str {
+        "http://localhost:3000"
+    }
+}
+
+impl SecretsProvider for TestConfig {
+    fn access_keys(&self) -> &JwtKeys {
+        &ACCESS_KEYS
+    }
+
+    fn refresh_secret(&self) -> &[u8] {
+        b"test refresh secret"
+    }
+}
+
+impl PassportKeysProvider for TestConfig {
+    fn passport_keys(&self) -> &PassportKeys {
+        &PASSPORT_KEYS
+    }
+}
+
+impl LedgerKeysProvider for TestConfig {
+    fn ledger_keys(&self) -> &JwtKeys {
+        &LEDGER_KEYS
+    }
+}
+
+impl MfaPolicyProvider for TestConfig {
+    fn mfa_issuer(&self) -> &str {
+        "Test"
+    }
+
+    fn mfa_required_for(&self, role: UserProfilesRoles) -> bool {
+        role == UserProfilesRoles::Admin
+    }
+}
+
+/// Login attempts guard that always answers the same
+pub struct TestAttempts(pub Option<fn() -> LoginAttemptsError>);
+
+impl LoginAttemptsGuard for TestAttempts {
+    fn check(&self, _: &Uuid) -> Result<(), LoginAttemptsError> {
+        self.0.map_or(Ok(()), |err| Err(err()))
+    }
+
+    fn failed(&self, _: &Uuid) {}
+
+    fn succeeded(&self, _: &Uuid) {}
+}
+
+/// OpenID provider on a local port. It serves the discovery document and an
+/// Ed25519 key, and its token endpoint answers with the ID token set by
+/// [`MockIdp::respond_with`].
+pub struct MockIdp {
+    pub issuer: String,
+    key: EncodingKey,
+    id_token: Arc<Mutex<String>>,
+}
+
+impl MockIdp {
+    pub const CLIENT_ID: &'static str = "test-client";
+
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        let public_key = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref())
+            .unwrap()
+            .public_key()
+            .as_ref()
+            .to_vec();
+        let id_token = Arc::new(Mutex::new(String::new()));
+        let discovery = serde_json::json!({
+            "issuer": issuer,
+            "authorization_endpoint": format!("{}/authorize", issuer),
+            "token_endpoint": format!("{}/token", issuer),
+            "jwks_uri": format!("{}/jwks", issuer),
+        });
+        let jwks = serde_json::json!({
+            "keys": [{
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "kid": "test",
+                "alg": "EdDSA",
+                "use": "sig",
+                "x": URL_SAFE_NO_PAD.encode(public_key),
+            }],
+        });
+        let served_token = id_token.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+                let mut reader = BufReader::new(&mut stream);
+                let mut request_line = String::new();
+                let mut content_length = 0;
+
+                reader.read_line(&mut request_line).unwrap();
+
+                loop {
+                    let mut line = String::new();
+
+                    reader.read_line(&mut line).unwrap();
+
+                    if line.trim().is_empty() {
+                        break;
+                    }
+
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+
+                let mut body = vec![0; content_length];
+
+                reader.read_exact(&mut body).unwrap();
+
+                let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+                let response = match path {
+                    "/.well-known/openid-configuration" => discovery.clone(),
+                    "/jwks" => jwks.clone(),
+                    "/token" => {
+                        serde_json::json!({ "id_token": *served_token.lock().unwrap() })
+                    }
+                    _ => serde_json::Value::Null,
+                }
+                .to_string();
+
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response.len(),
+                    response
+                );
+            }
+        });
+
+        Self {
+            issuer,
+            key: EncodingKey::from_ed_der(pkcs8.as_ref()),
+            id_token,
+        }
+    }
+
+    pub fn settings(&self) -> OidcSettings {
+        OidcSettings {
+            issuer_url: self.issuer.clone(),
+            client_id: Self::CLIENT_ID.to_owned(),
+            client_secret: None,
+            redirect_url: "http://localhost:3000/oidc/callback".to_owned(),
+            scopes: "openid email".to_owned(),
+            role_claim: "roles".to_owned(),
+            default_role: UserProfilesRoles::Employee,
+        }
+    }
+
+    /// Claims of a valid ID token for `email`, `nonce` is taken from the
+    /// authorization url
+    pub fn claims(&self, authorization_url: &str, email: &str) -> serde_json::Value {
+        let nonce = Url::parse(authorization_url)
+            .unwrap()
+            .query_pairs()
+            .find(|(name, _)| name == "nonce")
+            .map(|(_, value)| value.into_owned())
+            .unwrap();
+
+        serde_json::json!({
+            "iss": self.issuer,
+            "sub": Uuid::new_v4().to_string(),
+            "aud": Self::CLIENT_ID,
+            "exp": chrono::Utc::now().timestamp() + 300,
+            "nonce": nonce,
+            "email": email,
+            "email_verified": true,
+        })
+    }
+
+    pub fn sign(&self, claims: &serde_json::Value) -> String {
+        let mut header = Header::new(Algorithm::EdDSA);
+
+        header.kid = Some("test".to_owned());
+
+        jsonwebtoken::encode(&header, claims, &self.key).unwrap()
+    }
+
+    pub fn respond_with(&self, id_token: String) {
+        *self.id_token.lock().unwrap() = id_token;
+    }
+}
+
+/// The `state` parameter of an authorization url
+pub fn authorization_state(authorization_url: &str) -> String {
+    Url::parse(authorization_url)
+        .unwrap()
+        .query_pairs()
+        .find(|(name, _)| name == "state")
+        .map(|(_, value)| value.into_owned())
+        .unwrap()
+}